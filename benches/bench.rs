@@ -32,10 +32,81 @@ fn bench_passing_a_1k_buffer_blocking(b: &mut Bencher) {
     });
 }
 
+/// Writes and reads a block of `size` elements without any thread synchronization.
+fn write_read_block<T: Copy + Default>(b: &mut Bencher, size: usize) {
+    let rb = SpscRb::new(size);
+    let (producer, consumer) = (rb.producer(), rb.consumer());
+    let data = vec![T::default(); size];
+    let mut buf = vec![T::default(); size];
+    // Move the positions to the middle so that every copy wraps around.
+    producer.write(&data[..size / 2]).unwrap();
+    consumer.read(&mut buf[..size / 2]).unwrap();
+    b.iter(|| {
+        assert_eq!(producer.write(&data).unwrap(), size);
+        assert_eq!(consumer.read(&mut buf).unwrap(), size);
+    });
+}
+
+#[bench]
+fn bench_write_read_1k_f32(b: &mut Bencher) {
+    write_read_block::<f32>(b, 1024);
+}
+
+#[bench]
+fn bench_write_read_8k_f32(b: &mut Bencher) {
+    write_read_block::<f32>(b, 8192);
+}
+
+#[bench]
+fn bench_write_read_1k_f64(b: &mut Bencher) {
+    write_read_block::<f64>(b, 1024);
+}
+
+#[bench]
+fn bench_write_read_8k_f64(b: &mut Bencher) {
+    write_read_block::<f64>(b, 8192);
+}
+
+/// Writes and reads a block of `frames` stereo frames as separate channels.
+fn interleave_block<T: Copy + Default>(b: &mut Bencher, frames: usize) {
+    let rb = SpscRb::new(frames * 2);
+    let (producer, consumer) = (rb.producer(), rb.consumer());
+    let (left, right) = (vec![T::default(); frames], vec![T::default(); frames]);
+    let (mut l, mut r) = (vec![T::default(); frames], vec![T::default(); frames]);
+    // Move the positions to the middle so that every copy wraps around.
+    producer.write(&left[..frames / 2]).unwrap();
+    consumer.read(&mut l[..frames / 2]).unwrap();
+    b.iter(|| {
+        assert_eq!(
+            producer.write_interleaved(&[&left, &right]).unwrap(),
+            frames
+        );
+        assert_eq!(
+            consumer.read_deinterleaved(&mut [&mut l, &mut r]).unwrap(),
+            frames
+        );
+    });
+}
+
+#[bench]
+fn bench_interleave_1k_f32(b: &mut Bencher) {
+    interleave_block::<f32>(b, 512);
+}
+
+#[bench]
+fn bench_interleave_8k_f32(b: &mut Bencher) {
+    interleave_block::<f32>(b, 4096);
+}
+
+#[bench]
+fn bench_interleave_8k_f64(b: &mut Bencher) {
+    interleave_block::<f64>(b, 4096);
+}
+
 fn rand_float(rng: &mut XorShiftRng) -> f64 {
     let r = rng.next_u32();
     if r == 0 {
         return 0.0;
     }
-    f64::from(u32::max_value() / (r - i32::max_value() as u32))
+    f64::from(u32::MAX / (r - i32::MAX as u32))
 }
@@ -0,0 +1,26 @@
+#[cfg(feature = "ffi")]
+extern crate cbindgen;
+
+fn main() {
+    #[cfg(feature = "ffi")]
+    generate_header();
+}
+
+/// Writes the header of the C API in `src/ffi.rs` to `include/rb.h`.
+#[cfg(feature = "ffi")]
+fn generate_header() {
+    use std::env;
+    use std::path::Path;
+
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let config = cbindgen::Config::from_file(Path::new(&dir).join("cbindgen.toml"))
+        .expect("cbindgen.toml is valid");
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(Path::new(&dir).join("src/ffi.rs"))
+        .generate()
+        .expect("the C API can be translated")
+        .write_to_file(Path::new(&dir).join("include/rb.h"));
+}
@@ -2,11 +2,11 @@ extern crate rb;
 
 use rb::*;
 use std::thread;
+use std::time::Duration;
 
 fn main() {
     const SIZE: usize = 128;
-    let rb = SpscRb::new(SIZE);
-    let (prod, cons) = (rb.producer(), rb.consumer());
+    let (prod, cons) = SpscRb::new(SIZE).into_split();
 
     // producer thread
     const PERIOD: usize = 16;
@@ -19,11 +19,17 @@ fn main() {
                 .map(|x| x as f32 / (PERIOD / 2) as f32)
         };
         loop {
-            // write a slice of size length 32 (length can be anything smaller than SIZE)
-            let cnt = prod
-                .write_blocking(&saw().skip(skip).take(PERIOD).collect::<Vec<f32>>())
-                .unwrap();
-            skip += cnt % (PERIOD);
+            // reserve up to one period of free slots and write the wave into them in place
+            match prod.grant(PERIOD) {
+                Ok(mut grant) => {
+                    for (slot, x) in grant.iter_mut().zip(saw().skip(skip)) {
+                        *slot = x;
+                    }
+                    skip += grant.len() % (PERIOD);
+                    // dropping the grant publishes the written slots
+                }
+                Err(_) => thread::sleep(Duration::from_millis(1)),
+            }
         }
     });
 
@@ -0,0 +1,205 @@
+#ifndef RB_H
+#define RB_H
+
+/* Generated by cbindgen from src/ffi.rs, don't edit it by hand. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+// Sample ring buffer, created by `rb_f32_new`.
+typedef struct RbF32 RbF32;
+
+// Lock-free byte ring buffer, created by `rb_ringbuffer_create`.
+typedef struct RbRingbuffer RbRingbuffer;
+
+// Byte ring buffer, created by `rb_u8_new`.
+typedef struct RbU8 RbU8;
+
+// Region of a `RbRingbuffer`, like `jack_ringbuffer_data_t`.
+typedef struct RbRingbufferData {
+  // First slot of the region, `NULL` if it is empty.
+  uint8_t *buf;
+  // Number of slots of the region.
+  size_t len;
+} RbRingbufferData;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Creates a buffer for `capacity` bytes.
+//
+// Returns `NULL` if the capacity is zero or too large.
+struct RbU8 *rb_u8_new(size_t capacity);
+
+// Destroys the buffer, does nothing for `NULL`.
+//
+// # Safety
+//
+// `rb` was returned by `rb_u8_new` and is not used afterwards.
+void rb_u8_free(struct RbU8 *rb);
+
+// Writes as many of the `len` bytes at `data` as there are free slots, never blocks.
+//
+// Returns the number of written bytes.
+//
+// # Safety
+//
+// `rb` is a live buffer and `data` points to `len` bytes.
+size_t rb_u8_write(const struct RbU8 *rb, const uint8_t *data, size_t len);
+
+// Reads up to `len` bytes into `data`, never blocks.
+//
+// Returns the number of read bytes.
+//
+// # Safety
+//
+// `rb` is a live buffer and `data` points to `len` writable bytes.
+size_t rb_u8_read(const struct RbU8 *rb, uint8_t *data, size_t len);
+
+// Returns the number of pending bytes.
+//
+// # Safety
+//
+// `rb` is a live buffer.
+size_t rb_u8_count(const struct RbU8 *rb);
+
+// Returns the number of bytes that can be written until the buffer is full.
+//
+// # Safety
+//
+// `rb` is a live buffer.
+size_t rb_u8_slots_free(const struct RbU8 *rb);
+
+// Creates a buffer for `capacity` samples.
+//
+// Returns `NULL` if the capacity is zero or too large.
+struct RbF32 *rb_f32_new(size_t capacity);
+
+// Destroys the buffer, does nothing for `NULL`.
+//
+// # Safety
+//
+// `rb` was returned by `rb_f32_new` and is not used afterwards.
+void rb_f32_free(struct RbF32 *rb);
+
+// Writes as many of the `len` samples at `data` as there are free slots, never blocks.
+//
+// Returns the number of written samples.
+//
+// # Safety
+//
+// `rb` is a live buffer and `data` points to `len` samples.
+size_t rb_f32_write(const struct RbF32 *rb, const float *data, size_t len);
+
+// Reads up to `len` samples into `data`, never blocks.
+//
+// Returns the number of read samples.
+//
+// # Safety
+//
+// `rb` is a live buffer and `data` points to `len` writable samples.
+size_t rb_f32_read(const struct RbF32 *rb, float *data, size_t len);
+
+// Returns the number of pending samples.
+//
+// # Safety
+//
+// `rb` is a live buffer.
+size_t rb_f32_count(const struct RbF32 *rb);
+
+// Returns the number of samples that can be written until the buffer is full.
+//
+// # Safety
+//
+// `rb` is a live buffer.
+size_t rb_f32_slots_free(const struct RbF32 *rb);
+
+// Creates a buffer for `capacity` bytes, like `jack_ringbuffer_create`.
+//
+// Returns `NULL` if the capacity is zero or too large.
+struct RbRingbuffer *rb_ringbuffer_create(size_t capacity);
+
+// Destroys the buffer, does nothing for `NULL`.
+//
+// # Safety
+//
+// `rb` was returned by `rb_ringbuffer_create` and is not used afterwards.
+void rb_ringbuffer_free(struct RbRingbuffer *rb);
+
+// Returns the number of bytes that can be written.
+//
+// # Safety
+//
+// `rb` is a live buffer, only the writing thread calls the writing functions.
+size_t rb_ringbuffer_write_space(const struct RbRingbuffer *rb);
+
+// Returns the number of bytes that can be read.
+//
+// # Safety
+//
+// `rb` is a live buffer, only the reading thread calls the reading functions.
+size_t rb_ringbuffer_read_space(const struct RbRingbuffer *rb);
+
+// Writes as many of the `cnt` bytes at `src` as there is space for.
+//
+// Returns the number of written bytes.
+//
+// # Safety
+//
+// See `rb_ringbuffer_write_space`, `src` points to `cnt` bytes.
+size_t rb_ringbuffer_write(struct RbRingbuffer *rb, const uint8_t *src, size_t cnt);
+
+// Reads up to `cnt` bytes into `dest`.
+//
+// Returns the number of read bytes.
+//
+// # Safety
+//
+// See `rb_ringbuffer_read_space`, `dest` points to `cnt` writable bytes.
+size_t rb_ringbuffer_read(struct RbRingbuffer *rb, uint8_t *dest, size_t cnt);
+
+// Works analog to `rb_ringbuffer_read` but keeps the bytes pending.
+//
+// # Safety
+//
+// See `rb_ringbuffer_read`.
+size_t rb_ringbuffer_peek(struct RbRingbuffer *rb, uint8_t *dest, size_t cnt);
+
+// Stores the two regions of free slots in `vec`, the second one is only non-empty if
+// they wrap around.
+//
+// # Safety
+//
+// See `rb_ringbuffer_write_space`, `vec` points to two regions. The regions are valid
+// until the next call of `rb_ringbuffer_write_advance`.
+void rb_ringbuffer_get_write_vector(struct RbRingbuffer *rb, struct RbRingbufferData *vec);
+
+// Publishes the next `cnt` free slots.
+//
+// # Safety
+//
+// See `rb_ringbuffer_write_space`.
+void rb_ringbuffer_write_advance(struct RbRingbuffer *rb, size_t cnt);
+
+// Stores the two regions of pending bytes in `vec`, the second one is only non-empty
+// if they wrap around.
+//
+// # Safety
+//
+// See `rb_ringbuffer_read_space`, `vec` points to two regions. The regions are valid
+// until the next call of `rb_ringbuffer_read_advance`.
+void rb_ringbuffer_get_read_vector(struct RbRingbuffer *rb, struct RbRingbufferData *vec);
+
+// Consumes the next `cnt` pending bytes.
+//
+// # Safety
+//
+// See `rb_ringbuffer_read_space`.
+void rb_ringbuffer_read_advance(struct RbRingbuffer *rb, size_t cnt);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* RB_H */
@@ -0,0 +1,99 @@
+use alloc::alloc::{self, Layout};
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, NonNull};
+use core::slice;
+
+/// Slots whose first one starts at an address that is a multiple of a given alignment,
+/// e.g. 4 KiB to start at a page or 2 MiB for a transparent huge page, which reduces the
+/// TLB misses when copying from or into very large buffers. See `SpscRb::new_aligned`.
+///
+/// All slots are initialized with the default value.
+///
+/// ```
+/// use rb::*;
+///
+/// let slots = AlignedStorage::<u8>::new(4096, 4096);
+/// assert_eq!(slots.as_ptr() as usize % 4096, 0);
+/// let rb = SpscRb::from_storage(slots);
+/// assert_eq!(rb.capacity(), 4095);
+/// ```
+pub struct AlignedStorage<T> {
+    ptr: NonNull<T>,
+    len: usize,
+    layout: Layout,
+    marker: PhantomData<T>,
+}
+
+// The slots are owned like the ones of a `Box<[T]>`.
+unsafe impl<T: Send> Send for AlignedStorage<T> {}
+unsafe impl<T: Sync> Sync for AlignedStorage<T> {}
+
+impl<T: Default> AlignedStorage<T> {
+    /// Allocates `len` slots that start at a multiple of `align`, which is raised to the
+    /// alignment of `T` if it is smaller.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is zero, `align` is not a power of two or the slots are too large.
+    pub fn new(len: usize, align: usize) -> Self {
+        assert!(len > 0, "the storage must provide at least one slot");
+        let layout = Layout::array::<T>(len)
+            .and_then(|layout| layout.align_to(align))
+            .expect("the alignment must be a power of two and the slots must not be too large");
+        let ptr = if layout.size() == 0 {
+            // Zero-sized values need no memory, any aligned address will do.
+            NonNull::new(layout.align() as *mut T).expect("the alignment is not zero")
+        } else {
+            // Safety: the size of the layout is not zero.
+            let ptr = unsafe { alloc::alloc(layout) } as *mut T;
+            NonNull::new(ptr).unwrap_or_else(|| alloc::handle_alloc_error(layout))
+        };
+        for i in 0..len {
+            // Safety: the slot is in bounds of the allocation and not initialized yet.
+            // A panicking `T::default` leaks the allocation but never drops a slot.
+            unsafe { ptr.as_ptr().add(i).write(T::default()) };
+        }
+        AlignedStorage {
+            ptr,
+            len,
+            layout,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Deref for AlignedStorage<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        // Safety: all `len` slots were initialized by `new`.
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T> DerefMut for AlignedStorage<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        // Safety: see `deref`, the slots are borrowed exclusively through `self`.
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T> AsRef<[T]> for AlignedStorage<T> {
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T> Drop for AlignedStorage<T> {
+    fn drop(&mut self) {
+        // Safety: the slots are initialized and dropped exactly once, the memory was
+        // allocated with `layout` unless it is zero-sized.
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.ptr.as_ptr(), self.len));
+            if self.layout.size() != 0 {
+                alloc::dealloc(self.ptr.as_ptr() as *mut u8, self.layout);
+            }
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use std::future::Future;
+#[cfg(feature = "tokio")]
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+
+use atomic_waker::AtomicWaker;
+
+use spsc::Storage;
+use wait::Signal;
+use {Consumer, Producer, RbConsumer, RbError, Result};
+
+/// Producer whose writes wait for free slots as futures instead of blocking the thread.
+///
+/// The other side of the buffer doesn't need to be async, e.g. a blocking audio callback can
+/// read what an async task writes. The futures work with any executor, each handle stores
+/// the waker of the task that waits for it in a lock-free slot.
+///
+/// ```edition2018
+/// use std::thread;
+/// use futures_executor::block_on;
+/// use rb::*;
+///
+/// let (prod, cons) = SpscRb::new(4).into_split();
+/// let mut prod = prod.into_async();
+/// let reader = thread::spawn(move || {
+///     let mut data = Vec::new();
+///     let mut buf = [0; 4];
+///     while let Some(cnt) = cons.read_blocking(&mut buf) {
+///         data.extend_from_slice(&buf[..cnt]);
+///     }
+///     data
+/// });
+/// block_on(async move {
+///     let data = (0..16).collect::<Vec<_>>();
+///     let mut written = 0;
+///     while written < data.len() {
+///         written += prod.write(&data[written..]).await.unwrap();
+///     }
+/// });
+/// assert_eq!(reader.join().unwrap(), (0..16).collect::<Vec<_>>());
+/// ```
+pub struct AsyncProducer<T, S = Vec<T>> {
+    producer: Producer<T, S>,
+    waker: WakerSlot,
+}
+
+/// Consumer whose reads wait for pending values as futures instead of blocking the thread.
+///
+/// The other side of the buffer doesn't need to be async, e.g. an async task can read what
+/// a blocking audio callback writes. The futures work with any executor.
+pub struct AsyncConsumer<T, S = Vec<T>> {
+    consumer: Consumer<T, S>,
+    waker: WakerSlot,
+}
+
+/// Waker slot of an async handle, registered at the signal of the side it waits for
+/// and removed again once the handle is dropped.
+struct WakerSlot {
+    signal: Arc<Signal>,
+    waker: Arc<AtomicWaker>,
+}
+
+impl WakerSlot {
+    fn new(signal: &Arc<Signal>) -> Self {
+        let waker = Arc::new(AtomicWaker::new());
+        signal.add_waker(waker.clone());
+        WakerSlot {
+            signal: signal.clone(),
+            waker,
+        }
+    }
+
+    /// Registers the waker of the current task, the caller has to check its condition
+    /// again afterwards since a notification right before the registration is missed.
+    fn register(&self, waker: &Waker) {
+        self.waker.register(waker);
+    }
+}
+
+impl Drop for WakerSlot {
+    fn drop(&mut self) {
+        self.signal.remove_waker(&self.waker);
+    }
+}
+
+impl<T, S> Producer<T, S> {
+    /// Turns the producer into one with async writes.
+    pub fn into_async(self) -> AsyncProducer<T, S> {
+        AsyncProducer {
+            waker: WakerSlot::new(&self.slots_free),
+            producer: self,
+        }
+    }
+}
+
+impl<T, S> Consumer<T, S> {
+    /// Turns the consumer into one with async reads.
+    pub fn into_async(self) -> AsyncConsumer<T, S> {
+        AsyncConsumer {
+            waker: WakerSlot::new(&self.data_available),
+            consumer: self,
+        }
+    }
+}
+
+impl<T, S> AsyncProducer<T, S> {
+    /// Returns the underlying producer, e.g. for non-blocking writes.
+    pub fn get_ref(&self) -> &Producer<T, S> {
+        &self.producer
+    }
+
+    /// Turns the producer back into a blocking one.
+    pub fn into_inner(self) -> Producer<T, S> {
+        let AsyncProducer { producer, .. } = self;
+        producer
+    }
+}
+
+impl<T: Clone, S: Storage<T>> AsyncProducer<T, S> {
+    /// Writes as many values of `data` as there are free slots,
+    /// waits until at least one slot is free.
+    ///
+    /// The future resolves to the number of written values or an error.
+    /// A handle has a single waker slot, thus only one future at a time can wait for it.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Disconnected` all consumers were dropped
+    pub fn write<'a>(&'a mut self, data: &'a [T]) -> WriteFuture<'a, T, S> {
+        WriteFuture {
+            producer: self,
+            data,
+        }
+    }
+
+    /// Writes `data` if there are free slots, otherwise the task is woken up once there are.
+    fn poll_write_values(&self, cx: &mut Context<'_>, data: &[T]) -> Poll<Result<usize>> {
+        match self.try_write(data) {
+            Err(RbError::Full) => {}
+            result => return Poll::Ready(result),
+        }
+        self.waker.register(cx.waker());
+        // The consumer might have read before the waker was registered.
+        match self.try_write(data) {
+            Err(RbError::Full) => Poll::Pending,
+            result => Poll::Ready(result),
+        }
+    }
+
+    /// Like the blocking writes, the future gives up once the consumers are disconnected.
+    fn try_write(&self, data: &[T]) -> Result<usize> {
+        if self.producer.is_disconnected() {
+            return Err(RbError::Disconnected);
+        }
+        self.producer.write_partial(data)
+    }
+}
+
+impl<T, S> AsyncConsumer<T, S> {
+    /// Returns the underlying consumer, e.g. for non-blocking reads.
+    pub fn get_ref(&self) -> &Consumer<T, S> {
+        &self.consumer
+    }
+
+    /// Turns the consumer back into a blocking one.
+    pub fn into_inner(self) -> Consumer<T, S> {
+        let AsyncConsumer { consumer, .. } = self;
+        consumer
+    }
+}
+
+impl<T: Clone, S: Storage<T>> AsyncConsumer<T, S> {
+    /// Fills `data` with as many values as are pending,
+    /// waits until at least one value is pending.
+    ///
+    /// The future resolves to the number of read values or an error.
+    /// A handle has a single waker slot, thus only one future at a time can wait for it.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Disconnected` all producers were dropped and nothing is left to read
+    pub fn read<'a>(&'a mut self, data: &'a mut [T]) -> ReadFuture<'a, T, S> {
+        ReadFuture {
+            consumer: self,
+            data,
+        }
+    }
+
+    /// Reads into `data` if values are pending, otherwise the task is woken up once there are.
+    fn poll_read_values(&self, cx: &mut Context<'_>, data: &mut [T]) -> Poll<Result<usize>> {
+        match self.consumer.read(data) {
+            Err(RbError::Empty) => {}
+            result => return Poll::Ready(result),
+        }
+        self.waker.register(cx.waker());
+        // The producer might have written before the waker was registered.
+        match self.consumer.read(data) {
+            Err(RbError::Empty) => Poll::Pending,
+            result => Poll::Ready(result),
+        }
+    }
+}
+
+/// Future returned by `AsyncProducer::write`.
+pub struct WriteFuture<'a, T, S = Vec<T>> {
+    producer: &'a AsyncProducer<T, S>,
+    data: &'a [T],
+}
+
+impl<'a, T: Clone, S: Storage<T>> Future for WriteFuture<'a, T, S> {
+    type Output = Result<usize>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.producer.poll_write_values(cx, self.data)
+    }
+}
+
+/// Future returned by `AsyncConsumer::read`.
+pub struct ReadFuture<'a, T, S = Vec<T>> {
+    consumer: &'a AsyncConsumer<T, S>,
+    data: &'a mut [T],
+}
+
+impl<'a, T: Clone, S: Storage<T>> Future for ReadFuture<'a, T, S> {
+    type Output = Result<usize>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        this.consumer.poll_read_values(cx, this.data)
+    }
+}
+
+/// An in-memory pipe between async tasks, or an async task and a blocking thread.
+/// Reads return end of file once all producers were dropped and nothing is left to read.
+#[cfg(feature = "tokio")]
+impl<S: Storage<u8>> tokio::io::AsyncRead for AsyncConsumer<u8, S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        if buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+        match self.poll_read_values(cx, buf.initialize_unfilled()) {
+            Poll::Ready(Ok(cnt)) => buf.advance(cnt),
+            // Reads only fail once the producers disconnected.
+            Poll::Ready(Err(_)) => {}
+            Poll::Pending => return Poll::Pending,
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Writes fail with `io::ErrorKind::BrokenPipe` once all consumers were dropped.
+#[cfg(feature = "tokio")]
+impl<S: Storage<u8>> tokio::io::AsyncWrite for AsyncProducer<u8, S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        // Writes only fail once the consumers disconnected.
+        self.poll_write_values(cx, buf)
+            .map_err(|_| io::ErrorKind::BrokenPipe.into())
+    }
+
+    /// Written bytes are visible to the consumer right away, thus there is nothing to flush.
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    /// Closes the buffer, the consumer reads the pending bytes followed by the end of file.
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.producer.close();
+        Poll::Ready(Ok(()))
+    }
+}
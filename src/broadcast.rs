@@ -0,0 +1,465 @@
+use std::cmp;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use copy::copy_from_ring;
+use sync::{Condvar, Mutex, MutexGuard};
+use {RbConsumer, RbError, RbInspector, RbProducer, Result, Storage};
+
+struct State<T> {
+    // Grows up to `size` elements, see `Storage` for `Vec`.
+    buf: Vec<T>,
+    size: usize,
+    // Total number of elements written, the positions are never wrapped
+    // to distinguish between the read positions of the consumers.
+    write_pos: u64,
+    // Read position of each registered consumer, indexed by `BroadcastConsumer::id`.
+    read_pos: Vec<Option<u64>>,
+    // Number of live producers and whether the last one was dropped.
+    producers: usize,
+    producers_dropped: bool,
+    // Set by `close`, disconnects producer and consumers for good.
+    closed: bool,
+}
+
+impl<T> State<T> {
+    fn index(&self, pos: u64) -> usize {
+        (pos % self.size as u64) as usize
+    }
+
+    /// Number of elements that the slowest consumer has not read yet.
+    fn count(&self) -> usize {
+        self.read_pos
+            .iter()
+            .filter_map(|pos| pos.map(|pos| (self.write_pos - pos) as usize))
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn slots_free(&self) -> usize {
+        self.size - self.count()
+    }
+
+    /// Registers a consumer starting at `read_pos` and returns its id.
+    fn register(&mut self, read_pos: u64) -> usize {
+        match self.read_pos.iter().position(Option::is_none) {
+            Some(id) => {
+                self.read_pos[id] = Some(read_pos);
+                id
+            }
+            None => {
+                self.read_pos.push(Some(read_pos));
+                self.read_pos.len() - 1
+            }
+        }
+    }
+
+    fn pending(&self, id: usize) -> usize {
+        let read_pos = self.read_pos[id].expect("consumer is registered");
+        (self.write_pos - read_pos) as usize
+    }
+
+    fn producers_disconnected(&self) -> bool {
+        self.producers_dropped || self.closed
+    }
+
+    /// Returns the error for a consumer without pending values.
+    fn empty_error(&self) -> RbError {
+        if self.producers_disconnected() {
+            RbError::Disconnected
+        } else {
+            RbError::Empty
+        }
+    }
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    slots_free: Condvar,
+    data_available: Condvar,
+}
+
+impl<T> Shared<T> {
+    /// Closes the buffer and wakes up all blocked threads.
+    fn close(&self) {
+        self.state.lock().closed = true;
+        self.slots_free.notify_all();
+        self.data_available.notify_all();
+    }
+}
+
+/// Waits on `condvar` until `ready` returns true or the `deadline` is reached.
+fn wait_until<'a, T, F>(
+    state: &'a Mutex<State<T>>,
+    condvar: &Condvar,
+    mut guard: MutexGuard<'a, State<T>>,
+    deadline: Option<Instant>,
+    ready: F,
+) -> Result<MutexGuard<'a, State<T>>>
+where
+    F: Fn(&State<T>) -> bool,
+{
+    while !ready(&guard) {
+        guard = match deadline {
+            None => condvar.wait(state, guard),
+            Some(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    return Err(RbError::TimedOut);
+                }
+                condvar.wait_timeout(state, guard, deadline - now).0
+            }
+        };
+    }
+    Ok(guard)
+}
+
+/// A *thread-safe* Single-Producer-Multi-Consumer RingBuffer where every consumer sees every element.
+///
+/// Each consumer has its own read position, e.g. one consumer writes the data to disk while
+/// another one feeds a visualizer. The producer only overwrites slots that were read by all
+/// consumers, i.e. the slowest consumer determines how many slots are free.
+/// A consumer only sees the elements that were written after it was created.
+///
+/// ```
+/// use rb::*;
+///
+/// let rb = BroadcastRb::new(16);
+/// let (disk, scope) = (rb.consumer(), rb.consumer());
+/// let prod = rb.producer();
+/// prod.write(&[1, 2, 3]).unwrap();
+/// let mut buf = [0; 3];
+/// assert_eq!(disk.read(&mut buf).unwrap(), 3);
+/// assert_eq!(rb.count(), 3);
+/// assert_eq!(scope.read(&mut buf).unwrap(), 3);
+/// assert!(rb.is_empty());
+/// ```
+pub struct BroadcastRb<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T: Clone> BroadcastRb<T> {
+    pub fn new(size: usize) -> Self {
+        BroadcastRb {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State {
+                    buf: Vec::with_capacity(size),
+                    size,
+                    write_pos: 0,
+                    read_pos: Vec::new(),
+                    producers: 0,
+                    producers_dropped: false,
+                    closed: false,
+                }),
+                slots_free: Condvar::new(),
+                data_available: Condvar::new(),
+            }),
+        }
+    }
+
+    /// Closes the buffer, see `SpscRb::close`.
+    pub fn close(&self) {
+        self.shared.close();
+    }
+
+    /// Creates the *producer* view inside the buffer.
+    pub fn producer(&self) -> BroadcastProducer<T> {
+        let mut state = self.shared.state.lock();
+        state.producers += 1;
+        state.producers_dropped = false;
+        BroadcastProducer {
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Registers a new *consumer* view that reads every element written from now on.
+    pub fn consumer(&self) -> BroadcastConsumer<T> {
+        let mut state = self.shared.state.lock();
+        let write_pos = state.write_pos;
+        BroadcastConsumer {
+            id: state.register(write_pos),
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> RbInspector for BroadcastRb<T> {
+    fn is_empty(&self) -> bool {
+        self.count() == 0
+    }
+    fn is_full(&self) -> bool {
+        self.slots_free() == 0
+    }
+    fn capacity(&self) -> usize {
+        self.shared.state.lock().size
+    }
+    fn slots_free(&self) -> usize {
+        self.shared.state.lock().slots_free()
+    }
+    /// Returns the number of values that the slowest consumer has not read yet.
+    fn count(&self) -> usize {
+        self.shared.state.lock().count()
+    }
+}
+
+/// Producer view into a `BroadcastRb`.
+pub struct BroadcastProducer<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> BroadcastProducer<T> {
+    /// Closes the buffer, see `SpscRb::close`.
+    pub fn close(&self) {
+        self.shared.close();
+    }
+}
+
+impl<T: Clone> BroadcastProducer<T> {
+    fn write_locked(&self, state: &mut State<T>, data: &[T]) -> usize {
+        let cnt = cmp::min(data.len(), state.slots_free());
+        let wr_pos = state.index(state.write_pos);
+        let size = state.size;
+        state.buf.store(size, wr_pos, &data[..cnt]);
+        state.write_pos += cnt as u64;
+        self.shared.data_available.notify_all();
+        cnt
+    }
+
+    fn write_until(&self, data: &[T], deadline: Option<Instant>) -> Result<Option<usize>> {
+        if data.is_empty() {
+            return Ok(None);
+        }
+        let guard = self.shared.state.lock();
+        let mut state = wait_until(
+            &self.shared.state,
+            &self.shared.slots_free,
+            guard,
+            deadline,
+            |state| state.slots_free() > 0 || state.closed,
+        )?;
+        if state.closed {
+            return Err(RbError::Disconnected);
+        }
+        Ok(Some(self.write_locked(&mut state, data)))
+    }
+}
+
+/// Wakes up blocked consumers once the last producer is dropped.
+impl<T> Drop for BroadcastProducer<T> {
+    fn drop(&mut self) {
+        {
+            let mut state = self.shared.state.lock();
+            state.producers -= 1;
+            state.producers_dropped = state.producers == 0;
+        }
+        self.shared.data_available.notify_all();
+    }
+}
+
+impl<T: Clone> RbProducer<T> for BroadcastProducer<T> {
+    fn write(&self, data: &[T]) -> Result<usize> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        let mut state = self.shared.state.lock();
+        if state.closed {
+            return Err(RbError::Disconnected);
+        }
+        if state.slots_free() == 0 {
+            return Err(RbError::Full);
+        }
+        Ok(self.write_locked(&mut state, data))
+    }
+
+    fn write_exact(&self, data: &[T]) -> Result<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        let mut state = self.shared.state.lock();
+        if state.closed {
+            return Err(RbError::Disconnected);
+        }
+        if state.slots_free() < data.len() {
+            return Err(RbError::Full);
+        }
+        self.write_locked(&mut state, data);
+        Ok(())
+    }
+
+    fn write_blocking(&self, data: &[T]) -> Option<usize> {
+        match self.write_until(data, None) {
+            Ok(cnt) => cnt,
+            Err(RbError::Disconnected) => None,
+            Err(_) => panic!("Max duration should not time out"),
+        }
+    }
+
+    fn write_blocking_timeout(&self, data: &[T], timeout: Duration) -> Result<Option<usize>> {
+        self.write_until(data, Instant::now().checked_add(timeout))
+    }
+
+    fn write_blocking_deadline(&self, data: &[T], deadline: Instant) -> Result<Option<usize>> {
+        self.write_until(data, Some(deadline))
+    }
+}
+
+/// Consumer view into a `BroadcastRb` with its own read position.
+///
+/// Dropping the consumer unregisters it, so it does no longer hold back the producer.
+pub struct BroadcastConsumer<T> {
+    shared: Arc<Shared<T>>,
+    id: usize,
+}
+
+impl<T> Clone for BroadcastConsumer<T> {
+    /// Registers a new consumer that starts at the same read position as this one.
+    fn clone(&self) -> Self {
+        let mut state = self.shared.state.lock();
+        let read_pos = state.read_pos[self.id].expect("consumer is registered");
+        BroadcastConsumer {
+            id: state.register(read_pos),
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for BroadcastConsumer<T> {
+    fn drop(&mut self) {
+        self.shared.state.lock().read_pos[self.id] = None;
+        self.shared.slots_free.notify_all();
+    }
+}
+
+impl<T> BroadcastConsumer<T> {
+    /// Closes the buffer, see `SpscRb::close`.
+    pub fn close(&self) {
+        self.shared.close();
+    }
+}
+
+impl<T: Clone> BroadcastConsumer<T> {
+    /// Returns the number of values this consumer has not read yet.
+    pub fn count(&self) -> usize {
+        self.shared.state.lock().pending(self.id)
+    }
+
+    fn advance(&self, state: &mut State<T>, cnt: usize) {
+        if let Some(ref mut pos) = state.read_pos[self.id] {
+            *pos += cnt as u64;
+        }
+        self.shared.slots_free.notify_all();
+    }
+
+    fn copy_pending(&self, state: &State<T>, data: &mut [T]) -> usize {
+        let cnt = cmp::min(data.len(), state.pending(self.id));
+        let re_pos = state.index(state.read_pos[self.id].expect("consumer is registered"));
+        copy_from_ring(&state.buf, re_pos, &mut data[..cnt]);
+        cnt
+    }
+
+    /// Waits until at least `min` values are pending and reads them.
+    fn read_until(
+        &self,
+        data: &mut [T],
+        min: usize,
+        deadline: Option<Instant>,
+    ) -> Result<Option<usize>> {
+        if data.is_empty() {
+            return Ok(None);
+        }
+        let id = self.id;
+        let guard = self.shared.state.lock();
+        let min = cmp::min(cmp::max(min, 1), cmp::min(data.len(), guard.size));
+        let mut state = wait_until(
+            &self.shared.state,
+            &self.shared.data_available,
+            guard,
+            deadline,
+            |state| state.pending(id) >= min || state.producers_disconnected(),
+        )?;
+        if state.pending(id) == 0 {
+            return Err(RbError::Disconnected);
+        }
+        let cnt = self.copy_pending(&state, data);
+        self.advance(&mut state, cnt);
+        Ok(Some(cnt))
+    }
+}
+
+impl<T: Clone> RbConsumer<T> for BroadcastConsumer<T> {
+    fn skip_pending(&self) -> Result<usize> {
+        let mut state = self.shared.state.lock();
+        let cnt = state.pending(self.id);
+        if cnt == 0 {
+            return Err(state.empty_error());
+        }
+        self.advance(&mut state, cnt);
+        Ok(cnt)
+    }
+
+    fn skip(&self, cnt: usize) -> Result<usize> {
+        let mut state = self.shared.state.lock();
+        let pending = state.pending(self.id);
+        if pending == 0 {
+            return Err(state.empty_error());
+        }
+        let cnt = cmp::min(cnt, pending);
+        self.advance(&mut state, cnt);
+        Ok(cnt)
+    }
+
+    fn get(&self, data: &mut [T]) -> Result<usize> {
+        self.peek(0, data)
+    }
+
+    fn peek(&self, offset: usize, data: &mut [T]) -> Result<usize> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        let state = self.shared.state.lock();
+        let cnt = cmp::min(data.len(), state.pending(self.id).saturating_sub(offset));
+        if cnt == 0 {
+            return Err(state.empty_error());
+        }
+        let read_pos = state.read_pos[self.id].expect("consumer is registered");
+        copy_from_ring(
+            &state.buf,
+            state.index(read_pos + offset as u64),
+            &mut data[..cnt],
+        );
+        Ok(cnt)
+    }
+
+    fn read(&self, data: &mut [T]) -> Result<usize> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        let mut state = self.shared.state.lock();
+        if state.pending(self.id) == 0 {
+            return Err(state.empty_error());
+        }
+        let cnt = self.copy_pending(&state, data);
+        self.advance(&mut state, cnt);
+        Ok(cnt)
+    }
+
+    fn read_blocking(&self, data: &mut [T]) -> Option<usize> {
+        self.read_at_least(data, 1)
+    }
+
+    fn read_blocking_timeout(&self, data: &mut [T], timeout: Duration) -> Result<Option<usize>> {
+        self.read_until(data, 1, Instant::now().checked_add(timeout))
+    }
+
+    fn read_blocking_deadline(&self, data: &mut [T], deadline: Instant) -> Result<Option<usize>> {
+        self.read_until(data, 1, Some(deadline))
+    }
+
+    fn read_at_least(&self, data: &mut [T], min: usize) -> Option<usize> {
+        match self.read_until(data, min, None) {
+            Ok(cnt) => cnt,
+            Err(RbError::Disconnected) => None,
+            Err(_) => panic!("Max duration shouldn't time out"),
+        }
+    }
+}
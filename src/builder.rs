@@ -0,0 +1,128 @@
+use {OverflowPolicy, RbHooks, SpscRb, WaitStrategy};
+
+/// Builder for a `SpscRb`, created by `SpscRb::builder`.
+///
+/// ```
+/// use rb::*;
+///
+/// let rb = SpscRb::builder()
+///     .capacity(4)
+///     .fill(0.5f32)
+///     .overflow_policy(OverflowPolicy::DropNewest)
+///     .wait_strategy(WaitStrategy::Spin)
+///     .build();
+/// let (prod, cons) = (rb.producer(), rb.consumer());
+/// assert_eq!(prod.write(&[1.0; 6]).unwrap(), 6);
+/// assert_eq!(cons.read_vec(8), vec![1.0; 4]);
+/// ```
+pub struct SpscRbBuilder<T> {
+    capacity: Option<usize>,
+    fill: Option<T>,
+    overflow_policy: OverflowPolicy,
+    wait_strategy: WaitStrategy,
+    stats: bool,
+    histogram: Option<usize>,
+    latency: bool,
+    hooks: Option<Box<dyn RbHooks>>,
+}
+
+impl<T> Default for SpscRbBuilder<T> {
+    fn default() -> Self {
+        SpscRbBuilder {
+            capacity: None,
+            fill: None,
+            overflow_policy: OverflowPolicy::default(),
+            wait_strategy: WaitStrategy::default(),
+            stats: false,
+            histogram: None,
+            latency: false,
+            hooks: None,
+        }
+    }
+}
+
+impl<T: Clone> SpscRbBuilder<T> {
+    /// Sets the number of values that the buffer can hold, which is mandatory.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Initializes all slots with `value`, see `SpscRb::new_with`.
+    pub fn fill(mut self, value: T) -> Self {
+        self.fill = Some(value);
+        self
+    }
+
+    /// Sets how `write` handles a full buffer, see `SpscRb::with_overflow_policy`.
+    pub fn overflow_policy(mut self, overflow_policy: OverflowPolicy) -> Self {
+        self.overflow_policy = overflow_policy;
+        self
+    }
+
+    /// Overwrites the oldest pending values on `write`, short for
+    /// `overflow_policy(OverflowPolicy::OverwriteOldest)`.
+    pub fn overwrite_oldest(self) -> Self {
+        self.overflow_policy(OverflowPolicy::OverwriteOldest)
+    }
+
+    /// Sets how the blocking calls wait, see `SpscRb::with_wait_strategy`.
+    pub fn wait_strategy(mut self, wait_strategy: WaitStrategy) -> Self {
+        self.wait_strategy = wait_strategy;
+        self
+    }
+
+    /// Enables the statistics, see `SpscRb::with_stats`.
+    pub fn with_stats(mut self) -> Self {
+        self.stats = true;
+        self
+    }
+
+    /// Enables the histogram of the number of pending values, see `SpscRb::with_histogram`.
+    pub fn with_histogram(mut self, bins: usize) -> Self {
+        self.histogram = Some(bins);
+        self
+    }
+
+    /// Measures how long values spend in the buffer, see `SpscRb::with_latency`.
+    pub fn with_latency(mut self) -> Self {
+        self.latency = true;
+        self
+    }
+
+    /// Installs hooks for the events of the buffer, see `SpscRb::with_hooks`.
+    pub fn with_hooks<H: RbHooks + 'static>(mut self, hooks: H) -> Self {
+        self.hooks = Some(Box::new(hooks));
+        self
+    }
+
+    /// Creates the buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity was not set or the histogram has zero bins.
+    pub fn build(self) -> SpscRb<T> {
+        let capacity = self
+            .capacity
+            .expect("the capacity of the buffer must be set");
+        let mut rb = match self.fill {
+            Some(value) => SpscRb::new_with(capacity, value),
+            None => SpscRb::new(capacity),
+        }
+        .with_overflow_policy(self.overflow_policy)
+        .with_wait_strategy(self.wait_strategy);
+        if self.stats {
+            rb = rb.with_stats();
+        }
+        if let Some(bins) = self.histogram {
+            rb = rb.with_histogram(bins);
+        }
+        if self.latency {
+            rb = rb.with_latency();
+        }
+        if let Some(hooks) = self.hooks {
+            rb = rb.with_boxed_hooks(hooks);
+        }
+        rb
+    }
+}
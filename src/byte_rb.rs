@@ -0,0 +1,259 @@
+use std::cell::{Cell, UnsafeCell};
+use std::cmp;
+use std::marker::PhantomData;
+use std::slice;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use CapacityError;
+
+/// A lock-free byte ring buffer with the semantics of `jack_ringbuffer_t` and PortAudio's
+/// `PaUtilRingBuffer`, to port C audio code that uses them step by step. Its C API is
+/// part of `rb::ffi`.
+///
+/// The buffer is split into a writer and a reader, which can be moved to their threads.
+/// Both query the available space and access the slots either by copying or in place
+/// through two slices, the second one is only non-empty if the region wraps around the
+/// end of the buffer. Unlike `jack_ringbuffer_t`, the size is not rounded up to a power
+/// of two.
+///
+/// | JACK | PortAudio | `ByteRb` |
+/// |---|---|---|
+/// | `jack_ringbuffer_write_space` | `PaUtil_GetRingBufferWriteAvailable` | `write_space` |
+/// | `jack_ringbuffer_read_space` | `PaUtil_GetRingBufferReadAvailable` | `read_space` |
+/// | `jack_ringbuffer_write` | `PaUtil_WriteRingBuffer` | `write` |
+/// | `jack_ringbuffer_read` | `PaUtil_ReadRingBuffer` | `read` |
+/// | `jack_ringbuffer_peek` | | `peek` |
+/// | `jack_ringbuffer_get_write_vector` | `PaUtil_GetRingBufferWriteRegions` | `write_vector` |
+/// | `jack_ringbuffer_write_advance` | `PaUtil_AdvanceRingBufferWriteIndex` | `write_advance` |
+/// | `jack_ringbuffer_get_read_vector` | `PaUtil_GetRingBufferReadRegions` | `read_vector` |
+/// | `jack_ringbuffer_read_advance` | `PaUtil_AdvanceRingBufferReadIndex` | `read_advance` |
+///
+/// ```
+/// use rb::*;
+///
+/// let (mut writer, mut reader) = ByteRb::new(8).split();
+/// let [head, _] = writer.write_vector();
+/// head[..3].copy_from_slice(b"abc");
+/// writer.write_advance(3);
+/// assert_eq!(reader.read_space(), 3);
+/// let [head, tail] = reader.read_vector();
+/// assert_eq!((head, tail), (&b"abc"[..], &b""[..]));
+/// reader.read_advance(3);
+/// assert_eq!(writer.write_space(), 8);
+/// ```
+pub struct ByteRb {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    buf: Box<[UnsafeCell<u8>]>,
+    read_pos: AtomicUsize,
+    write_pos: AtomicUsize,
+}
+
+// The slots between the read and the write position are only accessed by the reader,
+// all others only by the writer.
+unsafe impl Sync for Inner {}
+
+impl ByteRb {
+    /// Creates a buffer for `capacity` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero or too large, see `try_new`.
+    pub fn new(capacity: usize) -> Self {
+        match ByteRb::try_new(capacity) {
+            Ok(rb) => rb,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Works analog to `new` but fails if the buffer can't be created with `capacity`.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// assert_eq!(ByteRb::try_new(0).err(), Some(CapacityError::Zero));
+    /// assert_eq!(ByteRb::try_new(usize::MAX).err(), Some(CapacityError::TooLarge));
+    /// assert!(ByteRb::try_new(4).is_ok());
+    /// ```
+    ///
+    /// Possible errors:
+    ///
+    /// - `CapacityError::Zero` the buffer could never hold a byte
+    /// - `CapacityError::TooLarge` the slots can't be allocated
+    pub fn try_new(capacity: usize) -> Result<Self, CapacityError> {
+        if capacity == 0 {
+            return Err(CapacityError::Zero);
+        }
+        // the additional slot is used to distinct between empty and full state
+        let size = capacity.checked_add(1).ok_or(CapacityError::TooLarge)?;
+        let mut buf = Vec::new();
+        buf.try_reserve_exact(size)
+            .map_err(|_| CapacityError::TooLarge)?;
+        buf.resize_with(size, || UnsafeCell::new(0));
+        Ok(ByteRb {
+            inner: Arc::new(Inner {
+                buf: buf.into_boxed_slice(),
+                read_pos: AtomicUsize::new(0),
+                write_pos: AtomicUsize::new(0),
+            }),
+        })
+    }
+
+    /// Returns the writer and the reader of the buffer.
+    pub fn split(self) -> (ByteWriter, ByteReader) {
+        (
+            ByteWriter {
+                inner: self.inner.clone(),
+                marker: PhantomData,
+            },
+            ByteReader {
+                inner: self.inner,
+                marker: PhantomData,
+            },
+        )
+    }
+}
+
+impl Inner {
+    fn size(&self) -> usize {
+        self.buf.len()
+    }
+
+    fn slots(&self) -> *mut u8 {
+        // `UnsafeCell<u8>` has the same layout as `u8`.
+        self.buf.as_ptr() as *mut u8
+    }
+
+    /// Returns the number of pending bytes for the given positions.
+    fn count_between(&self, wr_pos: usize, re_pos: usize) -> usize {
+        (wr_pos + self.size() - re_pos) % self.size()
+    }
+
+    /// Returns the lengths of the two regions of `cnt` slots starting at `pos`,
+    /// the second one starts at the beginning of the buffer.
+    fn regions(&self, pos: usize, cnt: usize) -> (usize, usize) {
+        let first = cmp::min(cnt, self.size() - pos);
+        (first, cnt - first)
+    }
+}
+
+/// Writing side of a `ByteRb`.
+///
+/// It can be moved to another thread, but not shared between threads.
+pub struct ByteWriter {
+    inner: Arc<Inner>,
+    marker: PhantomData<Cell<()>>,
+}
+
+impl ByteWriter {
+    /// Returns the number of bytes that can be written.
+    pub fn write_space(&self) -> usize {
+        let inner = &self.inner;
+        let wr_pos = inner.write_pos.load(Ordering::Relaxed);
+        let re_pos = inner.read_pos.load(Ordering::Acquire);
+        inner.size() - 1 - inner.count_between(wr_pos, re_pos)
+    }
+
+    /// Writes as many bytes of `data` as there is space for.
+    ///
+    /// Returns the number of written bytes.
+    pub fn write(&mut self, data: &[u8]) -> usize {
+        let cnt = cmp::min(data.len(), self.write_space());
+        let [head, tail] = self.write_vector();
+        let first = cmp::min(cnt, head.len());
+        head[..first].copy_from_slice(&data[..first]);
+        tail[..cnt - first].copy_from_slice(&data[first..cnt]);
+        self.write_advance(cnt);
+        cnt
+    }
+
+    /// Returns the free slots, the second slice is only non-empty if they wrap around.
+    /// They are published by `write_advance`.
+    pub fn write_vector(&mut self) -> [&mut [u8]; 2] {
+        let wr_pos = self.inner.write_pos.load(Ordering::Relaxed);
+        let (len, wrapped) = self.inner.regions(wr_pos, self.write_space());
+        let slots = self.inner.slots();
+        // Safety: the free slots are not accessed by the reader, both regions are in
+        // bounds and disjoint, and borrowed exclusively through `self`.
+        unsafe {
+            [
+                slice::from_raw_parts_mut(slots.add(wr_pos), len),
+                slice::from_raw_parts_mut(slots, wrapped),
+            ]
+        }
+    }
+
+    /// Publishes the next `cnt` free slots, at most `write_space` of them.
+    pub fn write_advance(&mut self, cnt: usize) {
+        let cnt = cmp::min(cnt, self.write_space());
+        let wr_pos = self.inner.write_pos.load(Ordering::Relaxed);
+        self.inner
+            .write_pos
+            .store((wr_pos + cnt) % self.inner.size(), Ordering::Release);
+    }
+}
+
+/// Reading side of a `ByteRb`.
+///
+/// It can be moved to another thread, but not shared between threads.
+pub struct ByteReader {
+    inner: Arc<Inner>,
+    marker: PhantomData<Cell<()>>,
+}
+
+impl ByteReader {
+    /// Returns the number of bytes that can be read.
+    pub fn read_space(&self) -> usize {
+        let inner = &self.inner;
+        let re_pos = inner.read_pos.load(Ordering::Relaxed);
+        let wr_pos = inner.write_pos.load(Ordering::Acquire);
+        inner.count_between(wr_pos, re_pos)
+    }
+
+    /// Reads as many bytes into `data` as are pending.
+    ///
+    /// Returns the number of read bytes.
+    pub fn read(&mut self, data: &mut [u8]) -> usize {
+        let cnt = self.peek(data);
+        self.read_advance(cnt);
+        cnt
+    }
+
+    /// Works analog to `read` but keeps the bytes pending.
+    pub fn peek(&mut self, data: &mut [u8]) -> usize {
+        let cnt = cmp::min(data.len(), self.read_space());
+        let [head, tail] = self.read_vector();
+        let first = cmp::min(cnt, head.len());
+        data[..first].copy_from_slice(&head[..first]);
+        data[first..cnt].copy_from_slice(&tail[..cnt - first]);
+        cnt
+    }
+
+    /// Returns the pending bytes, the second slice is only non-empty if they wrap around.
+    /// They are consumed by `read_advance`.
+    pub fn read_vector(&mut self) -> [&[u8]; 2] {
+        let re_pos = self.inner.read_pos.load(Ordering::Relaxed);
+        let (len, wrapped) = self.inner.regions(re_pos, self.read_space());
+        let slots = self.inner.slots();
+        // Safety: the pending slots are not accessed by the writer until the read position
+        // is advanced, which needs `self` mutably, and both regions are in bounds.
+        unsafe {
+            [
+                slice::from_raw_parts(slots.add(re_pos), len),
+                slice::from_raw_parts(slots, wrapped),
+            ]
+        }
+    }
+
+    /// Consumes the next `cnt` pending bytes, at most `read_space` of them.
+    pub fn read_advance(&mut self, cnt: usize) {
+        let cnt = cmp::min(cnt, self.read_space());
+        let re_pos = self.inner.read_pos.load(Ordering::Relaxed);
+        self.inner
+            .read_pos
+            .store((re_pos + cnt) % self.inner.size(), Ordering::Release);
+    }
+}
@@ -0,0 +1,232 @@
+use std::slice;
+use std::sync::mpsc::{self, RecvError, RecvTimeoutError, SendError, TryRecvError, TrySendError};
+use std::time::Duration;
+
+use {Consumer, MpscProducer, MpscRb, Producer, RbError, RbProducer, Storage};
+
+/// Number of values that `pump_from_receiver` writes into the buffer at once at most.
+const BATCH_SIZE: usize = 256;
+
+/// Creates a bounded channel on top of a ring buffer with `capacity` preallocated slots.
+///
+/// The handles have the method names and error types of `std::sync::mpsc`, thus code that
+/// is written against a `sync_channel` of the standard library works with them as well.
+/// Unlike `sync_channel`, a capacity of zero is not supported.
+///
+/// ```
+/// use std::thread;
+/// use rb::*;
+///
+/// let (tx, rx) = channel(4);
+/// for id in 0..2 {
+///     let tx = tx.clone();
+///     thread::spawn(move || tx.send(id).unwrap());
+/// }
+/// drop(tx);
+/// let mut received = rx.iter().collect::<Vec<_>>();
+/// received.sort();
+/// assert_eq!(received, vec![0, 1]);
+/// ```
+pub fn channel<T: Clone>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    let rb = MpscRb::new(capacity);
+    (
+        Sender {
+            producer: rb.producer(),
+        },
+        Receiver {
+            consumer: rb.consumer(),
+        },
+    )
+}
+
+/// Sending half of a `channel`, which can be cloned and shared between threads.
+pub struct Sender<T> {
+    producer: MpscProducer<T>,
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Sender {
+            producer: self.producer.clone(),
+        }
+    }
+}
+
+impl<T: Clone> Sender<T> {
+    /// Sends `value`, blocks until a slot is free.
+    ///
+    /// Fails and returns the value if the receiver was dropped.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        match self.producer.write_blocking(slice::from_ref(&value)) {
+            Some(_) => Ok(()),
+            None => Err(SendError(value)),
+        }
+    }
+
+    /// Sends `value` if a slot is free, without blocking.
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        // Unlike the buffer, whose non-blocking writes keep storing values, a channel
+        // rejects them once the receiver is gone.
+        if self.producer.producer.is_disconnected() {
+            return Err(TrySendError::Disconnected(value));
+        }
+        match self.producer.write(slice::from_ref(&value)) {
+            Ok(_) => Ok(()),
+            Err(RbError::Disconnected) => Err(TrySendError::Disconnected(value)),
+            Err(_) => Err(TrySendError::Full(value)),
+        }
+    }
+}
+
+/// Receiving half of a `channel`.
+pub struct Receiver<T> {
+    consumer: Consumer<T>,
+}
+
+impl<T: Clone> Receiver<T> {
+    /// Receives the next value, blocks until one is sent.
+    ///
+    /// Fails once all senders were dropped and nothing is left to receive.
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.recv_timeout(Duration::MAX).map_err(|_| RecvError)
+    }
+
+    /// Receives the next value if one is pending, without blocking.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        self.recv_timeout(Duration::ZERO).map_err(|err| match err {
+            RecvTimeoutError::Timeout => TryRecvError::Empty,
+            RecvTimeoutError::Disconnected => TryRecvError::Disconnected,
+        })
+    }
+
+    /// Receives the next value, blocks until one is sent or `timeout` has elapsed.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        loop {
+            if let Some(value) = self.consumer.read_vec(1).pop() {
+                return Ok(value);
+            }
+            match self.consumer.wait_for_count(1, timeout) {
+                Ok(_) => {}
+                Err(RbError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+                Err(_) => return Err(RecvTimeoutError::Timeout),
+            }
+        }
+    }
+
+    /// Returns an iterator that blocks for the next value and ends once all senders
+    /// were dropped.
+    pub fn iter(&self) -> RecvIter<'_, T> {
+        RecvIter { rx: self }
+    }
+
+    /// Returns an iterator over the pending values that never blocks.
+    pub fn try_iter(&self) -> TryRecvIter<'_, T> {
+        TryRecvIter { rx: self }
+    }
+}
+
+/// Blocking iterator over the values of a `Receiver`, see `Receiver::iter`.
+pub struct RecvIter<'a, T: 'a> {
+    rx: &'a Receiver<T>,
+}
+
+impl<'a, T: Clone> Iterator for RecvIter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rx.recv().ok()
+    }
+}
+
+/// Non-blocking iterator over the values of a `Receiver`, see `Receiver::try_iter`.
+pub struct TryRecvIter<'a, T: 'a> {
+    rx: &'a Receiver<T>,
+}
+
+impl<'a, T: Clone> Iterator for TryRecvIter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rx.try_recv().ok()
+    }
+}
+
+/// Owning blocking iterator over the values of a `Receiver`.
+pub struct IntoRecvIter<T> {
+    rx: Receiver<T>,
+}
+
+impl<T: Clone> Iterator for IntoRecvIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rx.recv().ok()
+    }
+}
+
+impl<'a, T: Clone> IntoIterator for &'a Receiver<T> {
+    type Item = T;
+    type IntoIter = RecvIter<'a, T>;
+
+    fn into_iter(self) -> RecvIter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T: Clone> IntoIterator for Receiver<T> {
+    type Item = T;
+    type IntoIter = IntoRecvIter<T>;
+
+    fn into_iter(self) -> IntoRecvIter<T> {
+        IntoRecvIter { rx: self }
+    }
+}
+
+/// Forwards the values of a standard `mpsc::Receiver` into the buffer of `producer` until
+/// all senders were dropped or the buffer is disconnected, e.g. to migrate from channels to
+/// a ring buffer one side at a time. Blocks while the channel is empty or the buffer is full.
+///
+/// The values that are queued in the channel are written as one slice, up to 256 at once.
+/// Once the buffer is disconnected, the values of the current batch that weren't written
+/// are dropped, the remaining ones stay in the channel.
+///
+/// Returns the number of values that were written into the buffer.
+///
+/// ```
+/// use std::sync::mpsc;
+/// use std::thread;
+/// use rb::*;
+///
+/// let (tx, rx) = mpsc::channel();
+/// let rb = SpscRb::new(16);
+/// let (prod, cons) = (rb.producer(), rb.consumer());
+/// let bridge = thread::spawn(move || pump_from_receiver(&rx, &prod));
+/// for i in 0..4 {
+///     tx.send(i).unwrap();
+/// }
+/// drop(tx);
+/// assert_eq!(bridge.join().unwrap(), 4);
+/// assert_eq!(cons.read_vec(16), vec![0, 1, 2, 3]);
+/// ```
+pub fn pump_from_receiver<T, S>(receiver: &mpsc::Receiver<T>, producer: &Producer<T, S>) -> usize
+where
+    T: Clone,
+    S: Storage<T>,
+{
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+    let mut total = 0;
+    while let Ok(value) = receiver.recv() {
+        batch.clear();
+        batch.push(value);
+        batch.extend(receiver.try_iter().take(BATCH_SIZE - 1));
+        let mut written = 0;
+        while written < batch.len() {
+            match producer.write_blocking(&batch[written..]) {
+                Some(cnt) => written += cnt,
+                None => return total + written,
+            }
+        }
+        total += written;
+    }
+    total
+}
@@ -0,0 +1,27 @@
+/// Copies `data` into `buf` starting at `wr_pos`, wrapping around at the end of `buf`.
+///
+/// The overwritten elements are dropped, for `Copy` types this is a plain `memcpy`.
+#[inline(always)]
+pub(crate) fn copy_to_ring<T: Clone>(buf: &mut [T], wr_pos: usize, data: &[T]) {
+    let cnt = data.len();
+    if (wr_pos + cnt) < buf.len() {
+        buf[wr_pos..wr_pos + cnt].clone_from_slice(data);
+    } else {
+        let d = buf.len() - wr_pos;
+        buf[wr_pos..].clone_from_slice(&data[..d]);
+        buf[..(cnt - d)].clone_from_slice(&data[d..]);
+    }
+}
+
+/// Fills `data` with elements of `buf` starting at `re_pos`, wrapping around at the end of `buf`.
+#[inline(always)]
+pub(crate) fn copy_from_ring<T: Clone>(buf: &[T], re_pos: usize, data: &mut [T]) {
+    let cnt = data.len();
+    if (re_pos + cnt) < buf.len() {
+        data.clone_from_slice(&buf[re_pos..re_pos + cnt]);
+    } else {
+        let d = buf.len() - re_pos;
+        data[..d].clone_from_slice(&buf[re_pos..]);
+        data[d..].clone_from_slice(&buf[..(cnt - d)]);
+    }
+}
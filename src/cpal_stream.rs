@@ -0,0 +1,106 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use cpal::traits::DeviceTrait;
+use cpal::{
+    BuildStreamError, Device, InputCallbackInfo, OutputCallbackInfo, Stream, StreamConfig,
+    StreamError,
+};
+
+use {Consumer, Producer, RbProducer};
+
+/// Connects the ring buffer to a cpal stream.
+///
+/// An output stream plays everything read from a `Consumer<f32>`, an input stream writes
+/// the recorded samples into a `Producer<f32>`. The data callbacks never block, instead
+/// every callback that could not be served completely is counted as xrun, i.e. an underrun
+/// for output (the missing samples are played as silence) and an overrun for input (the
+/// samples that did not fit are dropped).
+///
+/// Depending on the host the stream starts paused, call `StreamTrait::play` on `stream()`
+/// to start it. Streams that are built by hand can use `output_callback` and
+/// `input_callback` directly.
+pub struct CpalStream {
+    stream: Stream,
+    xruns: Arc<AtomicUsize>,
+}
+
+impl CpalStream {
+    /// Builds an output stream on `device` that plays everything read from `consumer`.
+    pub fn output<E>(
+        device: &Device,
+        config: &StreamConfig,
+        consumer: Consumer<f32>,
+        error_callback: E,
+    ) -> Result<Self, BuildStreamError>
+    where
+        E: FnMut(StreamError) + Send + 'static,
+    {
+        let xruns = Arc::new(AtomicUsize::new(0));
+        let stream = device.build_output_stream(
+            config,
+            output_callback(consumer, xruns.clone()),
+            error_callback,
+            None,
+        )?;
+        Ok(CpalStream { stream, xruns })
+    }
+
+    /// Builds an input stream on `device` that writes all recorded samples into `producer`.
+    pub fn input<E>(
+        device: &Device,
+        config: &StreamConfig,
+        producer: Producer<f32>,
+        error_callback: E,
+    ) -> Result<Self, BuildStreamError>
+    where
+        E: FnMut(StreamError) + Send + 'static,
+    {
+        let xruns = Arc::new(AtomicUsize::new(0));
+        let stream = device.build_input_stream(
+            config,
+            input_callback(producer, xruns.clone()),
+            error_callback,
+            None,
+        )?;
+        Ok(CpalStream { stream, xruns })
+    }
+
+    /// Returns the number of callbacks that could not be served completely.
+    pub fn xruns(&self) -> usize {
+        self.xruns.load(Ordering::Relaxed)
+    }
+
+    /// Returns the underlying stream, e.g. to play or pause it.
+    pub fn stream(&self) -> &Stream {
+        &self.stream
+    }
+}
+
+/// Returns a data callback for `DeviceTrait::build_output_stream` that plays the samples
+/// read from `consumer`. If less samples are pending than requested, the rest is filled
+/// with silence and `xruns` is incremented.
+pub fn output_callback(
+    consumer: Consumer<f32>,
+    xruns: Arc<AtomicUsize>,
+) -> impl FnMut(&mut [f32], &OutputCallbackInfo) + Send + 'static {
+    move |data, _| {
+        if consumer.read_or_default(data) < data.len() {
+            xruns.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Returns a data callback for `DeviceTrait::build_input_stream` that writes the recorded
+/// samples into `producer`. If not all of them fit, the rest is dropped and `xruns` is
+/// incremented.
+pub fn input_callback(
+    producer: Producer<f32>,
+    xruns: Arc<AtomicUsize>,
+) -> impl FnMut(&[f32], &InputCallbackInfo) + Send + 'static {
+    move |data, _| {
+        if producer.write(data).unwrap_or(0) < data.len() {
+            xruns.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use {Consumer, Producer, SpscRb};
+
+/// Creates two connected endpoints on top of two ring buffers with `capacity` slots each,
+/// e.g. to send commands to an audio thread and receive samples back.
+///
+/// Each endpoint writes into one buffer and reads from the other one, the values written
+/// by the first endpoint are read by the second one and vice versa.
+///
+/// ```
+/// use std::thread;
+/// use rb::*;
+///
+/// let (control, audio) = duplex::<u8, f32>(64);
+/// let worker = thread::spawn(move || {
+///     let mut cmd = [0];
+///     while audio.consumer().read_blocking(&mut cmd).is_some() {
+///         audio.producer().write_blocking(&[cmd[0] as f32; 4]);
+///     }
+/// });
+/// control.producer().write(&[1]).unwrap();
+/// let mut samples = [0.0; 4];
+/// control.consumer().read_blocking(&mut samples).unwrap();
+/// assert_eq!(samples, [1.0; 4]);
+/// drop(control);
+/// worker.join().unwrap();
+/// ```
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero.
+pub fn duplex<A: Clone, B: Clone>(capacity: usize) -> (Endpoint<A, B>, Endpoint<B, A>) {
+    let (a_producer, a_consumer) = SpscRb::new(capacity).into_split();
+    let (b_producer, b_consumer) = SpscRb::new(capacity).into_split();
+    (
+        Endpoint {
+            producer: a_producer,
+            consumer: b_consumer,
+        },
+        Endpoint {
+            producer: b_producer,
+            consumer: a_consumer,
+        },
+    )
+}
+
+/// One side of a `duplex` pair, which writes values of type `W` and reads values of type `R`.
+pub struct Endpoint<W, R> {
+    producer: Producer<W>,
+    consumer: Consumer<R>,
+}
+
+impl<W, R> Endpoint<W, R> {
+    /// Returns the producer of the values for the other endpoint.
+    pub fn producer(&self) -> &Producer<W> {
+        &self.producer
+    }
+
+    /// Returns the consumer of the values from the other endpoint.
+    pub fn consumer(&self) -> &Consumer<R> {
+        &self.consumer
+    }
+
+    /// Closes both buffers, see `SpscRb::close`.
+    pub fn close(&self) {
+        self.producer.close();
+        self.consumer.close();
+    }
+
+    /// Returns the producer and the consumer, e.g. to move them to different threads.
+    pub fn into_split(self) -> (Producer<W>, Consumer<R>) {
+        (self.producer, self.consumer)
+    }
+}
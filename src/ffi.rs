@@ -0,0 +1,383 @@
+//! C API around `SpscRb<u8>` and `SpscRb<f32>`, e.g. for C and C++ audio callbacks that
+//! push into a buffer owned by Rust code, and around `ByteRb`, whose `rb_ringbuffer_*`
+//! functions behave like the ones of `jack_ringbuffer_t`. The header is generated into
+//! `include/rb.h`.
+//!
+//! Each buffer is created with its only producer and consumer. The producer functions
+//! and the consumer functions may be called from one thread each, the others from any.
+
+use std::ptr;
+use std::slice;
+
+use {
+    ByteRb, ByteReader, ByteWriter, Consumer, Producer, RbConsumer, RbInspector, RbProducer, SpscRb,
+};
+
+/// Byte ring buffer, created by `rb_u8_new`.
+pub struct RbU8 {
+    producer: Producer<u8>,
+    consumer: Consumer<u8>,
+}
+
+/// Sample ring buffer, created by `rb_f32_new`.
+pub struct RbF32 {
+    producer: Producer<f32>,
+    consumer: Consumer<f32>,
+}
+
+fn new<T: Clone>(capacity: usize) -> Option<(Producer<T>, Consumer<T>)> {
+    SpscRb::try_new(capacity).ok().map(SpscRb::into_split)
+}
+
+/// Writes as many of the `len` values at `data` as there are free slots.
+unsafe fn write<T: Clone>(producer: &Producer<T>, data: *const T, len: usize) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    producer
+        .write(slice::from_raw_parts(data, len))
+        .unwrap_or(0)
+}
+
+/// Reads up to `len` values into `data`.
+unsafe fn read<T: Clone>(consumer: &Consumer<T>, data: *mut T, len: usize) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    consumer
+        .read(slice::from_raw_parts_mut(data, len))
+        .unwrap_or(0)
+}
+
+/// Creates a buffer for `capacity` bytes.
+///
+/// Returns `NULL` if the capacity is zero or too large.
+#[no_mangle]
+pub extern "C" fn rb_u8_new(capacity: usize) -> *mut RbU8 {
+    match new(capacity) {
+        Some((producer, consumer)) => Box::into_raw(Box::new(RbU8 { producer, consumer })),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Destroys the buffer, does nothing for `NULL`.
+///
+/// # Safety
+///
+/// `rb` was returned by `rb_u8_new` and is not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn rb_u8_free(rb: *mut RbU8) {
+    if !rb.is_null() {
+        drop(Box::from_raw(rb));
+    }
+}
+
+/// Writes as many of the `len` bytes at `data` as there are free slots, never blocks.
+///
+/// Returns the number of written bytes.
+///
+/// # Safety
+///
+/// `rb` is a live buffer and `data` points to `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn rb_u8_write(rb: *const RbU8, data: *const u8, len: usize) -> usize {
+    write(&(*rb).producer, data, len)
+}
+
+/// Reads up to `len` bytes into `data`, never blocks.
+///
+/// Returns the number of read bytes.
+///
+/// # Safety
+///
+/// `rb` is a live buffer and `data` points to `len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn rb_u8_read(rb: *const RbU8, data: *mut u8, len: usize) -> usize {
+    read(&(*rb).consumer, data, len)
+}
+
+/// Returns the number of pending bytes.
+///
+/// # Safety
+///
+/// `rb` is a live buffer.
+#[no_mangle]
+pub unsafe extern "C" fn rb_u8_count(rb: *const RbU8) -> usize {
+    (*rb).consumer.count()
+}
+
+/// Returns the number of bytes that can be written until the buffer is full.
+///
+/// # Safety
+///
+/// `rb` is a live buffer.
+#[no_mangle]
+pub unsafe extern "C" fn rb_u8_slots_free(rb: *const RbU8) -> usize {
+    (*rb).producer.slots_free()
+}
+
+/// Creates a buffer for `capacity` samples.
+///
+/// Returns `NULL` if the capacity is zero or too large.
+#[no_mangle]
+pub extern "C" fn rb_f32_new(capacity: usize) -> *mut RbF32 {
+    match new(capacity) {
+        Some((producer, consumer)) => Box::into_raw(Box::new(RbF32 { producer, consumer })),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Destroys the buffer, does nothing for `NULL`.
+///
+/// # Safety
+///
+/// `rb` was returned by `rb_f32_new` and is not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn rb_f32_free(rb: *mut RbF32) {
+    if !rb.is_null() {
+        drop(Box::from_raw(rb));
+    }
+}
+
+/// Writes as many of the `len` samples at `data` as there are free slots, never blocks.
+///
+/// Returns the number of written samples.
+///
+/// # Safety
+///
+/// `rb` is a live buffer and `data` points to `len` samples.
+#[no_mangle]
+pub unsafe extern "C" fn rb_f32_write(rb: *const RbF32, data: *const f32, len: usize) -> usize {
+    write(&(*rb).producer, data, len)
+}
+
+/// Reads up to `len` samples into `data`, never blocks.
+///
+/// Returns the number of read samples.
+///
+/// # Safety
+///
+/// `rb` is a live buffer and `data` points to `len` writable samples.
+#[no_mangle]
+pub unsafe extern "C" fn rb_f32_read(rb: *const RbF32, data: *mut f32, len: usize) -> usize {
+    read(&(*rb).consumer, data, len)
+}
+
+/// Returns the number of pending samples.
+///
+/// # Safety
+///
+/// `rb` is a live buffer.
+#[no_mangle]
+pub unsafe extern "C" fn rb_f32_count(rb: *const RbF32) -> usize {
+    (*rb).consumer.count()
+}
+
+/// Returns the number of samples that can be written until the buffer is full.
+///
+/// # Safety
+///
+/// `rb` is a live buffer.
+#[no_mangle]
+pub unsafe extern "C" fn rb_f32_slots_free(rb: *const RbF32) -> usize {
+    (*rb).producer.slots_free()
+}
+
+/// Lock-free byte ring buffer, created by `rb_ringbuffer_create`.
+pub struct RbRingbuffer {
+    writer: ByteWriter,
+    reader: ByteReader,
+}
+
+/// Region of a `RbRingbuffer`, like `jack_ringbuffer_data_t`.
+#[repr(C)]
+pub struct RbRingbufferData {
+    /// First slot of the region, `NULL` if it is empty.
+    pub buf: *mut u8,
+    /// Number of slots of the region.
+    pub len: usize,
+}
+
+impl<'a> From<&'a [u8]> for RbRingbufferData {
+    fn from(region: &'a [u8]) -> Self {
+        RbRingbufferData {
+            // Empty regions are null, like the ones of `jack_ringbuffer_get_read_vector`.
+            buf: if region.is_empty() {
+                ptr::null_mut()
+            } else {
+                region.as_ptr() as *mut u8
+            },
+            len: region.len(),
+        }
+    }
+}
+
+impl<'a> From<&'a mut [u8]> for RbRingbufferData {
+    fn from(region: &'a mut [u8]) -> Self {
+        RbRingbufferData {
+            // The C side writes into the free slots, so the pointer must come from a
+            // mutable borrow.
+            buf: if region.is_empty() {
+                ptr::null_mut()
+            } else {
+                region.as_mut_ptr()
+            },
+            len: region.len(),
+        }
+    }
+}
+
+/// Creates a buffer for `capacity` bytes, like `jack_ringbuffer_create`.
+///
+/// Returns `NULL` if the capacity is zero or too large.
+#[no_mangle]
+pub extern "C" fn rb_ringbuffer_create(capacity: usize) -> *mut RbRingbuffer {
+    match ByteRb::try_new(capacity) {
+        Ok(rb) => {
+            let (writer, reader) = rb.split();
+            Box::into_raw(Box::new(RbRingbuffer { writer, reader }))
+        }
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Destroys the buffer, does nothing for `NULL`.
+///
+/// # Safety
+///
+/// `rb` was returned by `rb_ringbuffer_create` and is not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn rb_ringbuffer_free(rb: *mut RbRingbuffer) {
+    if !rb.is_null() {
+        drop(Box::from_raw(rb));
+    }
+}
+
+/// Returns the number of bytes that can be written.
+///
+/// # Safety
+///
+/// `rb` is a live buffer, only the writing thread calls the writing functions.
+#[no_mangle]
+pub unsafe extern "C" fn rb_ringbuffer_write_space(rb: *const RbRingbuffer) -> usize {
+    (*rb).writer.write_space()
+}
+
+/// Returns the number of bytes that can be read.
+///
+/// # Safety
+///
+/// `rb` is a live buffer, only the reading thread calls the reading functions.
+#[no_mangle]
+pub unsafe extern "C" fn rb_ringbuffer_read_space(rb: *const RbRingbuffer) -> usize {
+    (*rb).reader.read_space()
+}
+
+/// Writes as many of the `cnt` bytes at `src` as there is space for.
+///
+/// Returns the number of written bytes.
+///
+/// # Safety
+///
+/// See `rb_ringbuffer_write_space`, `src` points to `cnt` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn rb_ringbuffer_write(
+    rb: *mut RbRingbuffer,
+    src: *const u8,
+    cnt: usize,
+) -> usize {
+    if cnt == 0 {
+        return 0;
+    }
+    (*rb).writer.write(slice::from_raw_parts(src, cnt))
+}
+
+/// Reads up to `cnt` bytes into `dest`.
+///
+/// Returns the number of read bytes.
+///
+/// # Safety
+///
+/// See `rb_ringbuffer_read_space`, `dest` points to `cnt` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn rb_ringbuffer_read(
+    rb: *mut RbRingbuffer,
+    dest: *mut u8,
+    cnt: usize,
+) -> usize {
+    if cnt == 0 {
+        return 0;
+    }
+    (*rb).reader.read(slice::from_raw_parts_mut(dest, cnt))
+}
+
+/// Works analog to `rb_ringbuffer_read` but keeps the bytes pending.
+///
+/// # Safety
+///
+/// See `rb_ringbuffer_read`.
+#[no_mangle]
+pub unsafe extern "C" fn rb_ringbuffer_peek(
+    rb: *mut RbRingbuffer,
+    dest: *mut u8,
+    cnt: usize,
+) -> usize {
+    if cnt == 0 {
+        return 0;
+    }
+    (*rb).reader.peek(slice::from_raw_parts_mut(dest, cnt))
+}
+
+/// Stores the two regions of free slots in `vec`, the second one is only non-empty if
+/// they wrap around.
+///
+/// # Safety
+///
+/// See `rb_ringbuffer_write_space`, `vec` points to two regions. The regions are valid
+/// until the next call of `rb_ringbuffer_write_advance`.
+#[no_mangle]
+pub unsafe extern "C" fn rb_ringbuffer_get_write_vector(
+    rb: *mut RbRingbuffer,
+    vec: *mut RbRingbufferData,
+) {
+    let [head, tail] = (*rb).writer.write_vector();
+    vec.write(RbRingbufferData::from(head));
+    vec.add(1).write(RbRingbufferData::from(tail));
+}
+
+/// Publishes the next `cnt` free slots.
+///
+/// # Safety
+///
+/// See `rb_ringbuffer_write_space`.
+#[no_mangle]
+pub unsafe extern "C" fn rb_ringbuffer_write_advance(rb: *mut RbRingbuffer, cnt: usize) {
+    (*rb).writer.write_advance(cnt);
+}
+
+/// Stores the two regions of pending bytes in `vec`, the second one is only non-empty
+/// if they wrap around.
+///
+/// # Safety
+///
+/// See `rb_ringbuffer_read_space`, `vec` points to two regions. The regions are valid
+/// until the next call of `rb_ringbuffer_read_advance`.
+#[no_mangle]
+pub unsafe extern "C" fn rb_ringbuffer_get_read_vector(
+    rb: *mut RbRingbuffer,
+    vec: *mut RbRingbufferData,
+) {
+    let [head, tail] = (*rb).reader.read_vector();
+    vec.write(RbRingbufferData::from(head));
+    vec.add(1).write(RbRingbufferData::from(tail));
+}
+
+/// Consumes the next `cnt` pending bytes.
+///
+/// # Safety
+///
+/// See `rb_ringbuffer_read_space`.
+#[no_mangle]
+pub unsafe extern "C" fn rb_ringbuffer_read_advance(rb: *mut RbRingbuffer, cnt: usize) {
+    (*rb).reader.read_advance(cnt);
+}
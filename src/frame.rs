@@ -0,0 +1,103 @@
+use std::cmp;
+
+use {Consumer, Producer, RbError, Result, Storage};
+
+/// Length of the prefix of a frame, its length as little endian `u32`.
+const PREFIX_LEN: usize = 4;
+
+impl<S: Storage<u8>> Producer<u8, S> {
+    /// Writes `frame` prefixed by its length, either completely or not at all,
+    /// so that messages can be passed over a byte buffer, see `Consumer::read_frame`.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(64);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write_frame(b"hello").unwrap();
+    /// prod.write_frame(b"world").unwrap();
+    /// let mut frame = Vec::new();
+    /// cons.read_frame(&mut frame).unwrap();
+    /// assert_eq!(frame, b"hello");
+    /// ```
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Full` less free slots than the frame and its 4 byte prefix
+    /// - `RbError::Disconnected` all consumers were dropped
+    ///
+    /// # Panics
+    ///
+    /// Panics if the frame is longer than `u32::MAX` bytes.
+    pub fn write_frame(&self, frame: &[u8]) -> Result<()> {
+        assert!(frame.len() <= u32::MAX as usize, "the frame is too long");
+        let prefix = (frame.len() as u32).to_le_bytes();
+        let len = PREFIX_LEN + frame.len();
+        let written = self.write_access(|head, tail| {
+            if head.len() + tail.len() < len {
+                return 0;
+            }
+            scatter(head, tail, 0, &prefix);
+            scatter(head, tail, PREFIX_LEN, frame);
+            len
+        });
+        match written {
+            Ok(0) | Err(RbError::Full) => Err(RbError::Full),
+            Ok(_) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl<S: Storage<u8>> Consumer<u8, S> {
+    /// Reads the next frame that was written by `Producer::write_frame` into `frame`,
+    /// which is cleared beforehand.
+    ///
+    /// Returns the length of the frame.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Empty` no complete frame is pending
+    /// - `RbError::Disconnected` the buffer is empty and all producers were dropped
+    pub fn read_frame(&self, frame: &mut Vec<u8>) -> Result<usize> {
+        let consumed = self.read_access(|head, tail| {
+            let pending = head.len() + tail.len();
+            if pending < PREFIX_LEN {
+                return 0;
+            }
+            let mut prefix = [0; PREFIX_LEN];
+            gather(head, tail, 0, &mut prefix);
+            let len = u32::from_le_bytes(prefix) as usize;
+            if pending < PREFIX_LEN + len {
+                return 0;
+            }
+            frame.clear();
+            frame.resize(len, 0);
+            gather(head, tail, PREFIX_LEN, frame);
+            PREFIX_LEN + len
+        })?;
+        if consumed == 0 {
+            return Err(RbError::Empty);
+        }
+        Ok(consumed - PREFIX_LEN)
+    }
+}
+
+/// Copies `data` to `offset` of the concatenation of `head` and `tail`.
+fn scatter(head: &mut [u8], tail: &mut [u8], offset: usize, data: &[u8]) {
+    let start = cmp::min(offset, head.len());
+    let cnt = cmp::min(head.len() - start, data.len());
+    head[start..start + cnt].copy_from_slice(&data[..cnt]);
+    let start = (offset + cnt).saturating_sub(head.len());
+    tail[start..start + data.len() - cnt].copy_from_slice(&data[cnt..]);
+}
+
+/// Fills `data` from `offset` of the concatenation of `head` and `tail`.
+fn gather(head: &[u8], tail: &[u8], offset: usize, data: &mut [u8]) {
+    let start = cmp::min(offset, head.len());
+    let cnt = cmp::min(head.len() - start, data.len());
+    data[..cnt].copy_from_slice(&head[start..start + cnt]);
+    let start = (offset + cnt).saturating_sub(head.len());
+    let rest = data.len() - cnt;
+    data[cnt..].copy_from_slice(&tail[start..start + rest]);
+}
@@ -0,0 +1,160 @@
+use std::cmp;
+use std::time::Duration;
+
+use {Consumer, Producer, RbConsumer, RbError, RbInspector, RbProducer, Result, SpscRb};
+
+/// A ring buffer that is written and read in whole frames of `frame_len` values, e.g. stereo
+/// pairs or blocks of 64 samples, so that an odd count can never shift the channels against
+/// each other.
+///
+/// Writes and reads are rounded down to whole frames, the values of an incomplete frame at
+/// the end of a slice are ignored.
+///
+/// ```
+/// use rb::*;
+///
+/// let (prod, cons) = FrameRb::new(2, 4).split();
+/// // the incomplete frame is not written
+/// assert_eq!(prod.write(&[1, -1, 2, -2, 3]).unwrap(), 2);
+/// let mut buf = [0; 3];
+/// assert_eq!(cons.read(&mut buf).unwrap(), 1);
+/// assert_eq!(buf, [1, -1, 0]);
+/// ```
+pub struct FrameRb<T> {
+    rb: SpscRb<T>,
+    frame_len: usize,
+}
+
+impl<T: Clone> FrameRb<T> {
+    /// Creates a buffer for `frames` frames of `frame_len` values each.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frame_len` or `frames` is zero.
+    pub fn new(frame_len: usize, frames: usize) -> Self {
+        assert!(frame_len > 0, "the length of a frame is zero");
+        FrameRb {
+            rb: SpscRb::new(frame_len * frames),
+            frame_len,
+        }
+    }
+
+    /// Returns the producer and the consumer of the buffer.
+    pub fn split(self) -> (FrameProducer<T>, FrameConsumer<T>) {
+        let (producer, consumer) = self.rb.into_split();
+        (
+            FrameProducer {
+                rb: producer,
+                frame_len: self.frame_len,
+            },
+            FrameConsumer {
+                rb: consumer,
+                frame_len: self.frame_len,
+            },
+        )
+    }
+}
+
+/// Producer of a `FrameRb`.
+pub struct FrameProducer<T> {
+    rb: Producer<T>,
+    frame_len: usize,
+}
+
+impl<T: Clone> FrameProducer<T> {
+    /// Returns the number of values per frame.
+    pub fn frame_len(&self) -> usize {
+        self.frame_len
+    }
+
+    /// Returns the number of frames that can be written.
+    pub fn frames_free(&self) -> usize {
+        self.rb.slots_free() / self.frame_len
+    }
+
+    /// Writes as many whole frames of `data` as there are free slots for.
+    ///
+    /// Returns the number of written frames.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Full` not a single frame fits
+    /// - `RbError::Disconnected` the consumer was dropped
+    pub fn write(&self, data: &[T]) -> Result<usize> {
+        self.write_within(data, Duration::ZERO)
+    }
+
+    /// Works analog to `write` but blocks until there are free slots for a frame.
+    ///
+    /// Returns `None` if the consumer was dropped.
+    pub fn write_blocking(&self, data: &[T]) -> Option<usize> {
+        self.write_within(data, Duration::MAX).ok()
+    }
+
+    fn write_within(&self, data: &[T], timeout: Duration) -> Result<usize> {
+        let frames = data.len() / self.frame_len;
+        if frames == 0 {
+            return Ok(0);
+        }
+        match self.rb.wait_for_slots(self.frame_len, timeout) {
+            Err(RbError::TimedOut) => return Err(RbError::Full),
+            free => free?,
+        };
+        // The consumer only frees slots, thus all of them are written.
+        let frames = cmp::min(frames, self.frames_free());
+        self.rb.write(&data[..frames * self.frame_len])?;
+        Ok(frames)
+    }
+}
+
+/// Consumer of a `FrameRb`.
+pub struct FrameConsumer<T> {
+    rb: Consumer<T>,
+    frame_len: usize,
+}
+
+impl<T: Clone> FrameConsumer<T> {
+    /// Returns the number of values per frame.
+    pub fn frame_len(&self) -> usize {
+        self.frame_len
+    }
+
+    /// Returns the number of pending frames.
+    pub fn frames(&self) -> usize {
+        self.rb.count() / self.frame_len
+    }
+
+    /// Reads as many pending frames into `data` as fit into it.
+    ///
+    /// Returns the number of read frames.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Empty` no pending frame
+    /// - `RbError::Disconnected` no pending frame and the producer was dropped
+    pub fn read(&self, data: &mut [T]) -> Result<usize> {
+        self.read_within(data, Duration::ZERO)
+    }
+
+    /// Works analog to `read` but blocks until a frame is pending.
+    ///
+    /// Returns `None` if the producer was dropped and no frame is left to read.
+    pub fn read_blocking(&self, data: &mut [T]) -> Option<usize> {
+        self.read_within(data, Duration::MAX).ok()
+    }
+
+    fn read_within(&self, data: &mut [T], timeout: Duration) -> Result<usize> {
+        let frames = data.len() / self.frame_len;
+        if frames == 0 {
+            return Ok(0);
+        }
+        match self.rb.wait_for_count(self.frame_len, timeout) {
+            Err(RbError::TimedOut) => return Err(RbError::Empty),
+            count => count?,
+        };
+        // The producer only adds values, thus all of them are read.
+        let frames = cmp::min(frames, self.frames());
+        self.rb.read(&mut data[..frames * self.frame_len])?;
+        Ok(frames)
+    }
+}
@@ -0,0 +1,238 @@
+use std::cmp;
+#[cfg(target_os = "linux")]
+use std::convert::TryInto;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
+use sync::{Mutex, MutexGuard};
+
+/// Condition variable that blocks on a futex, or `WaitOnAddress` on Windows.
+///
+/// Notifications skip the syscall if no thread is blocked, i.e. the common case of
+/// publishing new positions while the other side is busy does not enter the kernel.
+#[derive(Default)]
+pub(crate) struct Condvar {
+    // Incremented by every notification, waiters block as long as it's unchanged.
+    seq: AtomicU32,
+    // Number of blocked threads.
+    waiters: AtomicU32,
+}
+
+impl Condvar {
+    pub(crate) fn new() -> Self {
+        Condvar::default()
+    }
+
+    pub(crate) fn notify_all(&self) {
+        self.seq.fetch_add(1, Ordering::SeqCst);
+        // A thread that registers as waiter afterwards already sees the new sequence number
+        // and does not block.
+        if self.waiters.load(Ordering::SeqCst) > 0 {
+            wake_all(&self.seq);
+        }
+    }
+
+    /// Blocks until the condition variable is notified.
+    pub(crate) fn wait<'a, T>(
+        &self,
+        mutex: &'a Mutex<T>,
+        guard: MutexGuard<'a, T>,
+    ) -> MutexGuard<'a, T> {
+        self.block(mutex, guard, None)
+    }
+
+    /// Blocks until the condition variable is notified or `timeout` has elapsed.
+    /// Returns the guard and whether the wait timed out.
+    pub(crate) fn wait_timeout<'a, T>(
+        &self,
+        mutex: &'a Mutex<T>,
+        guard: MutexGuard<'a, T>,
+        timeout: Duration,
+    ) -> (MutexGuard<'a, T>, bool) {
+        let start = Instant::now();
+        let guard = self.block(mutex, guard, Some(timeout));
+        (guard, start.elapsed() >= timeout)
+    }
+
+    fn block<'a, T>(
+        &self,
+        mutex: &'a Mutex<T>,
+        guard: MutexGuard<'a, T>,
+        timeout: Option<Duration>,
+    ) -> MutexGuard<'a, T> {
+        // Loaded while holding the lock, the notification of any later change increments it.
+        let seq = self.seq.load(Ordering::Relaxed);
+        self.waiters.fetch_add(1, Ordering::SeqCst);
+        drop(guard);
+        wait(&self.seq, seq, timeout);
+        self.waiters.fetch_sub(1, Ordering::Relaxed);
+        mutex.lock()
+    }
+}
+
+// Bits of `Occupancy::word`.
+// The number of pending values, only its lowest bits if the capacity exceeds them.
+const COUNT: u32 = (1 << 24) - 1;
+// Incremented by every event that changes the condition of blocked handles without
+// changing the count, e.g. the drop of the last handle of a side.
+const EVENTS: u32 = 0x7f << 24;
+const EVENT: u32 = 1 << 24;
+// Set if the count doesn't fit into its bits, another count might have the same bits.
+const TRUNCATED: u32 = 1 << 31;
+
+/// Interval at which blocked handles check their condition if the count is truncated.
+const TRUNCATED_POLL: Duration = Duration::from_millis(1);
+
+/// Futex word with the occupancy of a buffer, the number of its pending values, that the
+/// blocked handles of both sides wait on directly, without taking the buffer's lock.
+///
+/// The condition of a blocked handle only depends on the count, the capacity and whether
+/// the other side disconnected. The word changes along with any of them, thus a handle
+/// that saw an unchanged word does not miss a change of its condition.
+#[derive(Default)]
+pub(crate) struct Occupancy {
+    word: AtomicU32,
+    // Number of blocked threads.
+    waiters: AtomicU32,
+}
+
+impl Occupancy {
+    pub(crate) fn new() -> Self {
+        Occupancy::default()
+    }
+
+    /// Publishes the `count` of pending values and wakes up the blocked threads if the
+    /// word changed. `event` is set for changes of the capacity or of the handles.
+    ///
+    /// Must be called while holding the buffer's lock, after the change.
+    pub(crate) fn publish(&self, count: usize, capacity: usize, event: bool) {
+        let prev = self.word.load(Ordering::Relaxed);
+        let mut events = prev & EVENTS;
+        if event {
+            events = events.wrapping_add(EVENT) & EVENTS;
+        }
+        let truncated = if capacity > COUNT as usize {
+            TRUNCATED
+        } else {
+            0
+        };
+        let word = truncated | events | (count as u32 & COUNT);
+        if word == prev {
+            return;
+        }
+        self.word.store(word, Ordering::SeqCst);
+        // A thread that registers as waiter afterwards already sees the new word
+        // and does not block.
+        if self.waiters.load(Ordering::SeqCst) > 0 {
+            wake_all(&self.word);
+        }
+    }
+
+    /// Blocks until `ready` returns true or `timeout` has elapsed.
+    /// Returns whether the wait timed out.
+    pub(crate) fn wait<F>(&self, timeout: Duration, ready: F) -> bool
+    where
+        F: Fn() -> bool,
+    {
+        let start = Instant::now();
+        self.waiters.fetch_add(1, Ordering::SeqCst);
+        let timed_out = loop {
+            // Loaded before checking the condition, any later change of it changes the word.
+            let word = self.word.load(Ordering::SeqCst);
+            if ready() {
+                break false;
+            }
+            let elapsed = start.elapsed();
+            if elapsed >= timeout {
+                break true;
+            }
+            let remaining = if word & TRUNCATED != 0 {
+                Some(cmp::min(timeout - elapsed, TRUNCATED_POLL))
+            } else if timeout == Duration::MAX {
+                None
+            } else {
+                Some(timeout - elapsed)
+            };
+            wait(&self.word, word, remaining);
+        };
+        self.waiters.fetch_sub(1, Ordering::Relaxed);
+        timed_out
+    }
+}
+
+/// Blocks while `word` holds `expected`, but at most for `timeout`.
+///
+/// The call might return spuriously, the caller has to check its condition again.
+#[cfg(target_os = "linux")]
+pub(crate) fn wait(word: &AtomicU32, expected: u32, timeout: Option<Duration>) {
+    use std::ptr;
+
+    let timespec = timeout.and_then(|timeout| {
+        Some(libc::timespec {
+            tv_sec: timeout.as_secs().try_into().ok()?,
+            tv_nsec: timeout.subsec_nanos() as libc::c_long,
+        })
+    });
+    let timespec = timespec
+        .as_ref()
+        .map_or(ptr::null(), |timespec| timespec as *const libc::timespec);
+    // Safety: `word` is a valid 32 bit integer for the duration of the call
+    // and the kernel only reads it.
+    unsafe {
+        libc::syscall(
+            libc::SYS_futex,
+            word as *const AtomicU32,
+            libc::FUTEX_WAIT | libc::FUTEX_PRIVATE_FLAG,
+            expected,
+            timespec,
+        );
+    }
+}
+
+/// Wakes up all threads that are blocked on `word`.
+#[cfg(target_os = "linux")]
+pub(crate) fn wake_all(word: &AtomicU32) {
+    // Safety: see `wait`.
+    unsafe {
+        libc::syscall(
+            libc::SYS_futex,
+            word as *const AtomicU32,
+            libc::FUTEX_WAKE | libc::FUTEX_PRIVATE_FLAG,
+            i32::MAX,
+        );
+    }
+}
+
+/// Blocks while `word` holds `expected`, but at most for `timeout`.
+///
+/// The call might return spuriously, the caller has to check its condition again.
+#[cfg(windows)]
+pub(crate) fn wait(word: &AtomicU32, expected: u32, timeout: Option<Duration>) {
+    use windows_sys::Win32::System::Threading::{WaitOnAddress, INFINITE};
+
+    let millis = timeout.map_or(INFINITE, |timeout| {
+        // Rounded up, a zero timeout would not block at all.
+        let millis = (timeout.as_nanos() + 999_999) / 1_000_000;
+        millis.min(u128::from(INFINITE - 1)) as u32
+    });
+    // Safety: both addresses point to valid 32 bit integers for the duration of the call.
+    unsafe {
+        WaitOnAddress(
+            word as *const AtomicU32 as *const _,
+            &expected as *const u32 as *const _,
+            4,
+            millis,
+        );
+    }
+}
+
+/// Wakes up all threads that are blocked on `word`.
+#[cfg(windows)]
+pub(crate) fn wake_all(word: &AtomicU32) {
+    use windows_sys::Win32::System::Threading::WakeByAddressAll;
+
+    // Safety: see `wait`.
+    unsafe {
+        WakeByAddressAll(word as *const AtomicU32 as *const _);
+    }
+}
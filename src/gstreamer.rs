@@ -0,0 +1,205 @@
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use gst_app::gst;
+use gst_app::prelude::*;
+use gst_app::{AppSink, AppSinkCallbacks, AppSrc, AppStreamType};
+
+use {Consumer, Producer, RbConsumer, RbError, RbProducer};
+
+// Upper bound for the time the feeder thread needs to react on `stop`.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Element types that can be exchanged with GStreamer buffers.
+pub trait GstSample: Copy + Default + Send + 'static {
+    /// Returns the caps of a stream made of `Self` elements, or `None` if any stream is accepted.
+    fn caps() -> Option<gst::Caps>;
+    /// Appends the byte representation of `samples` to `bytes`.
+    fn extend_bytes(samples: &[Self], bytes: &mut Vec<u8>);
+    /// Appends the elements decoded from `bytes` to `samples`.
+    fn extend_samples(bytes: &[u8], samples: &mut Vec<Self>);
+
+    /// Returns true if `caps` describe a stream of `Self` elements. Missing caps are accepted.
+    fn accepts_caps(caps: Option<&gst::CapsRef>) -> bool {
+        match (caps, Self::caps()) {
+            (Some(caps), Some(expected)) => caps.is_subset(&expected),
+            _ => true,
+        }
+    }
+}
+
+impl GstSample for u8 {
+    fn caps() -> Option<gst::Caps> {
+        None
+    }
+
+    fn extend_bytes(samples: &[u8], bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(samples);
+    }
+
+    fn extend_samples(bytes: &[u8], samples: &mut Vec<u8>) {
+        samples.extend_from_slice(bytes);
+    }
+}
+
+impl GstSample for f32 {
+    fn caps() -> Option<gst::Caps> {
+        let format = if cfg!(target_endian = "little") {
+            "F32LE"
+        } else {
+            "F32BE"
+        };
+        Some(
+            gst::Caps::builder("audio/x-raw")
+                .field("format", format)
+                .field("layout", "interleaved")
+                .build(),
+        )
+    }
+
+    fn extend_bytes(samples: &[f32], bytes: &mut Vec<u8>) {
+        for sample in samples {
+            bytes.extend_from_slice(&sample.to_ne_bytes());
+        }
+    }
+
+    fn extend_samples(bytes: &[u8], samples: &mut Vec<f32>) {
+        for chunk in bytes.chunks_exact(mem::size_of::<f32>()) {
+            samples.push(f32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]));
+        }
+    }
+}
+
+/// Pushes everything read from a consumer into a GStreamer `appsrc`.
+///
+/// A background thread reads blocks of at most `block_size` elements and pushes each
+/// of them as one buffer. Caps that are already set on the `appsrc` must describe a stream
+/// of `T` elements, e.g. interleaved native-endian `F32` audio for `Consumer<f32>`.
+/// The `appsrc` blocks when its internal queue is full, so the pipeline throttles the
+/// consumer.
+pub struct AppSrcBridge {
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl AppSrcBridge {
+    /// Configures `appsrc` and starts feeding it from `consumer`.
+    ///
+    /// Possible errors:
+    ///
+    /// - the caps of `appsrc` do not describe a stream of `T` elements
+    pub fn spawn<T: GstSample>(
+        appsrc: AppSrc,
+        consumer: Consumer<T>,
+        block_size: usize,
+    ) -> Result<Self, gst::glib::BoolError> {
+        if !T::accepts_caps(appsrc.caps().as_deref()) {
+            return Err(gst::glib::bool_error!(
+                "appsrc caps are incompatible with the ring buffer element type"
+            ));
+        }
+        let block_size = block_size.max(1);
+        appsrc.set_stream_type(AppStreamType::Stream);
+        appsrc.set_block(true);
+        appsrc.set_property("blocksize", (block_size * mem::size_of::<T>()) as u32);
+
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+        let handle = thread::spawn(move || {
+            let mut block = vec![T::default(); block_size];
+            while thread_running.load(Ordering::Relaxed) {
+                let cnt = match consumer.read_blocking_timeout(&mut block, POLL_INTERVAL) {
+                    Ok(Some(cnt)) => cnt,
+                    // all producers are gone, nothing more will arrive
+                    Err(RbError::Disconnected) => break,
+                    _ => continue,
+                };
+                let mut bytes = Vec::with_capacity(cnt * mem::size_of::<T>());
+                T::extend_bytes(&block[..cnt], &mut bytes);
+                if appsrc
+                    .push_buffer(gst::Buffer::from_mut_slice(bytes))
+                    .is_err()
+                {
+                    // flushing or EOS, the pipeline does not accept any more data
+                    return;
+                }
+            }
+            let _ = appsrc.end_of_stream();
+        });
+        Ok(AppSrcBridge {
+            running,
+            handle: Some(handle),
+        })
+    }
+
+    /// Stops the feeder thread and signals end-of-stream to the `appsrc`.
+    pub fn stop(mut self) {
+        self.join();
+    }
+
+    fn join(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for AppSrcBridge {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
+
+/// Writes every buffer received by a GStreamer `appsink` into `producer`.
+///
+/// The caps of `appsink` are restricted to streams of `T` elements, so upstream elements
+/// negotiate a compatible format. Samples are written blocking on the streaming thread,
+/// i.e. a full ring buffer throttles the pipeline. Once all consumers were dropped the
+/// appsink reports end-of-stream.
+pub fn connect_appsink<T: GstSample>(appsink: &AppSink, producer: Producer<T>) {
+    appsink.set_caps(T::caps().as_ref());
+    let mut samples = Vec::new();
+    appsink.set_callbacks(
+        AppSinkCallbacks::builder()
+            .new_sample(move |appsink| {
+                let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+                write_sample(&producer, &sample, &mut samples)
+            })
+            .build(),
+    );
+}
+
+/// Writes the elements of `sample` into `producer`, blocking until all of them fit. This is
+/// what `connect_appsink` does for every sample that the `appsink` receives, `samples` is
+/// reused for the decoded elements.
+///
+/// Possible errors:
+///
+/// - `gst::FlowError::NotNegotiated` the caps of `sample` do not describe a stream of `T` elements
+/// - `gst::FlowError::Error` the buffer of `sample` is missing or can't be read
+/// - `gst::FlowError::Eos` all consumers were dropped
+pub fn write_sample<T: GstSample>(
+    producer: &Producer<T>,
+    sample: &gst::SampleRef,
+    samples: &mut Vec<T>,
+) -> Result<gst::FlowSuccess, gst::FlowError> {
+    if !T::accepts_caps(sample.caps()) {
+        return Err(gst::FlowError::NotNegotiated);
+    }
+    let buffer = sample.buffer().ok_or(gst::FlowError::Error)?;
+    let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
+    samples.clear();
+    T::extend_samples(&map, samples);
+    let mut written = 0;
+    while written < samples.len() {
+        // `None` means that all consumers were dropped
+        written += producer
+            .write_blocking(&samples[written..])
+            .ok_or(gst::FlowError::Eos)?;
+    }
+    Ok(gst::FlowSuccess::Ok)
+}
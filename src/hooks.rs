@@ -0,0 +1,101 @@
+use std::sync::{Arc, OnceLock};
+
+/// Side of a buffer, see `RbHooks`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    /// The producers, which block while the buffer is full.
+    Producer,
+    /// The consumers, which block while the buffer is empty.
+    Consumer,
+}
+
+/// Callbacks for the events of a buffer, e.g. to feed a metrics backend of choice,
+/// see `SpscRb::with_hooks`. All methods do nothing by default.
+///
+/// The hooks run on the thread of the handle while the buffer is locked, thus they should
+/// return quickly and must not access the buffer.
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+/// use rb::*;
+///
+/// #[derive(Default)]
+/// struct Throughput(AtomicUsize);
+///
+/// impl RbHooks for Throughput {
+///     fn on_write(&self, n: usize) {
+///         self.0.fetch_add(n, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let throughput = Arc::new(Throughput::default());
+/// let rb = SpscRb::new(8).with_hooks(throughput.clone());
+/// rb.producer().write(&[1, 2, 3]).unwrap();
+/// assert_eq!(throughput.0.load(Ordering::Relaxed), 3);
+/// ```
+pub trait RbHooks: Send + Sync {
+    /// Called after `n` values were written, `n` is never zero.
+    fn on_write(&self, _n: usize) {}
+    /// Called after `n` values were read or skipped, `n` is never zero.
+    fn on_read(&self, _n: usize) {}
+    /// Called before a handle of `side` blocks.
+    fn on_block_start(&self, _side: Side) {}
+    /// Called once a handle of `side` stopped blocking, regardless of whether
+    /// the wait succeeded.
+    fn on_block_end(&self, _side: Side) {}
+}
+
+/// Allows to keep access to the hooks, e.g. to read the metrics they collect.
+impl<H: RbHooks + ?Sized> RbHooks for Arc<H> {
+    fn on_write(&self, n: usize) {
+        (**self).on_write(n)
+    }
+    fn on_read(&self, n: usize) {
+        (**self).on_read(n)
+    }
+    fn on_block_start(&self, side: Side) {
+        (**self).on_block_start(side)
+    }
+    fn on_block_end(&self, side: Side) {
+        (**self).on_block_end(side)
+    }
+}
+
+/// Forwards to the hooks of a buffer, if any were installed.
+#[derive(Default)]
+pub(crate) struct Hooks(OnceLock<Box<dyn RbHooks>>);
+
+impl Hooks {
+    pub(crate) fn install(&self, hooks: Box<dyn RbHooks>) {
+        assert!(self.0.set(hooks).is_ok(), "the hooks are already installed");
+    }
+
+    #[inline(always)]
+    pub(crate) fn on_write(&self, n: usize) {
+        if let (Some(hooks), true) = (self.0.get(), n > 0) {
+            hooks.on_write(n);
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn on_read(&self, n: usize) {
+        if let (Some(hooks), true) = (self.0.get(), n > 0) {
+            hooks.on_read(n);
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn on_block_start(&self, side: Side) {
+        if let Some(hooks) = self.0.get() {
+            hooks.on_block_start(side);
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn on_block_end(&self, side: Side) {
+        if let Some(hooks) = self.0.get() {
+            hooks.on_block_end(side);
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::cmp;
+
+use {Consumer, Producer, RbError, Result, Storage};
+
+impl<T: Clone + Default, S: Storage<T>> Producer<T, S> {
+    /// Writes the samples of separate `channels` into the buffer as interleaved frames, i.e.
+    /// the first sample of every channel, then the second one and so on, without a scratch
+    /// buffer. Only complete frames are written, as many as there are free slots for and at
+    /// most the length of the shortest channel.
+    ///
+    /// Returns the number of written frames.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(8);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// let (left, right) = ([1, 2, 3], [-1, -2, -3]);
+    /// assert_eq!(prod.write_interleaved(&[&left, &right]).unwrap(), 3);
+    /// assert_eq!(cons.read_vec(8), vec![1, -1, 2, -2, 3, -3]);
+    /// ```
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Full` less free slots than channels
+    /// - `RbError::Disconnected` all consumers were dropped
+    pub fn write_interleaved(&self, channels: &[&[T]]) -> Result<usize> {
+        let frames = channels.iter().map(|channel| channel.len()).min();
+        let (n, frames) = match frames {
+            Some(frames) if frames > 0 => (channels.len(), frames),
+            _ => return Ok(0),
+        };
+        let written = self.write_access(|head, tail| {
+            let frames = cmp::min(frames, (head.len() + tail.len()) / n);
+            let in_head = cmp::min(frames * n, head.len());
+            interleave(&mut head[..in_head], channels, 0);
+            interleave(&mut tail[..frames * n - in_head], channels, in_head);
+            frames * n
+        })?;
+        if written == 0 {
+            return Err(RbError::Full);
+        }
+        Ok(written / n)
+    }
+}
+
+impl<T: Clone, S: Storage<T>> Consumer<T, S> {
+    /// Reads interleaved frames from the buffer into separate `channels`, see
+    /// `Producer::write_interleaved`. Only complete frames are read, as many as are pending
+    /// and at most the length of the shortest channel.
+    ///
+    /// Returns the number of read frames.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(8);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write(&[1, -1, 2, -2, 3]).unwrap();
+    /// let (mut left, mut right) = ([0; 4], [0; 4]);
+    /// assert_eq!(cons.read_deinterleaved(&mut [&mut left, &mut right]).unwrap(), 2);
+    /// assert_eq!((left, right), ([1, 2, 0, 0], [-1, -2, 0, 0]));
+    /// assert_eq!(cons.count(), 1);
+    /// ```
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Empty` less pending values than channels
+    /// - `RbError::Disconnected` the buffer is empty and all producers were dropped
+    pub fn read_deinterleaved(&self, channels: &mut [&mut [T]]) -> Result<usize> {
+        let frames = channels.iter().map(|channel| channel.len()).min();
+        let (n, frames) = match frames {
+            Some(frames) if frames > 0 => (channels.len(), frames),
+            _ => return Ok(0),
+        };
+        let consumed = self.read_access(|head, tail| {
+            let frames = cmp::min(frames, (head.len() + tail.len()) / n);
+            let in_head = cmp::min(frames * n, head.len());
+            deinterleave(&head[..in_head], channels, 0);
+            deinterleave(&tail[..frames * n - in_head], channels, in_head);
+            frames * n
+        })?;
+        if consumed == 0 {
+            return Err(RbError::Empty);
+        }
+        Ok(consumed / n)
+    }
+}
+
+/// Clones the samples of `channels` into `slots` as interleaved frames, the first slot takes
+/// the interleaved sample at `pos`. The channels must be long enough for all frames.
+///
+/// Whole frames are copied by loops without divisions, which the compiler vectorizes for
+/// `Copy` samples, in particular the ones of stereo frames. Frames that are cut off by the
+/// end of the ring are copied sample by sample.
+fn interleave<T: Clone>(slots: &mut [T], channels: &[&[T]], pos: usize) {
+    let n = channels.len();
+    let lead = cmp::min((n - pos % n) % n, slots.len());
+    let (lead_slots, slots) = slots.split_at_mut(lead);
+    for (i, slot) in (pos..).zip(lead_slots) {
+        slot.clone_from(&channels[i % n][i / n]);
+    }
+    let first = (pos + lead) / n;
+    let last = first + slots.len() / n;
+    let mut frames = slots.chunks_exact_mut(n);
+    if let [left, right] = channels {
+        let samples = left[first..last].iter().zip(&right[first..last]);
+        for (frame, (l, r)) in (&mut frames).zip(samples) {
+            frame[0].clone_from(l);
+            frame[1].clone_from(r);
+        }
+    } else {
+        for (i, frame) in (first..).zip(&mut frames) {
+            for (slot, channel) in frame.iter_mut().zip(channels) {
+                slot.clone_from(&channel[i]);
+            }
+        }
+    }
+    for (slot, channel) in frames.into_remainder().iter_mut().zip(channels) {
+        slot.clone_from(&channel[last]);
+    }
+}
+
+/// Clones the interleaved `values` into `channels`, the first value is the interleaved
+/// sample at `pos`, see `interleave`.
+fn deinterleave<T: Clone>(values: &[T], channels: &mut [&mut [T]], pos: usize) {
+    let n = channels.len();
+    let lead = cmp::min((n - pos % n) % n, values.len());
+    let (lead_values, values) = values.split_at(lead);
+    for (i, value) in (pos..).zip(lead_values) {
+        channels[i % n][i / n].clone_from(value);
+    }
+    let first = (pos + lead) / n;
+    let last = first + values.len() / n;
+    let mut frames = values.chunks_exact(n);
+    if let [left, right] = channels {
+        let samples = left[first..last].iter_mut().zip(&mut right[first..last]);
+        for (frame, (l, r)) in (&mut frames).zip(samples) {
+            l.clone_from(&frame[0]);
+            r.clone_from(&frame[1]);
+        }
+    } else {
+        for (i, frame) in (first..).zip(&mut frames) {
+            for (value, channel) in frame.iter().zip(channels.iter_mut()) {
+                channel[i].clone_from(value);
+            }
+        }
+    }
+    for (value, channel) in frames.remainder().iter().zip(channels.iter_mut()) {
+        channel[last].clone_from(value);
+    }
+}
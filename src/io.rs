@@ -0,0 +1,189 @@
+use std::cmp;
+use std::io;
+use std::mem;
+
+use {Consumer, Producer, RbConsumer, RbError, RbProducer, Storage};
+
+/// Number of bytes `io::BufRead::fill_buf` takes from the ring at most.
+const IO_BUF_SIZE: usize = 8 * 1024;
+
+impl<T, S> Consumer<T, S> {
+    /// Moves the consumer into or out of nonblocking mode, which only affects `io::Read`.
+    /// In nonblocking mode reading from an empty buffer fails with `io::ErrorKind::WouldBlock`
+    /// instead of blocking until data is available.
+    pub fn set_nonblocking(&mut self, nonblocking: bool) {
+        self.nonblocking = nonblocking;
+    }
+}
+
+/// Reads block until data is available, unless the consumer is in nonblocking mode.
+/// Once all producers were dropped and the buffer is drained, reads return `Ok(0)`.
+///
+/// ```
+/// use std::io::{ErrorKind, Read};
+/// use rb::*;
+///
+/// let rb = SpscRb::new(16);
+/// let (prod, mut cons) = (rb.producer(), rb.consumer());
+/// cons.set_nonblocking(true);
+/// prod.write(b"rb").unwrap();
+/// let mut text = String::new();
+/// let err = cons.read_to_string(&mut text).unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::WouldBlock);
+/// assert_eq!(text, "rb");
+/// ```
+impl<S: Storage<u8>> io::Read for Consumer<u8, S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.io_pos < self.io_buf.len() {
+            let pending = &self.io_buf[self.io_pos..];
+            let cnt = cmp::min(buf.len(), pending.len());
+            buf[..cnt].copy_from_slice(&pending[..cnt]);
+            self.io_pos += cnt;
+            return Ok(cnt);
+        }
+        self.read_ring(buf)
+    }
+}
+
+impl<S: Storage<u8>> Consumer<u8, S> {
+    fn read_ring(&self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.nonblocking {
+            return Ok(self.read_blocking(buf).unwrap_or(0));
+        }
+        match RbConsumer::read(self, buf) {
+            Ok(cnt) => Ok(cnt),
+            Err(RbError::Disconnected) => Ok(0),
+            Err(_) => Err(io::ErrorKind::WouldBlock.into()),
+        }
+    }
+}
+
+/// `fill_buf` takes up to 8 KiB from the ring, these bytes are no longer pending for
+/// `RbConsumer` methods, but only returned by `io::Read` and `io::BufRead`.
+///
+/// ```
+/// use std::io::BufRead;
+/// use rb::*;
+///
+/// let rb = SpscRb::new(16);
+/// let (prod, mut cons) = (rb.producer(), rb.consumer());
+/// prod.write(b"first\nsecond\n").unwrap();
+/// let mut line = Vec::new();
+/// cons.read_until(b'\n', &mut line).unwrap();
+/// assert_eq!(line, b"first\n");
+/// ```
+impl<S: Storage<u8>> io::BufRead for Consumer<u8, S> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.io_pos == self.io_buf.len() {
+            let mut buf = mem::take(&mut self.io_buf);
+            buf.resize(IO_BUF_SIZE, 0);
+            let result = self.read_ring(&mut buf);
+            buf.truncate(*result.as_ref().unwrap_or(&0));
+            self.io_buf = buf;
+            self.io_pos = 0;
+            result?;
+        }
+        Ok(&self.io_buf[self.io_pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.io_pos = cmp::min(self.io_pos + amt, self.io_buf.len());
+    }
+}
+
+/// Writes block until there is at least one free slot in the buffer,
+/// they fail with `io::ErrorKind::BrokenPipe` once all consumers were dropped.
+///
+/// ```
+/// use std::io::Write;
+/// use rb::*;
+///
+/// let rb = SpscRb::new(16);
+/// let (mut prod, cons) = (rb.producer(), rb.consumer());
+/// write!(prod, "{}-{}", 4, 2).unwrap();
+/// let mut data = [0; 3];
+/// assert_eq!(cons.read(&mut data).unwrap(), 3);
+/// assert_eq!(&data, b"4-2");
+/// ```
+impl<S: Storage<u8>> io::Write for Producer<u8, S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.write_blocking(buf) {
+            Some(cnt) => Ok(cnt),
+            None if buf.is_empty() => Ok(0),
+            None => Err(io::ErrorKind::BrokenPipe.into()),
+        }
+    }
+
+    /// Written bytes are visible to the consumer right away, thus there is nothing to flush.
+    /// Use `Producer::flush` to wait until the consumer has read them.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Copies the bytes of `reader` into the buffer until `reader` reaches its end or the buffer
+/// is disconnected, e.g. by `SpscRb::close`, blocking while the buffer is full.
+/// Use `io::Read::take` to copy at most a number of bytes.
+///
+/// Returns the number of bytes that were written into the buffer, the bytes that were read
+/// after the consumers disconnected are dropped. Reads that fail with
+/// `io::ErrorKind::Interrupted` are retried, other errors are returned.
+///
+/// ```
+/// use std::thread;
+/// use rb::*;
+///
+/// let (prod, cons) = SpscRb::new(4).into_split();
+/// let pump = thread::spawn(move || pump_from(&b"hello world"[..], &prod).unwrap());
+/// let mut data = Vec::new();
+/// assert_eq!(pump_into(&cons, &mut data).unwrap(), 11);
+/// assert_eq!(pump.join().unwrap(), 11);
+/// assert_eq!(data, b"hello world");
+/// ```
+pub fn pump_from<R, S>(mut reader: R, producer: &Producer<u8, S>) -> io::Result<u64>
+where
+    R: io::Read,
+    S: Storage<u8>,
+{
+    let mut buf = vec![0; IO_BUF_SIZE];
+    let mut total = 0;
+    loop {
+        let cnt = match reader.read(&mut buf) {
+            Ok(0) => return Ok(total),
+            Ok(cnt) => cnt,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        let mut written = 0;
+        while written < cnt {
+            match producer.write_blocking(&buf[written..cnt]) {
+                Some(n) => written += n,
+                None => return Ok(total + written as u64),
+            }
+        }
+        total += cnt as u64;
+    }
+}
+
+/// Copies the bytes of the buffer into `writer` until all producers were dropped or the
+/// buffer was closed, and the buffer is drained, blocking while the buffer is empty.
+/// `writer` is flushed at the end. Bytes that `io::BufRead` took from the ring already are
+/// not copied.
+///
+/// Returns the number of bytes that were written into `writer`. Writes that fail with
+/// `io::ErrorKind::Interrupted` are retried, other errors are returned and the bytes of
+/// the failed write are lost.
+pub fn pump_into<W, S>(consumer: &Consumer<u8, S>, mut writer: W) -> io::Result<u64>
+where
+    W: io::Write,
+    S: Storage<u8>,
+{
+    let mut buf = vec![0; IO_BUF_SIZE];
+    let mut total = 0;
+    while let Some(cnt) = consumer.read_blocking(&mut buf) {
+        writer.write_all(&buf[..cnt])?;
+        total += cnt as u64;
+    }
+    writer.flush()?;
+    Ok(total)
+}
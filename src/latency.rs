@@ -0,0 +1,162 @@
+use std::cmp;
+use std::collections::VecDeque;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use sync::Mutex;
+
+/// Snapshot of the time values spent in a buffer, see `SpscRb::with_latency`.
+///
+/// Every read that returns values is one sample, the age of the oldest of them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Latency {
+    /// Number of samples.
+    pub samples: usize,
+    /// Shortest age, zero without samples.
+    pub min: Duration,
+    /// Average age, zero without samples.
+    pub avg: Duration,
+    /// Longest age, zero without samples.
+    pub max: Duration,
+}
+
+/// Times of the pending values, one entry per write with the number of its values that
+/// are still pending, the oldest first.
+#[derive(Default)]
+struct Timeline {
+    writes: VecDeque<(Instant, usize)>,
+    samples: usize,
+    min: Duration,
+    total: Duration,
+    max: Duration,
+}
+
+impl Timeline {
+    /// Removes the oldest `cnt` values.
+    fn pop_front(&mut self, mut cnt: usize) {
+        while let Some(front) = self.writes.front_mut() {
+            if front.1 > cnt {
+                front.1 -= cnt;
+                return;
+            }
+            cnt -= front.1;
+            self.writes.pop_front();
+        }
+    }
+}
+
+/// Collects the latency of a buffer once enabled, otherwise all updates are no-ops.
+///
+/// All updates are made while holding the buffer's lock, thus the timeline follows the
+/// order of the writes and reads.
+#[derive(Default)]
+pub(crate) struct LatencyCollector(OnceLock<Mutex<Timeline>>);
+
+impl LatencyCollector {
+    /// Enables the collector, the `count` values that are already pending are assumed to
+    /// be written now.
+    pub(crate) fn enable(&self, count: usize) {
+        let mut timeline = Timeline::default();
+        if count > 0 {
+            timeline.writes.push_back((Instant::now(), count));
+        }
+        let _ = self.0.set(Mutex::new(timeline));
+    }
+
+    /// Records the time of a write of `cnt` values.
+    #[inline(always)]
+    pub(crate) fn on_write(&self, cnt: usize) {
+        if let (Some(timeline), true) = (self.0.get(), cnt > 0) {
+            timeline.lock().writes.push_back((Instant::now(), cnt));
+        }
+    }
+
+    /// Records the age of the oldest of `cnt` read values.
+    #[inline(always)]
+    pub(crate) fn on_read(&self, cnt: usize) {
+        if let (Some(timeline), true) = (self.0.get(), cnt > 0) {
+            let mut timeline = timeline.lock();
+            if let Some(&(written, _)) = timeline.writes.front() {
+                let age = written.elapsed();
+                timeline.min = match timeline.samples {
+                    0 => age,
+                    _ => cmp::min(timeline.min, age),
+                };
+                timeline.max = cmp::max(timeline.max, age);
+                timeline.total += age;
+                timeline.samples += 1;
+            }
+            timeline.pop_front(cnt);
+        }
+    }
+
+    /// Forgets the oldest `cnt` pending values, which were dropped without being read.
+    pub(crate) fn discard(&self, cnt: usize) {
+        if let Some(timeline) = self.0.get() {
+            timeline.lock().pop_front(cnt);
+        }
+    }
+
+    /// Forgets the newest `cnt` pending values, see `Producer::rollback`.
+    pub(crate) fn retract(&self, mut cnt: usize) {
+        if let Some(timeline) = self.0.get() {
+            let mut timeline = timeline.lock();
+            while let Some(back) = timeline.writes.back_mut() {
+                if back.1 > cnt {
+                    back.1 -= cnt;
+                    return;
+                }
+                cnt -= back.1;
+                timeline.writes.pop_back();
+            }
+        }
+    }
+
+    /// Adds `cnt` values that are pending again, see `Consumer::unread`. Their time is
+    /// gone, they are assumed to be as old as the oldest pending value or written now.
+    pub(crate) fn unread(&self, cnt: usize) {
+        if let Some(timeline) = self.0.get() {
+            let mut timeline = timeline.lock();
+            match timeline.writes.front_mut() {
+                Some(front) => front.1 += cnt,
+                None => timeline.writes.push_back((Instant::now(), cnt)),
+            }
+        }
+    }
+
+    /// Forgets all pending values, e.g. once the buffer was cleared.
+    pub(crate) fn discard_all(&self) {
+        if let Some(timeline) = self.0.get() {
+            timeline.lock().writes.clear();
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> Latency {
+        let timeline = match self.0.get() {
+            Some(timeline) => timeline.lock(),
+            None => return Latency::default(),
+        };
+        Latency {
+            samples: timeline.samples,
+            min: timeline.min,
+            avg: match timeline.samples {
+                0 => Duration::ZERO,
+                samples => {
+                    Duration::from_nanos((timeline.total.as_nanos() / samples as u128) as u64)
+                }
+            },
+            max: timeline.max,
+        }
+    }
+
+    /// Resets the samples, the times of the pending values are kept.
+    pub(crate) fn reset(&self) {
+        if let Some(timeline) = self.0.get() {
+            let mut timeline = timeline.lock();
+            timeline.samples = 0;
+            timeline.min = Duration::ZERO;
+            timeline.total = Duration::ZERO;
+            timeline.max = Duration::ZERO;
+        }
+    }
+}
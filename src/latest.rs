@@ -0,0 +1,134 @@
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use sync::{Mutex, MutexGuard};
+
+// Set in `back` when the back slot holds a value that the consumer has not seen yet.
+const FRESH: usize = 0b100;
+const INDEX: usize = 0b011;
+
+/// A triple buffer that always hands the most recent value to the consumer,
+/// e.g. to pass control parameters from a GUI thread to an audio thread.
+///
+/// Writes never block or fail and the consumer skips all values that were overwritten
+/// by newer ones. Producer and consumer each own one of three slots, the third one is
+/// exchanged with an atomic swap, thus neither side ever waits for the other one.
+///
+/// ```
+/// use rb::*;
+///
+/// let (mut prod, mut cons) = LatestRb::new(vec![0.0f32; 4]).split();
+/// prod.write(vec![0.25; 4]);
+/// prod.write_with(|block| block.iter_mut().for_each(|x| *x = 0.5));
+/// assert!(cons.has_update());
+/// assert_eq!(*cons.read(), vec![0.5; 4]);
+/// assert!(!cons.has_update());
+/// // the value stays readable until a newer one is written
+/// assert_eq!(*cons.read(), vec![0.5; 4]);
+/// ```
+pub struct LatestRb<T> {
+    shared: Arc<Shared<T>>,
+}
+
+struct Shared<T> {
+    // Each slot is either owned by the producer, the consumer or is the back slot,
+    // hence the locks are never contended.
+    slots: [Mutex<T>; 3],
+    // Index of the back slot and the `FRESH` flag.
+    back: AtomicUsize,
+}
+
+impl<T: Clone> LatestRb<T> {
+    /// Creates a buffer whose consumer reads `initial` until the first value is written.
+    pub fn new(initial: T) -> Self {
+        LatestRb {
+            shared: Arc::new(Shared {
+                slots: [
+                    Mutex::new(initial.clone()),
+                    Mutex::new(initial.clone()),
+                    Mutex::new(initial),
+                ],
+                back: AtomicUsize::new(2),
+            }),
+        }
+    }
+}
+
+impl<T> LatestRb<T> {
+    /// Returns the producer and the consumer of the buffer.
+    pub fn split(self) -> (LatestProducer<T>, LatestConsumer<T>) {
+        (
+            LatestProducer {
+                shared: self.shared.clone(),
+                index: 0,
+            },
+            LatestConsumer {
+                shared: self.shared,
+                index: 1,
+            },
+        )
+    }
+}
+
+/// Producer of a `LatestRb`.
+pub struct LatestProducer<T> {
+    shared: Arc<Shared<T>>,
+    // Slot that is written next.
+    index: usize,
+}
+
+impl<T> LatestProducer<T> {
+    /// Publishes `value`, it replaces the previous one if the consumer has not read it yet.
+    pub fn write(&mut self, value: T) {
+        self.write_with(|slot| *slot = value);
+    }
+
+    /// Updates a slot in place and publishes it, e.g. to fill a block without allocating.
+    ///
+    /// The slot holds an older value, not necessarily the last written one,
+    /// thus `f` has to overwrite all of it.
+    pub fn write_with<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut T),
+    {
+        f(&mut self.shared.slots[self.index].lock());
+        let back = self.shared.back.swap(self.index | FRESH, Ordering::AcqRel);
+        self.index = back & INDEX;
+    }
+}
+
+/// Consumer of a `LatestRb`.
+pub struct LatestConsumer<T> {
+    shared: Arc<Shared<T>>,
+    // Slot that holds the last read value.
+    index: usize,
+}
+
+impl<T> LatestConsumer<T> {
+    /// Returns true if a value was written since the last read.
+    pub fn has_update(&self) -> bool {
+        self.shared.back.load(Ordering::Relaxed) & FRESH != 0
+    }
+
+    /// Returns the most recently written value, or the last read one if nothing was written
+    /// in the meantime.
+    pub fn read(&mut self) -> LatestRef<'_, T> {
+        if self.has_update() {
+            let back = self.shared.back.swap(self.index, Ordering::AcqRel);
+            self.index = back & INDEX;
+        }
+        LatestRef(self.shared.slots[self.index].lock())
+    }
+}
+
+/// Value returned by `LatestConsumer::read`, it dereferences to the latest value.
+pub struct LatestRef<'a, T: 'a>(MutexGuard<'a, T>);
+
+impl<'a, T> Deref for LatestRef<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
@@ -1,22 +1,203 @@
-#[cfg(test)]
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(any(feature = "async", feature = "static-async"))]
+extern crate atomic_waker;
+#[cfg(feature = "std")]
+extern crate core;
+#[cfg(feature = "cpal")]
+extern crate cpal;
+#[cfg(feature = "gstreamer")]
+extern crate gstreamer_app as gst_app;
+#[cfg(feature = "hound")]
+extern crate hound;
+#[cfg(any(
+    all(feature = "futex", target_os = "linux"),
+    all(any(feature = "mmap", feature = "readiness"), unix)
+))]
+extern crate libc;
+#[cfg(feature = "pulseaudio")]
+extern crate libpulse_binding as pulse;
+#[cfg(feature = "pulseaudio")]
+extern crate libpulse_simple_binding as psimple;
+#[cfg(all(feature = "mio", unix))]
+extern crate mio;
+#[cfg(feature = "parking_lot")]
+extern crate parking_lot;
+#[cfg(feature = "symphonia")]
+extern crate symphonia_core;
+#[cfg(feature = "tokio")]
+extern crate tokio;
+#[cfg(feature = "tracing")]
+extern crate tracing;
+#[cfg(all(feature = "futex", windows))]
+extern crate windows_sys;
+#[cfg(feature = "zeroize")]
+extern crate zeroize;
+
+#[cfg(feature = "alloc")]
+mod aligned;
+#[cfg(feature = "async")]
+mod async_rb;
+#[cfg(feature = "std")]
+mod broadcast;
+#[cfg(feature = "std")]
+mod builder;
+#[cfg(feature = "std")]
+mod byte_rb;
+#[cfg(feature = "std")]
+mod channel;
+#[cfg(feature = "alloc")]
+mod copy;
+#[cfg(feature = "cpal")]
+mod cpal_stream;
+#[cfg(feature = "std")]
+mod duplex;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+mod frame;
+#[cfg(feature = "std")]
+mod frame_rb;
+#[cfg(all(feature = "futex", any(target_os = "linux", windows)))]
+mod futex;
+#[cfg(feature = "gstreamer")]
+mod gstreamer;
+#[cfg(feature = "std")]
+mod hooks;
+#[cfg(feature = "std")]
+mod interleave;
+#[cfg(feature = "std")]
+mod io;
+#[cfg(feature = "std")]
+mod latency;
+#[cfg(feature = "std")]
+mod latest;
+#[cfg(feature = "symphonia")]
+mod media_source;
+#[cfg(feature = "std")]
+mod message;
+#[cfg(all(feature = "mmap", unix))]
+mod mmap;
+#[cfg(feature = "std")]
+mod mpmc;
+#[cfg(feature = "std")]
+mod mpsc;
+#[cfg(feature = "alloc")]
+mod overflow;
+#[cfg(feature = "std")]
+mod planar;
+#[cfg(feature = "std")]
+mod pool;
+#[cfg(feature = "pulseaudio")]
+mod pulseaudio;
+#[cfg(all(feature = "readiness", unix))]
+mod readiness;
+#[cfg(feature = "std")]
+mod select;
+#[cfg(all(feature = "shm", unix))]
+mod shm;
+#[cfg(feature = "alloc")]
+mod spsc;
+mod static_rb;
+#[cfg(feature = "std")]
+mod stats;
+#[cfg(feature = "alloc")]
+mod sync;
+#[cfg(feature = "std")]
+mod tee;
+#[cfg(all(test, feature = "std"))]
 mod tests;
-
-use std::cmp;
-use std::fmt;
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::{Arc, Condvar, Mutex};
-use std::time::Duration;
-
-/// Managment interface for the ring buffer.
-pub trait RB<T: Clone + Copy + Default> {
-    /// Resets the whole buffer to the default value of type `T`.
-    /// The buffer is empty after this call.
-    fn clear(&self);
-    /// Creates a *producer* view inside the buffer.
-    fn producer(&self) -> Producer<T>;
-    /// Creates a *consumer* view inside the buffer.
-    fn consumer(&self) -> Consumer<T>;
-}
+#[cfg(feature = "std")]
+mod wait;
+#[cfg(feature = "std")]
+mod watchdog;
+#[cfg(feature = "alloc")]
+mod watermark;
+#[cfg(feature = "hound")]
+mod wav;
+
+#[cfg(feature = "alloc")]
+pub use aligned::AlignedStorage;
+#[cfg(feature = "async")]
+pub use async_rb::{AsyncConsumer, AsyncProducer, ReadFuture, WriteFuture};
+#[cfg(feature = "std")]
+pub use broadcast::{BroadcastConsumer, BroadcastProducer, BroadcastRb};
+#[cfg(feature = "std")]
+pub use builder::SpscRbBuilder;
+#[cfg(feature = "std")]
+pub use byte_rb::{ByteRb, ByteReader, ByteWriter};
+#[cfg(feature = "std")]
+pub use channel::{
+    channel, pump_from_receiver, IntoRecvIter, Receiver, RecvIter, Sender, TryRecvIter,
+};
+#[cfg(feature = "cpal")]
+pub use cpal_stream::{input_callback, output_callback, CpalStream};
+#[cfg(feature = "std")]
+pub use duplex::{duplex, Endpoint};
+#[cfg(feature = "std")]
+pub use frame_rb::{FrameConsumer, FrameProducer, FrameRb};
+#[cfg(feature = "gstreamer")]
+pub use gstreamer::{connect_appsink, write_sample, AppSrcBridge, GstSample};
+#[cfg(feature = "std")]
+pub use hooks::{RbHooks, Side};
+#[cfg(feature = "std")]
+pub use io::{pump_from, pump_into};
+#[cfg(feature = "std")]
+pub use latency::Latency;
+#[cfg(feature = "std")]
+pub use latest::{LatestConsumer, LatestProducer, LatestRb, LatestRef};
+#[cfg(feature = "symphonia")]
+pub use media_source::ConsumerSource;
+#[cfg(feature = "std")]
+pub use message::{MessageConsumer, MessageProducer, MessageRb};
+#[cfg(all(feature = "mmap", unix))]
+pub use mmap::MmapRb;
+#[cfg(feature = "std")]
+pub use mpmc::{MpmcConsumer, MpmcProducer, MpmcRb};
+#[cfg(feature = "std")]
+pub use mpsc::{MpscProducer, MpscRb};
+#[cfg(feature = "alloc")]
+pub use overflow::OverflowPolicy;
+#[cfg(feature = "std")]
+pub use planar::{PlanarConsumer, PlanarProducer, PlanarRb};
+#[cfg(feature = "std")]
+pub use pool::RbPool;
+#[cfg(feature = "pulseaudio")]
+pub use pulseaudio::{capture_callback, playback_callback, PulseStream};
+#[cfg(all(feature = "readiness", unix))]
+pub use readiness::Readiness;
+#[cfg(feature = "std")]
+pub use select::select;
+#[cfg(all(feature = "shm", unix))]
+pub use shm::{Pod, ShmConsumer, ShmProducer, ShmSpscRb};
+#[cfg(feature = "alloc")]
+pub use spsc::{
+    transfer, ConstSpscRb, Consumer, Producer, ReadGrant, SpscRb, Storage, WriteGrant, RB,
+};
+pub use static_rb::{StaticConsumer, StaticProducer, StaticSpscRb};
+#[cfg(feature = "static-async")]
+pub use static_rb::{StaticReadFuture, StaticWriteFuture};
+#[cfg(feature = "std")]
+pub use stats::Stats;
+#[cfg(feature = "std")]
+pub use tee::Tee;
+#[cfg(feature = "std")]
+pub use wait::WaitStrategy;
+#[cfg(feature = "std")]
+pub use watchdog::{WatchdogThread, Xrun, XrunKind, XrunWatchdog};
+#[cfg(feature = "alloc")]
+pub use watermark::Watermark;
+#[cfg(feature = "hound")]
+pub use wav::WavDrain;
+
+use core::fmt;
+#[cfg(feature = "std")]
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::time::Instant;
 
 /// RbInspector provides non-modifying operations on the ring buffer.
 pub trait RbInspector {
@@ -34,19 +215,36 @@ pub trait RbInspector {
 }
 
 /// Defines *write* methods for a producer view.
+///
+/// Closing the buffer disconnects the producer just like dropping all consumers.
 pub trait RbProducer<T> {
     /// Stores the given slice of data into the ring buffer.
     /// Returns the number of written elements or an error.
     ///
+    /// The producers of `SpscRb` and its variants handle a full buffer according to the
+    /// `OverflowPolicy` of the buffer, by default they write as many elements as fit.
+    ///
     /// Possible errors:
     ///
     /// - `RbError::Full`
+    /// - `RbError::Disconnected` the buffer was closed
     fn write(&self, data: &[T]) -> Result<usize>;
+    /// Works analog to `write` but either stores the whole slice or nothing at all,
+    /// so records are never split.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Full` less free slots than the length of `data`
+    /// - `RbError::Disconnected` the buffer was closed
+    fn write_exact(&self, data: &[T]) -> Result<()>;
+    #[cfg(feature = "std")]
     /// Works analog to `write` but blocks until there are free slots in the ring buffer.
     /// The number of actual blocks written is returned in the `Option` value.
     ///
-    /// Returns `None` if the given slice has zero length.
+    /// Returns `None` if the given slice has zero length
+    /// or all consumers were dropped.
     fn write_blocking(&self, data: &[T]) -> Option<usize>;
+    #[cfg(feature = "std")]
     /// Works analog to `write_blocking` but eventually returns if the specified timeout is reached.
     /// The number of actual blocks written is returned in the `Ok(Option)` value.
     ///
@@ -55,10 +253,25 @@ pub trait RbProducer<T> {
     /// Possible errors:
     ///
     /// - `RbError::TimedOut`
+    /// - `RbError::Disconnected` all consumers were dropped
     fn write_blocking_timeout(&self, data: &[T], timeout: Duration) -> Result<Option<usize>>;
+    /// Works analog to `write_blocking_timeout` but eventually returns once the `deadline`
+    /// has passed. Loops running at a fixed period don't accumulate drift this way.
+    ///
+    /// Returns `Ok(None)` if the given slice has zero length.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::TimedOut`
+    /// - `RbError::Disconnected` all consumers were dropped
+    #[cfg(feature = "std")]
+    fn write_blocking_deadline(&self, data: &[T], deadline: Instant) -> Result<Option<usize>>;
 }
 
 /// Defines *read* methods for a consumer view.
+///
+/// Closing the buffer disconnects the consumer just like dropping all producers,
+/// the pending values can still be read.
 pub trait RbConsumer<T> {
     /// Skips all pending values.
     /// Technically it sets the consumer's read pointer to the position
@@ -69,6 +282,7 @@ pub trait RbConsumer<T> {
     /// Possible errors:
     ///
     /// - `RbError::Empty` no pending elements
+    /// - `RbError::Disconnected` no pending elements and all producers were dropped
     fn skip_pending(&self) -> Result<usize>;
     /// Skips `cnt` number of elements.
     ///
@@ -77,6 +291,7 @@ pub trait RbConsumer<T> {
     /// Possible errors:
     ///
     /// - `RbError::Empty` no pending elements
+    /// - `RbError::Disconnected` no pending elements and all producers were dropped
     fn skip(&self, cnt: usize) -> Result<usize>;
     /// Fills the given slice with values or, if the buffer is empty, does not modify it.
     /// This method does not change the state of the buffer, this means that the read pointer
@@ -84,28 +299,66 @@ pub trait RbConsumer<T> {
     /// will fill the given slice with the same data.
     /// Using `get` can be beneficial to `read` when a successive call has failed and you want to
     /// try again with same data. You can use `skip` to move the read pointer i.e. mark the values
-    /// as read after the call succeeded.
+    /// as read after the call succeeded. Use `peek` to start at an offset instead.
     ///
     /// Returns the number of written values or an error.
     ///
     /// Possible errors:
     ///
     /// - RbError::Empty
+    /// - RbError::Disconnected the buffer is empty and all producers were dropped
     fn get(&self, data: &mut [T]) -> Result<usize>;
+    /// Works analog to `get` but starts to copy `offset` values after the read pointer.
+    /// This allows to look ahead into the pending values without consuming them, e.g. for
+    /// sliding-window algorithms that inspect data beyond the head.
+    ///
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(8);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write(&[1, 2, 3, 4, 5, 6]).unwrap();
+    /// let mut window = [0; 3];
+    /// assert_eq!(cons.peek(2, &mut window).unwrap(), 3);
+    /// assert_eq!(window, [3, 4, 5]);
+    /// // moving averages of all complete windows
+    /// let mut averages = Vec::new();
+    /// let mut offset = 0;
+    /// while cons.peek(offset, &mut window).unwrap_or(0) == window.len() {
+    ///     averages.push(window.iter().sum::<i32>() / 3);
+    ///     offset += 1;
+    /// }
+    /// assert_eq!(averages, [2, 3, 4, 5]);
+    /// assert_eq!(cons.count(), 6);
+    /// # }
+    /// ```
+    ///
+    /// Returns the number of written values or an error.
+    ///
+    /// Possible errors:
+    ///
+    /// - RbError::Empty no pending values after `offset`
+    /// - RbError::Disconnected no values after `offset` and all producers were dropped
+    fn peek(&self, offset: usize, data: &mut [T]) -> Result<usize>;
     /// Fills the given slice with values or, if the buffer is empty, does not modify it.
     /// Returns the number of written values or an error.
     ///
     /// Possible errors:
     ///
     /// - RbError::Empty
+    /// - RbError::Disconnected the buffer is empty and all producers were dropped
     fn read(&self, data: &mut [T]) -> Result<usize>;
+    #[cfg(feature = "std")]
     /// Works analog to `read` but blocks until it can read elements to fill
     /// the given buffer slice.
     /// The number of blocks read is not necessarily equal to the length of the given buffer slice,
     /// the exact number is returned in the `Option` value.
     ///
-    /// Returns `None` if the given slice has zero length.
+    /// Returns `None` if the given slice has zero length or the buffer is empty
+    /// and all producers were dropped.
     fn read_blocking(&self, data: &mut [T]) -> Option<usize>;
+    #[cfg(feature = "std")]
     /// Works analog to `read_blocking` but eventually returns if the specified timeout is reached.
     /// The exact number is returned in the `Ok(Option)` value.
     ///
@@ -114,7 +367,29 @@ pub trait RbConsumer<T> {
     /// Possible errors:
     ///
     /// - RbError::TimedOut
+    /// - RbError::Disconnected the buffer is empty and all producers were dropped
     fn read_blocking_timeout(&self, data: &mut [T], timeout: Duration) -> Result<Option<usize>>;
+    /// Works analog to `read_blocking_timeout` but eventually returns once the `deadline`
+    /// has passed. Loops running at a fixed period don't accumulate drift this way.
+    ///
+    /// Returns `Ok(None)` if the given slice has zero length.
+    ///
+    /// Possible errors:
+    ///
+    /// - RbError::TimedOut
+    /// - RbError::Disconnected the buffer is empty and all producers were dropped
+    #[cfg(feature = "std")]
+    fn read_blocking_deadline(&self, data: &mut [T], deadline: Instant) -> Result<Option<usize>>;
+    /// Works analog to `read_blocking` but blocks until at least `min` values are pending,
+    /// e.g. to wait for a full analysis window but take everything that is there.
+    /// `min` is limited to the length of the given slice and the capacity of the buffer.
+    ///
+    /// Fewer values are returned if all producers were dropped in the meantime.
+    ///
+    /// Returns `None` if the given slice has zero length or the buffer is empty
+    /// and all producers were dropped.
+    #[cfg(feature = "std")]
+    fn read_at_least(&self, data: &mut [T], min: usize) -> Option<usize>;
 }
 
 /// Ring buffer errors.
@@ -123,6 +398,9 @@ pub enum RbError {
     Full,
     Empty,
     TimedOut,
+    Disconnected,
+    /// The slice is too short for the next message, see `MessageConsumer::read`.
+    TooShort,
 }
 impl fmt::Display for RbError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -130,359 +408,28 @@ impl fmt::Display for RbError {
             RbError::Full => write!(f, "No free slots in the buffer"),
             RbError::Empty => write!(f, "Buffer is empty"),
             RbError::TimedOut => write!(f, "Timed out waiting for available slots"),
+            RbError::Disconnected => write!(f, "The other side of the buffer was dropped"),
+            RbError::TooShort => write!(f, "The slice is too short for the message"),
         }
     }
 }
 
-/// Result type used inside the module.
-pub type Result<T> = ::std::result::Result<T, RbError>;
-
-struct Inspector {
-    read_pos: Arc<AtomicUsize>,
-    write_pos: Arc<AtomicUsize>,
-    size: usize,
-}
-
-/// A *thread-safe* Single-Producer-Single-Consumer RingBuffer
-///
-/// - blocking and non-blocking IO
-/// - mutually exclusive access for producer and consumer
-/// - no use of `unsafe`
-/// - never under- or overflows
-///
-/// ```
-/// use std::thread;
-/// use rb::*;
-///
-/// let rb = SpscRb::new(1024);
-/// let (prod, cons) = (rb.producer(), rb.consumer());
-/// thread::spawn(move || {
-///     let gen = || {(-16..16+1).cycle().map(|x| x as f32/16.0)};
-///     loop {
-///         let data = gen().take(32).collect::<Vec<f32>>();
-///         prod.write(&data).unwrap();
-///     }
-/// });
-/// let mut data = Vec::with_capacity(1024);
-/// let mut buf = [0.0f32; 256];
-/// while data.len() < 1024 {
-///     let cnt = cons.read_blocking(&mut buf).unwrap();
-///     data.extend_from_slice(&buf[..cnt]);
-/// }
-/// ```
-pub struct SpscRb<T> {
-    buf: Arc<Mutex<Vec<T>>>,
-    inspector: Arc<Inspector>,
-    slots_free: Arc<Condvar>,
-    data_available: Arc<Condvar>,
-}
-
-impl<T: Clone + Copy + Default> SpscRb<T> {
-    pub fn new(size: usize) -> Self {
-        let (read_pos, write_pos) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
-        SpscRb {
-            buf: Arc::new(Mutex::new(vec![T::default(); size + 1])),
-            slots_free: Arc::new(Condvar::new()),
-            data_available: Arc::new(Condvar::new()),
-            // the additional element is used to distinct between empty and full state
-            inspector: Arc::new(Inspector {
-                read_pos,
-                write_pos,
-                size: size + 1,
-            }),
-        }
-    }
-}
-
-impl<T: Clone + Copy + Default> RB<T> for SpscRb<T> {
-    fn clear(&self) {
-        let mut buf = self.buf.lock().unwrap();
-        buf.iter_mut().map(|_| T::default()).count();
-        self.inspector.read_pos.store(0, Ordering::Relaxed);
-        self.inspector.write_pos.store(0, Ordering::Relaxed);
-    }
-
-    fn producer(&self) -> Producer<T> {
-        Producer {
-            buf: self.buf.clone(),
-            inspector: self.inspector.clone(),
-            slots_free: self.slots_free.clone(),
-            data_available: self.data_available.clone(),
-        }
-    }
-
-    fn consumer(&self) -> Consumer<T> {
-        Consumer {
-            buf: self.buf.clone(),
-            inspector: self.inspector.clone(),
-            slots_free: self.slots_free.clone(),
-            data_available: self.data_available.clone(),
-        }
-    }
+/// Error of `SpscRb::try_new` for a size that the buffer can't be created with.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CapacityError {
+    /// The buffer could never hold a value.
+    Zero,
+    /// The slots would take more than `isize::MAX` bytes or can't be allocated.
+    TooLarge,
 }
-
-impl<T: Clone + Copy + Default> RbInspector for SpscRb<T> {
-    fn is_empty(&self) -> bool {
-        self.inspector.is_empty()
-    }
-    fn is_full(&self) -> bool {
-        self.inspector.is_full()
-    }
-    fn capacity(&self) -> usize {
-        self.inspector.capacity()
-    }
-    fn slots_free(&self) -> usize {
-        self.inspector.slots_free()
-    }
-    fn count(&self) -> usize {
-        self.inspector.count()
-    }
-}
-
-impl RbInspector for Inspector {
-    #[inline(always)]
-    fn is_empty(&self) -> bool {
-        self.slots_free() == self.capacity()
-    }
-
-    #[inline(always)]
-    fn is_full(&self) -> bool {
-        self.slots_free() == 0
-    }
-
-    #[inline(always)]
-    fn capacity(&self) -> usize {
-        self.size - 1
-    }
-
-    #[inline(always)]
-    fn slots_free(&self) -> usize {
-        let wr_pos = self.write_pos.load(Ordering::Relaxed);
-        let re_pos = self.read_pos.load(Ordering::Relaxed);
-        if wr_pos < re_pos {
-            re_pos - wr_pos - 1
-        } else {
-            self.capacity() - wr_pos + re_pos
-        }
-    }
-
-    #[inline(always)]
-    fn count(&self) -> usize {
-        self.capacity() - self.slots_free()
-    }
-}
-
-/// Producer view into the ring buffer.
-pub struct Producer<T> {
-    buf: Arc<Mutex<Vec<T>>>,
-    inspector: Arc<Inspector>,
-    slots_free: Arc<Condvar>,
-    data_available: Arc<Condvar>,
-}
-
-/// Consumer view into the ring buffer.
-pub struct Consumer<T> {
-    buf: Arc<Mutex<Vec<T>>>,
-    inspector: Arc<Inspector>,
-    slots_free: Arc<Condvar>,
-    data_available: Arc<Condvar>,
-}
-
-impl<T: Clone + Copy> RbProducer<T> for Producer<T> {
-    fn write(&self, data: &[T]) -> Result<usize> {
-        if data.is_empty() {
-            return Ok(0);
-        }
-        if self.inspector.is_full() {
-            return Err(RbError::Full);
-        }
-        let cnt = cmp::min(data.len(), self.inspector.slots_free());
-        let mut buf = self.buf.lock().unwrap();
-        let buf_len = buf.len();
-        let wr_pos = self.inspector.write_pos.load(Ordering::Relaxed);
-
-        if (wr_pos + cnt) < buf_len {
-            buf[wr_pos..wr_pos + cnt].copy_from_slice(&data[..cnt]);
-        } else {
-            let d = buf_len - wr_pos;
-            buf[wr_pos..].copy_from_slice(&data[..d]);
-            buf[..(cnt - d)].copy_from_slice(&data[d..cnt]);
-        }
-        self.inspector
-            .write_pos
-            .store((wr_pos + cnt) % buf_len, Ordering::Relaxed);
-
-        self.data_available.notify_one();
-        Ok(cnt)
-    }
-
-    fn write_blocking(&self, data: &[T]) -> Option<usize> {
-        self.write_blocking_timeout(data, Duration::MAX)
-            .expect("Max duration should not time out")
-    }
-
-    fn write_blocking_timeout(&self, data: &[T], timeout: Duration) -> Result<Option<usize>> {
-        if data.is_empty() {
-            return Ok(None);
-        }
-
-        let guard = self.buf.lock().unwrap();
-        let mut buf = if self.inspector.is_full() {
-            if timeout == Duration::MAX {
-                // No need to call wait_timeout if the duration is max
-                self.slots_free.wait(guard).unwrap()
-            } else {
-                let (guard, result) = self.slots_free.wait_timeout(guard, timeout).unwrap();
-                if result.timed_out() {
-                    return Err(RbError::TimedOut);
-                }
-                guard
-            }
-        } else {
-            guard
-        };
-
-        let buf_len = buf.len();
-        let data_len = data.len();
-        let wr_pos = self.inspector.write_pos.load(Ordering::Relaxed);
-        let cnt = cmp::min(data_len, self.inspector.slots_free());
-
-        if (wr_pos + cnt) < buf_len {
-            buf[wr_pos..wr_pos + cnt].copy_from_slice(&data[..cnt]);
-        } else {
-            let d = buf_len - wr_pos;
-            buf[wr_pos..].copy_from_slice(&data[..d]);
-            buf[..(cnt - d)].copy_from_slice(&data[d..cnt]);
+impl fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CapacityError::Zero => write!(f, "The capacity of the buffer is zero"),
+            CapacityError::TooLarge => write!(f, "The capacity of the buffer is too large"),
         }
-        self.inspector
-            .write_pos
-            .store((wr_pos + cnt) % buf_len, Ordering::Relaxed);
-
-        self.data_available.notify_one();
-        Ok(Some(cnt))
     }
 }
 
-impl<T: Clone + Copy> RbConsumer<T> for Consumer<T> {
-    fn skip_pending(&self) -> Result<usize> {
-        if self.inspector.is_empty() {
-            Err(RbError::Empty)
-        } else {
-            // TODO check Order value
-            let write_pos = self.inspector.write_pos.load(Ordering::Relaxed);
-            let count = self.inspector.count();
-            self.inspector.read_pos.store(write_pos, Ordering::Relaxed);
-            Ok(count)
-        }
-    }
-
-    fn skip(&self, cnt: usize) -> Result<usize> {
-        if self.inspector.is_empty() {
-            Err(RbError::Empty)
-        } else {
-            let count = cmp::min(cnt, self.inspector.count());
-            let prev_read_pos = self.inspector.read_pos.load(Ordering::Relaxed);
-            self.inspector.read_pos.store(
-                (prev_read_pos + count) % self.inspector.size,
-                Ordering::Relaxed,
-            );
-            Ok(count)
-        }
-    }
-
-    fn get(&self, data: &mut [T]) -> Result<usize> {
-        if data.is_empty() {
-            return Ok(0);
-        }
-        if self.inspector.is_empty() {
-            return Err(RbError::Empty);
-        }
-        let cnt = cmp::min(data.len(), self.inspector.count());
-        let buf = self.buf.lock().unwrap();
-        let buf_len = buf.len();
-        let re_pos = self.inspector.read_pos.load(Ordering::Relaxed);
-
-        if (re_pos + cnt) < buf_len {
-            data[..cnt].copy_from_slice(&buf[re_pos..re_pos + cnt]);
-        } else {
-            let d = buf_len - re_pos;
-            data[..d].copy_from_slice(&buf[re_pos..]);
-            data[d..cnt].copy_from_slice(&buf[..(cnt - d)]);
-        }
-
-        Ok(cnt)
-    }
-
-    fn read(&self, data: &mut [T]) -> Result<usize> {
-        if data.is_empty() {
-            return Ok(0);
-        }
-        if self.inspector.is_empty() {
-            return Err(RbError::Empty);
-        }
-        let cnt = cmp::min(data.len(), self.inspector.count());
-        let buf = self.buf.lock().unwrap();
-        let buf_len = buf.len();
-        let re_pos = self.inspector.read_pos.load(Ordering::Relaxed);
-
-        if (re_pos + cnt) < buf_len {
-            data[..cnt].copy_from_slice(&buf[re_pos..re_pos + cnt]);
-        } else {
-            let d = buf_len - re_pos;
-            data[..d].copy_from_slice(&buf[re_pos..]);
-            data[d..cnt].copy_from_slice(&buf[..(cnt - d)]);
-        }
-
-        // TODO: Notify all? empty->slots_free
-        self.inspector
-            .read_pos
-            .store((re_pos + cnt) % buf_len, Ordering::Relaxed);
-        self.slots_free.notify_one();
-        Ok(cnt)
-    }
-
-    fn read_blocking(&self, data: &mut [T]) -> Option<usize> {
-        self.read_blocking_timeout(data, Duration::MAX)
-            .expect("Max duration shouldn't time out")
-    }
-
-    fn read_blocking_timeout(&self, data: &mut [T], timeout: Duration) -> Result<Option<usize>> {
-        if data.is_empty() {
-            return Ok(None);
-        }
-
-        let guard = self.buf.lock().unwrap();
-        let buf = if self.inspector.is_empty() {
-            if timeout == Duration::MAX {
-                // No need to call wait_timeout if the duration is max
-                self.data_available.wait(guard).unwrap()
-            } else {
-                let (guard, result) = self.data_available.wait_timeout(guard, timeout).unwrap();
-                if result.timed_out() {
-                    return Err(RbError::TimedOut);
-                }
-                guard
-            }
-        } else {
-            guard
-        };
-
-        let buf_len = buf.len();
-        let cnt = cmp::min(data.len(), self.inspector.count());
-        let re_pos = self.inspector.read_pos.load(Ordering::Relaxed);
-
-        if (re_pos + cnt) < buf_len {
-            data[..cnt].copy_from_slice(&buf[re_pos..re_pos + cnt]);
-        } else {
-            let d = buf_len - re_pos;
-            data[..d].copy_from_slice(&buf[re_pos..]);
-            data[d..cnt].copy_from_slice(&buf[..(cnt - d)]);
-        }
-
-        self.inspector
-            .read_pos
-            .store((re_pos + cnt) % buf_len, Ordering::Relaxed);
-        self.slots_free.notify_one();
-        Ok(Some(cnt))
-    }
-}
+/// Result type used inside the module.
+pub type Result<T> = ::core::result::Result<T, RbError>;
@@ -0,0 +1,62 @@
+use std::io;
+
+use symphonia_core::io::MediaSource;
+
+use {Consumer, RbConsumer};
+
+/// Exposes a `Consumer<u8>` as a symphonia `MediaSource`.
+///
+/// Reads block until the producer has written data, so compressed audio that is streamed
+/// into the ring buffer (e.g. from a network socket) can be decoded directly.
+/// The source is not seekable and its length is unknown.
+///
+/// ```no_run
+/// extern crate rb;
+/// extern crate symphonia_core;
+///
+/// use rb::{ConsumerSource, SpscRb, RB};
+/// use symphonia_core::io::{MediaSourceStream, MediaSourceStreamOptions};
+///
+/// let rb = SpscRb::<u8>::new(64 * 1024);
+/// let source = ConsumerSource::new(rb.consumer());
+/// let mss = MediaSourceStream::new(Box::new(source), MediaSourceStreamOptions::default());
+/// ```
+pub struct ConsumerSource {
+    consumer: Consumer<u8>,
+}
+
+impl ConsumerSource {
+    pub fn new(consumer: Consumer<u8>) -> Self {
+        ConsumerSource { consumer }
+    }
+
+    /// Returns the wrapped consumer.
+    pub fn into_inner(self) -> Consumer<u8> {
+        self.consumer
+    }
+}
+
+impl io::Read for ConsumerSource {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        Ok(self.consumer.read_blocking(buf).unwrap_or(0))
+    }
+}
+
+impl io::Seek for ConsumerSource {
+    fn seek(&mut self, _pos: io::SeekFrom) -> io::Result<u64> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "ring buffer is not seekable",
+        ))
+    }
+}
+
+impl MediaSource for ConsumerSource {
+    fn is_seekable(&self) -> bool {
+        false
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        None
+    }
+}
@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use {Consumer, Producer, RbConsumer, RbError, RbInspector, RbProducer, Result, SpscRb, RB};
+
+/// A ring buffer that preserves the boundaries of the writes, each `write` is one message
+/// and each `read` returns exactly one message, e.g. for variable-size events.
+///
+/// The values of the messages share a ring of `size` slots, their lengths are kept in a second
+/// ring, thus at most `size` messages are pending at once. A message is only visible
+/// to the consumer once all of its values are written.
+///
+/// ```
+/// use rb::*;
+///
+/// let (prod, cons) = MessageRb::new(16).split();
+/// prod.write(&[1, 2, 3]).unwrap();
+/// prod.write(&[4]).unwrap();
+/// let mut buf = [0; 16];
+/// assert_eq!(cons.read(&mut buf).unwrap(), 3);
+/// assert_eq!(buf[..3], [1, 2, 3]);
+/// assert_eq!(cons.read_vec().unwrap(), vec![4]);
+/// ```
+pub struct MessageRb<T> {
+    data: SpscRb<T>,
+    lengths: SpscRb<usize>,
+}
+
+impl<T: Clone> MessageRb<T> {
+    /// Creates a buffer for messages with `size` values in total.
+    pub fn new(size: usize) -> Self {
+        MessageRb {
+            data: SpscRb::new(size),
+            lengths: SpscRb::new(size),
+        }
+    }
+
+    /// Returns the producer and the consumer of the buffer.
+    pub fn split(self) -> (MessageProducer<T>, MessageConsumer<T>) {
+        (
+            MessageProducer {
+                data: self.data.producer(),
+                lengths: self.lengths.producer(),
+            },
+            MessageConsumer {
+                data: self.data.consumer(),
+                lengths: self.lengths.consumer(),
+            },
+        )
+    }
+}
+
+/// Producer of a `MessageRb`.
+pub struct MessageProducer<T> {
+    data: Producer<T>,
+    lengths: Producer<usize>,
+}
+
+impl<T: Clone> MessageProducer<T> {
+    /// Writes `msg` as a single message, either completely or not at all.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Full` not enough free slots for the message, always the case for
+    ///   messages that are longer than the size of the buffer
+    /// - `RbError::Disconnected` the consumer was dropped
+    pub fn write(&self, msg: &[T]) -> Result<()> {
+        self.reserve(msg.len(), Duration::ZERO)?;
+        self.data.write_exact(msg)?;
+        self.lengths.write_exact(&[msg.len()])
+    }
+
+    /// Works analog to `write` but blocks until there are enough free slots for `msg`.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Full` the message is longer than the size of the buffer
+    /// - `RbError::Disconnected` the consumer was dropped
+    pub fn write_blocking(&self, msg: &[T]) -> Result<()> {
+        self.reserve(msg.len(), Duration::MAX)?;
+        self.data.write_exact(msg)?;
+        self.lengths.write_exact(&[msg.len()])
+    }
+
+    /// Waits until there are free slots for a message of `len` values and its length.
+    /// The consumer only frees slots, thus both writes succeed afterwards.
+    fn reserve(&self, len: usize, timeout: Duration) -> Result<()> {
+        if len > self.data.inspector.capacity() {
+            return Err(RbError::Full);
+        }
+        let full = |err| match err {
+            RbError::TimedOut => RbError::Full,
+            err => err,
+        };
+        self.data.wait_for_slots(len, timeout).map_err(full)?;
+        self.lengths.wait_for_slots(1, timeout).map_err(full)?;
+        Ok(())
+    }
+}
+
+/// Consumer of a `MessageRb`.
+pub struct MessageConsumer<T> {
+    data: Consumer<T>,
+    lengths: Consumer<usize>,
+}
+
+impl<T: Clone> MessageConsumer<T> {
+    /// Returns the length of the next message or `None` if no message is pending.
+    pub fn next_len(&self) -> Option<usize> {
+        let mut len = [0];
+        self.lengths.get(&mut len).ok().map(|_| len[0])
+    }
+
+    /// Reads the next message into the beginning of `buf`.
+    ///
+    /// Returns the length of the message.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Empty` no pending message
+    /// - `RbError::Disconnected` no pending message and the producer was dropped
+    /// - `RbError::TooShort` `buf` is shorter than the message, which stays pending, see
+    ///   `next_len`
+    pub fn read(&self, buf: &mut [T]) -> Result<usize> {
+        let mut len = [0];
+        self.lengths.get(&mut len)?;
+        let len = len[0];
+        if buf.len() < len {
+            return Err(RbError::TooShort);
+        }
+        if len > 0 {
+            self.data.read(&mut buf[..len])?;
+        }
+        self.lengths.skip(1)?;
+        Ok(len)
+    }
+
+    /// Works analog to `read` but blocks until a message is pending.
+    ///
+    /// Returns `None` if the producer was dropped and no message is left to read, or if
+    /// `buf` is shorter than the next message.
+    pub fn read_blocking(&self, buf: &mut [T]) -> Option<usize> {
+        self.lengths.wait_for_count(1, Duration::MAX).ok()?;
+        self.read(buf).ok()
+    }
+
+    /// Reads the next message into a new `Vec`.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Empty` no pending message
+    /// - `RbError::Disconnected` no pending message and the producer was dropped
+    pub fn read_vec(&self) -> Result<Vec<T>> {
+        let mut len = [0];
+        self.lengths.get(&mut len)?;
+        let msg = self.data.read_vec(len[0]);
+        self.lengths.skip(1)?;
+        Ok(msg)
+    }
+}
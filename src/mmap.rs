@@ -0,0 +1,346 @@
+use std::cmp;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::mem;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::ptr::{self, NonNull};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use {RbError, RbInspector, Result};
+
+/// Identifies the files of a `MmapRb`.
+const MAGIC: [u8; 8] = *b"rb-mmap\0";
+
+/// Beginning of the file, followed by the `size` slots.
+#[repr(C)]
+struct Header {
+    magic: [u8; 8],
+    size: u64,
+    read_pos: AtomicU64,
+    write_pos: AtomicU64,
+}
+
+const HEADER_LEN: usize = mem::size_of::<Header>();
+
+/// A byte ring buffer whose slots and positions live in a memory-mapped file, e.g. for
+/// a log whose most recent entries survive a crash of the process. The data is in the
+/// page cache as soon as a call returns, `flush` writes it to the disk.
+///
+/// Reopening the file with `open` continues where the previous owner stopped, e.g. to
+/// replay the pending bytes in a separate tool. The buffer holds an exclusive `flock` of
+/// the file, thus only one process at a time can open it. Unix only.
+///
+/// ```
+/// use rb::*;
+///
+/// let path = std::env::temp_dir().join("rb-doc-mmap");
+/// let mut rb = MmapRb::create(&path, 1024).unwrap();
+/// rb.write(b"started\n").unwrap();
+/// rb.write_overwriting(b"crashed\n");
+/// drop(rb);
+///
+/// let mut rb = MmapRb::open(&path).unwrap();
+/// assert_eq!(rb.tail(8), b"crashed\n");
+/// let mut data = [0; 16];
+/// assert_eq!(rb.read(&mut data).unwrap(), 16);
+/// assert_eq!(&data, b"started\ncrashed\n");
+/// # std::fs::remove_file(path).unwrap();
+/// ```
+pub struct MmapRb {
+    map: Mapping,
+    size: usize,
+    // Holds the lock of the file as long as the buffer is alive.
+    _file: File,
+}
+
+impl MmapRb {
+    /// Creates the file at `path` for `capacity` bytes, an existing file is overwritten.
+    ///
+    /// Fails with `io::ErrorKind::WouldBlock` if another buffer has the file open.
+    pub fn create<P: AsRef<Path>>(path: P, capacity: usize) -> io::Result<Self> {
+        if capacity == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The capacity of the buffer is zero",
+            ));
+        }
+        let size = capacity + 1;
+        // Truncated only once it's locked, the file might still be in use.
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        lock(&file)?;
+        file.set_len(0)?;
+        file.set_len((HEADER_LEN + size) as u64)?;
+        let map = Mapping::new(&file, HEADER_LEN + size)?;
+        let header = map.as_ptr() as *mut Header;
+        // Safety: the mapping starts with the header, is aligned to a page and the file is
+        // locked. The magic comes last, a file whose creation was interrupted is never opened.
+        unsafe {
+            (*header).size = size as u64;
+            (*header).magic = MAGIC;
+        }
+        Ok(MmapRb {
+            map,
+            size,
+            _file: file,
+        })
+    }
+
+    /// Opens a file that was created by `create`, the pending bytes are kept.
+    ///
+    /// Fails with `io::ErrorKind::InvalidData` if the file is not a valid buffer and with
+    /// `io::ErrorKind::WouldBlock` if another buffer has the file open.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        lock(&file)?;
+        let len = file.metadata()?.len() as usize;
+        if len <= HEADER_LEN {
+            return Err(invalid_file());
+        }
+        let size = len - HEADER_LEN;
+        let rb = MmapRb {
+            map: Mapping::new(&file, len)?,
+            size,
+            _file: file,
+        };
+        let header = rb.header();
+        if header.magic != MAGIC
+            || header.size != size as u64
+            || header.read_pos.load(Ordering::Acquire) >= size as u64
+            || header.write_pos.load(Ordering::Acquire) >= size as u64
+        {
+            return Err(invalid_file());
+        }
+        Ok(rb)
+    }
+
+    /// Writes as many values of `data` as there are free slots.
+    ///
+    /// Returns the number of written values.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Full` no free slots
+    pub fn write(&mut self, data: &[u8]) -> Result<usize> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        let cnt = cmp::min(data.len(), self.slots_free());
+        if cnt == 0 {
+            return Err(RbError::Full);
+        }
+        self.store(&data[..cnt]);
+        Ok(cnt)
+    }
+
+    /// Writes all of `data` and overwrites the oldest pending values if necessary, like
+    /// `Producer::write_overwriting`.
+    ///
+    /// Returns the number of values that were lost.
+    pub fn write_overwriting(&mut self, data: &[u8]) -> usize {
+        let capacity = self.capacity();
+        let mut lost = data.len().saturating_sub(capacity);
+        let data = &data[lost..];
+        let missing = data.len().saturating_sub(self.slots_free());
+        if missing > 0 {
+            // Advanced first, a crash in between never exposes partially written slots.
+            let re_pos = (self.read_pos() + missing) % self.size;
+            self.header()
+                .read_pos
+                .store(re_pos as u64, Ordering::Release);
+            lost += missing;
+        }
+        self.store(data);
+        lost
+    }
+
+    /// Reads up to `data.len()` pending values.
+    ///
+    /// Returns the number of read values.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Empty` no pending values
+    pub fn read(&mut self, data: &mut [u8]) -> Result<usize> {
+        let cnt = cmp::min(data.len(), self.count());
+        if cnt == 0 {
+            return Err(RbError::Empty);
+        }
+        let re_pos = self.read_pos();
+        self.copy_from_ring(re_pos, &mut data[..cnt]);
+        let re_pos = (re_pos + cnt) % self.size;
+        self.header()
+            .read_pos
+            .store(re_pos as u64, Ordering::Release);
+        Ok(cnt)
+    }
+
+    /// Returns the up to `n` most recently written pending values without consuming them.
+    pub fn tail(&self, n: usize) -> Vec<u8> {
+        let cnt = cmp::min(n, self.count());
+        let re_pos = (self.write_pos() + self.size - cnt) % self.size;
+        let mut data = vec![0; cnt];
+        self.copy_from_ring(re_pos, &mut data);
+        data
+    }
+
+    /// Writes the slots and positions to the disk, blocks until it's done.
+    pub fn flush(&self) -> io::Result<()> {
+        self.map.flush()
+    }
+
+    fn store(&mut self, data: &[u8]) {
+        let wr_pos = self.write_pos();
+        self.copy_to_ring(wr_pos, data);
+        let wr_pos = (wr_pos + data.len()) % self.size;
+        self.header()
+            .write_pos
+            .store(wr_pos as u64, Ordering::Release);
+    }
+
+    fn header(&self) -> &Header {
+        // Safety: the mapping starts with the header and is aligned to a page.
+        unsafe { &*(self.map.as_ptr() as *const Header) }
+    }
+
+    fn slots(&self) -> *mut u8 {
+        // Safety: the `size` slots follow the header.
+        unsafe { self.map.as_ptr().add(HEADER_LEN) }
+    }
+
+    /// Copies `data` into the slots starting at `wr_pos`, it must fit into the slots.
+    ///
+    /// The slots are only accessed through raw pointers, since a process that ignores the
+    /// lock might modify the file concurrently.
+    fn copy_to_ring(&mut self, wr_pos: usize, data: &[u8]) {
+        let first = cmp::min(data.len(), self.size - wr_pos);
+        // Safety: both ranges are in bounds and borrowed exclusively through `self`.
+        unsafe {
+            ptr::copy_nonoverlapping(data.as_ptr(), self.slots().add(wr_pos), first);
+            ptr::copy_nonoverlapping(data.as_ptr().add(first), self.slots(), data.len() - first);
+        }
+    }
+
+    /// Copies the slots starting at `re_pos` into `data`, which must not be longer than
+    /// the slots, see `copy_to_ring`.
+    fn copy_from_ring(&self, re_pos: usize, data: &mut [u8]) {
+        let first = cmp::min(data.len(), self.size - re_pos);
+        // Safety: both ranges are in bounds.
+        unsafe {
+            ptr::copy_nonoverlapping(self.slots().add(re_pos), data.as_mut_ptr(), first);
+            ptr::copy_nonoverlapping(
+                self.slots(),
+                data.as_mut_ptr().add(first),
+                data.len() - first,
+            );
+        }
+    }
+
+    // The positions are validated by `open`, a file that was modified by a process that
+    // ignores the lock still never leads to accesses out of bounds.
+    fn read_pos(&self) -> usize {
+        (self.header().read_pos.load(Ordering::Acquire) % self.size as u64) as usize
+    }
+
+    fn write_pos(&self) -> usize {
+        (self.header().write_pos.load(Ordering::Acquire) % self.size as u64) as usize
+    }
+}
+
+impl RbInspector for MmapRb {
+    fn is_empty(&self) -> bool {
+        self.count() == 0
+    }
+    fn is_full(&self) -> bool {
+        self.slots_free() == 0
+    }
+    fn capacity(&self) -> usize {
+        self.size - 1
+    }
+    fn slots_free(&self) -> usize {
+        self.capacity() - self.count()
+    }
+    fn count(&self) -> usize {
+        (self.write_pos() + self.size - self.read_pos()) % self.size
+    }
+}
+
+/// Takes an exclusive lock of `file`, which fails right away if it's taken.
+fn lock(file: &File) -> io::Result<()> {
+    // Safety: the file descriptor is valid.
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+fn invalid_file() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "not a ring buffer file")
+}
+
+/// Shared read-write mapping of the first `len` bytes of a file.
+pub(crate) struct Mapping {
+    ptr: NonNull<u8>,
+    len: usize,
+}
+
+// The mapping is plain memory, synchronizing its accesses is up to the owner.
+unsafe impl Send for Mapping {}
+unsafe impl Sync for Mapping {}
+
+impl Mapping {
+    pub(crate) fn new<F: AsRawFd>(file: &F, len: usize) -> io::Result<Self> {
+        // Safety: a new mapping doesn't alias any memory of the process.
+        let ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Mapping {
+            ptr: NonNull::new(ptr as *mut u8).expect("mmap never maps the null page"),
+            len,
+        })
+    }
+
+    pub(crate) fn as_ptr(&self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+
+    /// Writes the mapped memory to the file.
+    pub(crate) fn flush(&self) -> io::Result<()> {
+        // Safety: the range is mapped.
+        if unsafe {
+            libc::msync(
+                self.ptr.as_ptr() as *mut libc::c_void,
+                self.len,
+                libc::MS_SYNC,
+            )
+        } == -1
+        {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        // Safety: the range was mapped by `new` and is not accessed afterwards.
+        unsafe {
+            libc::munmap(self.ptr.as_ptr() as *mut libc::c_void, self.len);
+        }
+    }
+}
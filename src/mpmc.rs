@@ -0,0 +1,296 @@
+use std::time::{Duration, Instant};
+
+use {
+    CapacityError, Consumer, Latency, MpscProducer, OverflowPolicy, RbConsumer, RbHooks,
+    RbInspector, Result, SpscRb, Stats, WaitStrategy, Watermark, XrunWatchdog, RB,
+};
+
+/// Producer view into a `MpmcRb`, which can be cloned and shared between threads.
+pub type MpmcProducer<T> = MpscProducer<T>;
+
+/// A *thread-safe* Multi-Producer-Multi-Consumer RingBuffer
+///
+/// Producers and consumers can be cloned and used from several threads at once.
+/// Every element is delivered to exactly one consumer, which makes the buffer a bounded,
+/// preallocated work queue with a slice oriented API.
+///
+/// ```
+/// use std::thread;
+/// use rb::*;
+///
+/// let rb = MpmcRb::new(64);
+/// let prod = rb.producer();
+/// let workers = (0..2)
+///     .map(|_| {
+///         let cons = rb.consumer();
+///         thread::spawn(move || {
+///             let mut buf = [0u32; 8];
+///             let mut sum = 0;
+///             while let Ok(Some(cnt)) =
+///                 cons.read_blocking_timeout(&mut buf, std::time::Duration::from_millis(50))
+///             {
+///                 sum += buf[..cnt].iter().sum::<u32>();
+///             }
+///             sum
+///         })
+///     })
+///     .collect::<Vec<_>>();
+/// for i in 1..=10 {
+///     prod.write_blocking(&[i]).unwrap();
+/// }
+/// let total: u32 = workers.into_iter().map(|w| w.join().unwrap()).sum();
+/// assert_eq!(total, 55);
+/// ```
+pub struct MpmcRb<T> {
+    rb: SpscRb<T>,
+}
+
+impl<T: Clone> MpmcRb<T> {
+    pub fn new(size: usize) -> Self {
+        MpmcRb {
+            rb: SpscRb::new(size),
+        }
+    }
+
+    /// Works analog to `new` but fails if the buffer can't be created with `size`,
+    /// see `SpscRb::try_new`.
+    pub fn try_new(size: usize) -> ::std::result::Result<Self, CapacityError> {
+        Ok(MpmcRb {
+            rb: SpscRb::try_new(size)?,
+        })
+    }
+
+    /// Changes the capacity while keeping the pending values, see `SpscRb::resize`.
+    pub fn resize(&self, capacity: usize) -> Result<()> {
+        self.rb.resize(capacity)
+    }
+
+    /// Drops all elements of the buffer, see `RB::clear`.
+    /// The buffer is empty after this call.
+    pub fn clear(&self) {
+        self.rb.clear()
+    }
+
+    /// Discards the pending values in constant time, see `RB::reset`.
+    /// The buffer is empty after this call.
+    pub fn reset(&self) {
+        self.rb.reset()
+    }
+
+    /// Sets how the blocking calls of the handles wait, see `SpscRb::with_wait_strategy`.
+    pub fn with_wait_strategy(self, wait_strategy: WaitStrategy) -> Self {
+        MpmcRb {
+            rb: self.rb.with_wait_strategy(wait_strategy),
+        }
+    }
+
+    /// Sets how `write` handles a full buffer, see `SpscRb::with_overflow_policy`.
+    pub fn with_overflow_policy(self, overflow_policy: OverflowPolicy) -> Self {
+        MpmcRb {
+            rb: self.rb.with_overflow_policy(overflow_policy),
+        }
+    }
+
+    /// Closes the buffer, see `SpscRb::close`.
+    pub fn close(&self) {
+        self.rb.close()
+    }
+
+    /// Returns the number of values that writes could not store, see `SpscRb::overruns`.
+    pub fn overruns(&self) -> usize {
+        self.rb.overruns()
+    }
+
+    /// Returns the number of reads that found the buffer empty, see `SpscRb::underruns`.
+    pub fn underruns(&self) -> usize {
+        self.rb.underruns()
+    }
+
+    /// Resets the overrun and underrun counters to zero.
+    pub fn reset_xruns(&self) {
+        self.rb.reset_xruns()
+    }
+
+    /// Returns a watchdog for the xruns of the buffer, see `SpscRb::watchdog`.
+    pub fn watchdog(&self) -> XrunWatchdog {
+        self.rb.watchdog()
+    }
+
+    /// Enables the statistics of the buffer, see `SpscRb::with_stats`.
+    pub fn with_stats(self) -> Self {
+        MpmcRb {
+            rb: self.rb.with_stats(),
+        }
+    }
+
+    /// Installs hooks for the events of the buffer, see `SpscRb::with_hooks`.
+    pub fn with_hooks<H: RbHooks + 'static>(self, hooks: H) -> Self {
+        MpmcRb {
+            rb: self.rb.with_hooks(hooks),
+        }
+    }
+
+    /// Returns a snapshot of the statistics, see `SpscRb::stats`.
+    pub fn stats(&self) -> Stats {
+        self.rb.stats()
+    }
+
+    /// Samples the number of pending values into a histogram, see `SpscRb::with_histogram`.
+    pub fn with_histogram(self, bins: usize) -> Self {
+        MpmcRb {
+            rb: self.rb.with_histogram(bins),
+        }
+    }
+
+    /// Returns the histogram of the number of pending values, see `SpscRb::histogram`.
+    pub fn histogram(&self) -> Vec<usize> {
+        self.rb.histogram()
+    }
+
+    /// Resets the statistics, see `SpscRb::reset_stats`.
+    pub fn reset_stats(&self) {
+        self.rb.reset_stats()
+    }
+
+    /// Measures how long values spend in the buffer, see `SpscRb::with_latency`.
+    pub fn with_latency(self) -> Self {
+        MpmcRb {
+            rb: self.rb.with_latency(),
+        }
+    }
+
+    /// Returns the latency of the buffer, see `SpscRb::latency`.
+    pub fn latency(&self) -> Latency {
+        self.rb.latency()
+    }
+
+    /// Resets the latency measurement, see `SpscRb::reset_latency`.
+    pub fn reset_latency(&self) {
+        self.rb.reset_latency()
+    }
+
+    /// Calls `callback` whenever the number of pending values crosses one of the watermarks,
+    /// see `SpscRb::on_watermark`.
+    pub fn on_watermark<F>(&self, low: usize, high: usize, callback: F)
+    where
+        F: FnMut(Watermark) + Send + 'static,
+    {
+        self.rb.on_watermark(low, high, callback)
+    }
+
+    /// Creates a cloneable *producer* view inside the buffer.
+    pub fn producer(&self) -> MpmcProducer<T> {
+        MpscProducer {
+            producer: self.rb.add_producer(),
+        }
+    }
+
+    /// Creates a cloneable *consumer* view inside the buffer.
+    pub fn consumer(&self) -> MpmcConsumer<T> {
+        MpmcConsumer {
+            consumer: self.rb.add_consumer(),
+        }
+    }
+}
+
+impl<T: Clone> RbInspector for MpmcRb<T> {
+    fn is_empty(&self) -> bool {
+        self.rb.is_empty()
+    }
+    fn is_full(&self) -> bool {
+        self.rb.is_full()
+    }
+    fn capacity(&self) -> usize {
+        self.rb.capacity()
+    }
+    fn slots_free(&self) -> usize {
+        self.rb.slots_free()
+    }
+    fn count(&self) -> usize {
+        self.rb.count()
+    }
+}
+
+/// Consumer view into a `MpmcRb`, which can be cloned and shared between threads.
+///
+/// Note that `get` followed by `skip` is not atomic if several consumers read concurrently,
+/// use `read` to take elements out of the buffer.
+pub struct MpmcConsumer<T> {
+    pub(crate) consumer: Consumer<T>,
+}
+
+impl<T> Clone for MpmcConsumer<T> {
+    fn clone(&self) -> Self {
+        MpmcConsumer {
+            consumer: self.consumer.share(),
+        }
+    }
+}
+
+impl<T> MpmcConsumer<T> {
+    /// Closes the buffer, see `SpscRb::close`.
+    pub fn close(&self) {
+        self.consumer.close()
+    }
+
+    /// Blocks until at least `n` values are pending, see `Consumer::wait_for_count`.
+    pub fn wait_for_count(&self, n: usize, timeout: Duration) -> Result<usize> {
+        self.consumer.wait_for_count(n, timeout)
+    }
+}
+
+impl<T: Clone> RbConsumer<T> for MpmcConsumer<T> {
+    fn skip_pending(&self) -> Result<usize> {
+        self.consumer.skip_pending()
+    }
+
+    fn skip(&self, cnt: usize) -> Result<usize> {
+        self.consumer.skip(cnt)
+    }
+
+    fn get(&self, data: &mut [T]) -> Result<usize> {
+        self.consumer.get(data)
+    }
+
+    fn peek(&self, offset: usize, data: &mut [T]) -> Result<usize> {
+        self.consumer.peek(offset, data)
+    }
+
+    fn read(&self, data: &mut [T]) -> Result<usize> {
+        self.consumer.read(data)
+    }
+
+    fn read_blocking(&self, data: &mut [T]) -> Option<usize> {
+        self.consumer.read_blocking(data)
+    }
+
+    fn read_blocking_timeout(&self, data: &mut [T], timeout: Duration) -> Result<Option<usize>> {
+        self.consumer.read_blocking_timeout(data, timeout)
+    }
+
+    fn read_blocking_deadline(&self, data: &mut [T], deadline: Instant) -> Result<Option<usize>> {
+        self.consumer.read_blocking_deadline(data, deadline)
+    }
+
+    fn read_at_least(&self, data: &mut [T], min: usize) -> Option<usize> {
+        self.consumer.read_at_least(data, min)
+    }
+}
+
+impl<T> RbInspector for MpmcConsumer<T> {
+    fn is_empty(&self) -> bool {
+        self.consumer.is_empty()
+    }
+    fn is_full(&self) -> bool {
+        self.consumer.is_full()
+    }
+    fn capacity(&self) -> usize {
+        self.consumer.capacity()
+    }
+    fn slots_free(&self) -> usize {
+        self.consumer.slots_free()
+    }
+    fn count(&self) -> usize {
+        self.consumer.count()
+    }
+}
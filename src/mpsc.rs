@@ -0,0 +1,275 @@
+use std::time::{Duration, Instant};
+
+use {
+    CapacityError, Consumer, Latency, OverflowPolicy, Producer, RbHooks, RbInspector, RbProducer,
+    Result, SpscRb, Stats, WaitStrategy, Watermark, XrunWatchdog, RB,
+};
+
+/// A *thread-safe* Multi-Producer-Single-Consumer RingBuffer
+///
+/// Works like `SpscRb`, but its producers can be cloned and used from several threads
+/// at once. Every `write` call is stored contiguously, i.e. the data of concurrent writes
+/// is never interleaved.
+///
+/// ```
+/// use std::thread;
+/// use rb::*;
+///
+/// let rb = MpscRb::new(1024);
+/// let cons = rb.consumer();
+/// for voice in 0..4 {
+///     let prod = rb.producer();
+///     thread::spawn(move || {
+///         prod.write_blocking(&[voice as f32; 32]).unwrap();
+///     });
+/// }
+/// let mut data = Vec::with_capacity(4 * 32);
+/// let mut buf = [0.0f32; 256];
+/// while data.len() < 4 * 32 {
+///     let cnt = cons.read_blocking(&mut buf).unwrap();
+///     data.extend_from_slice(&buf[..cnt]);
+/// }
+/// ```
+pub struct MpscRb<T> {
+    rb: SpscRb<T>,
+}
+
+impl<T: Clone> MpscRb<T> {
+    pub fn new(size: usize) -> Self {
+        MpscRb {
+            rb: SpscRb::new(size),
+        }
+    }
+
+    /// Works analog to `new` but fails if the buffer can't be created with `size`,
+    /// see `SpscRb::try_new`.
+    pub fn try_new(size: usize) -> ::std::result::Result<Self, CapacityError> {
+        Ok(MpscRb {
+            rb: SpscRb::try_new(size)?,
+        })
+    }
+
+    /// Changes the capacity while keeping the pending values, see `SpscRb::resize`.
+    pub fn resize(&self, capacity: usize) -> Result<()> {
+        self.rb.resize(capacity)
+    }
+
+    /// Drops all elements of the buffer, see `RB::clear`.
+    /// The buffer is empty after this call.
+    pub fn clear(&self) {
+        self.rb.clear()
+    }
+
+    /// Discards the pending values in constant time, see `RB::reset`.
+    /// The buffer is empty after this call.
+    pub fn reset(&self) {
+        self.rb.reset()
+    }
+
+    /// Sets how the blocking calls of the handles wait, see `SpscRb::with_wait_strategy`.
+    pub fn with_wait_strategy(self, wait_strategy: WaitStrategy) -> Self {
+        MpscRb {
+            rb: self.rb.with_wait_strategy(wait_strategy),
+        }
+    }
+
+    /// Sets how `write` handles a full buffer, see `SpscRb::with_overflow_policy`.
+    pub fn with_overflow_policy(self, overflow_policy: OverflowPolicy) -> Self {
+        MpscRb {
+            rb: self.rb.with_overflow_policy(overflow_policy),
+        }
+    }
+
+    /// Closes the buffer, see `SpscRb::close`.
+    pub fn close(&self) {
+        self.rb.close()
+    }
+
+    /// Returns the number of values that writes could not store, see `SpscRb::overruns`.
+    pub fn overruns(&self) -> usize {
+        self.rb.overruns()
+    }
+
+    /// Returns the number of reads that found the buffer empty, see `SpscRb::underruns`.
+    pub fn underruns(&self) -> usize {
+        self.rb.underruns()
+    }
+
+    /// Resets the overrun and underrun counters to zero.
+    pub fn reset_xruns(&self) {
+        self.rb.reset_xruns()
+    }
+
+    /// Returns a watchdog for the xruns of the buffer, see `SpscRb::watchdog`.
+    pub fn watchdog(&self) -> XrunWatchdog {
+        self.rb.watchdog()
+    }
+
+    /// Enables the statistics of the buffer, see `SpscRb::with_stats`.
+    pub fn with_stats(self) -> Self {
+        MpscRb {
+            rb: self.rb.with_stats(),
+        }
+    }
+
+    /// Installs hooks for the events of the buffer, see `SpscRb::with_hooks`.
+    pub fn with_hooks<H: RbHooks + 'static>(self, hooks: H) -> Self {
+        MpscRb {
+            rb: self.rb.with_hooks(hooks),
+        }
+    }
+
+    /// Returns a snapshot of the statistics, see `SpscRb::stats`.
+    pub fn stats(&self) -> Stats {
+        self.rb.stats()
+    }
+
+    /// Samples the number of pending values into a histogram, see `SpscRb::with_histogram`.
+    pub fn with_histogram(self, bins: usize) -> Self {
+        MpscRb {
+            rb: self.rb.with_histogram(bins),
+        }
+    }
+
+    /// Returns the histogram of the number of pending values, see `SpscRb::histogram`.
+    pub fn histogram(&self) -> Vec<usize> {
+        self.rb.histogram()
+    }
+
+    /// Resets the statistics, see `SpscRb::reset_stats`.
+    pub fn reset_stats(&self) {
+        self.rb.reset_stats()
+    }
+
+    /// Measures how long values spend in the buffer, see `SpscRb::with_latency`.
+    pub fn with_latency(self) -> Self {
+        MpscRb {
+            rb: self.rb.with_latency(),
+        }
+    }
+
+    /// Returns the latency of the buffer, see `SpscRb::latency`.
+    pub fn latency(&self) -> Latency {
+        self.rb.latency()
+    }
+
+    /// Resets the latency measurement, see `SpscRb::reset_latency`.
+    pub fn reset_latency(&self) {
+        self.rb.reset_latency()
+    }
+
+    /// Calls `callback` whenever the number of pending values crosses one of the watermarks,
+    /// see `SpscRb::on_watermark`.
+    pub fn on_watermark<F>(&self, low: usize, high: usize, callback: F)
+    where
+        F: FnMut(Watermark) + Send + 'static,
+    {
+        self.rb.on_watermark(low, high, callback)
+    }
+
+    /// Creates a cloneable *producer* view inside the buffer.
+    pub fn producer(&self) -> MpscProducer<T> {
+        MpscProducer {
+            producer: self.rb.add_producer(),
+        }
+    }
+
+    /// Creates a *consumer* view inside the buffer.
+    pub fn consumer(&self) -> Consumer<T> {
+        self.rb.add_consumer()
+    }
+}
+
+impl<T: Clone> RbInspector for MpscRb<T> {
+    fn is_empty(&self) -> bool {
+        self.rb.is_empty()
+    }
+    fn is_full(&self) -> bool {
+        self.rb.is_full()
+    }
+    fn capacity(&self) -> usize {
+        self.rb.capacity()
+    }
+    fn slots_free(&self) -> usize {
+        self.rb.slots_free()
+    }
+    fn count(&self) -> usize {
+        self.rb.count()
+    }
+}
+
+/// Producer view into a `MpscRb`, which can be cloned and shared between threads.
+pub struct MpscProducer<T> {
+    pub(crate) producer: Producer<T>,
+}
+
+impl<T> Clone for MpscProducer<T> {
+    fn clone(&self) -> Self {
+        MpscProducer {
+            producer: self.producer.share(),
+        }
+    }
+}
+
+impl<T> MpscProducer<T> {
+    /// Closes the buffer, see `SpscRb::close`.
+    pub fn close(&self) {
+        self.producer.close()
+    }
+
+    /// Blocks until at least `n` slots are free, see `Producer::wait_for_slots`.
+    pub fn wait_for_slots(&self, n: usize, timeout: Duration) -> Result<usize> {
+        self.producer.wait_for_slots(n, timeout)
+    }
+
+    /// Blocks until the consumers have read all pending values, see `Producer::flush`.
+    pub fn flush(&self) -> Result<()> {
+        self.producer.flush()
+    }
+
+    /// Blocks until the consumers have read all pending values or `timeout` has elapsed,
+    /// see `Producer::flush_timeout`.
+    pub fn flush_timeout(&self, timeout: Duration) -> Result<()> {
+        self.producer.flush_timeout(timeout)
+    }
+}
+
+impl<T: Clone> RbProducer<T> for MpscProducer<T> {
+    fn write(&self, data: &[T]) -> Result<usize> {
+        self.producer.write(data)
+    }
+
+    fn write_exact(&self, data: &[T]) -> Result<()> {
+        self.producer.write_exact(data)
+    }
+
+    fn write_blocking(&self, data: &[T]) -> Option<usize> {
+        self.producer.write_blocking(data)
+    }
+
+    fn write_blocking_timeout(&self, data: &[T], timeout: Duration) -> Result<Option<usize>> {
+        self.producer.write_blocking_timeout(data, timeout)
+    }
+
+    fn write_blocking_deadline(&self, data: &[T], deadline: Instant) -> Result<Option<usize>> {
+        self.producer.write_blocking_deadline(data, deadline)
+    }
+}
+
+impl<T> RbInspector for MpscProducer<T> {
+    fn is_empty(&self) -> bool {
+        self.producer.is_empty()
+    }
+    fn is_full(&self) -> bool {
+        self.producer.is_full()
+    }
+    fn capacity(&self) -> usize {
+        self.producer.capacity()
+    }
+    fn slots_free(&self) -> usize {
+        self.producer.slots_free()
+    }
+    fn count(&self) -> usize {
+        self.producer.count()
+    }
+}
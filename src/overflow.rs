@@ -0,0 +1,40 @@
+/// Determines how `write` handles a buffer without enough free slots.
+///
+/// The policy is chosen when the buffer is created, the same call sites behave
+/// differently per deployment this way, e.g. a recorder that blocks in a test setup and
+/// drops audio in production. The dedicated methods like `write_exact`, `write_blocking`,
+/// `write_dropping` and `write_overwriting` keep their behavior regardless of the policy.
+///
+/// ```
+/// use rb::*;
+///
+/// let rb = SpscRb::new(4).with_overflow_policy(OverflowPolicy::OverwriteOldest);
+/// let (prod, cons) = (rb.producer(), rb.consumer());
+/// assert_eq!(prod.write(&[1, 2, 3, 4, 5, 6]).unwrap(), 6);
+/// assert_eq!(cons.read_vec(4), vec![3, 4, 5, 6]);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Writes either all values or fails with `RbError::Full`, like `write_exact`.
+    Error,
+    /// Writes as many values as there are free slots and fails with `RbError::Full`
+    /// if there are none.
+    #[default]
+    Partial,
+    /// Writes as many values as there are free slots and discards the others,
+    /// like `write_dropping`. Reports all values as written.
+    DropNewest,
+    /// Writes all values and overwrites the oldest pending ones, like `write_overwriting`.
+    OverwriteOldest,
+    /// Blocks until there is at least one free slot, like `write_blocking`.
+    /// Requires the `std` feature, `SpscRb::with_overflow_policy` rejects it otherwise.
+    Block,
+    /// Doubles the capacity of a full buffer until all values fit, but not beyond `max`
+    /// values, and writes like `Partial` afterwards, see `SpscRb::resize`. For bursty
+    /// pipelines where an allocation now and then is acceptable but losing data is not.
+    /// Buffers with a fixed size, like `ConstSpscRb`, behave like `Partial`.
+    Grow {
+        /// Capacity that the buffer never grows beyond.
+        max: usize,
+    },
+}
@@ -0,0 +1,389 @@
+use std::cmp;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use copy::copy_from_ring;
+use sync::{Condvar, Mutex, MutexGuard};
+use {RbError, RbInspector, Result, Storage};
+
+struct State<T> {
+    // One lane per channel, each grows up to `size` samples, see `Storage` for `Vec`.
+    lanes: Vec<Vec<T>>,
+    size: usize,
+    // Total number of frames written and read, the positions of all lanes.
+    write_pos: u64,
+    read_pos: u64,
+    producers: usize,
+    consumers: usize,
+    // Set by `close`, disconnects both sides for good.
+    closed: bool,
+}
+
+impl<T> State<T> {
+    fn index(&self, pos: u64) -> usize {
+        (pos % self.size as u64) as usize
+    }
+
+    fn count(&self) -> usize {
+        (self.write_pos - self.read_pos) as usize
+    }
+
+    fn slots_free(&self) -> usize {
+        self.size - self.count()
+    }
+
+    fn producers_disconnected(&self) -> bool {
+        self.producers == 0 || self.closed
+    }
+
+    fn consumers_disconnected(&self) -> bool {
+        self.consumers == 0 || self.closed
+    }
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    slots_free: Condvar,
+    data_available: Condvar,
+}
+
+impl<T> Shared<T> {
+    /// Closes the buffer and wakes up all blocked threads.
+    fn close(&self) {
+        self.state.lock().closed = true;
+        self.slots_free.notify_all();
+        self.data_available.notify_all();
+    }
+
+    /// Waits on `condvar` until `ready` returns true or the `deadline` is reached.
+    fn wait_until<F>(
+        &self,
+        condvar: &Condvar,
+        deadline: Option<Instant>,
+        ready: F,
+    ) -> Result<MutexGuard<'_, State<T>>>
+    where
+        F: Fn(&State<T>) -> bool,
+    {
+        let mut guard = self.state.lock();
+        while !ready(&guard) {
+            guard = match deadline {
+                None => condvar.wait(&self.state, guard),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(RbError::TimedOut);
+                    }
+                    condvar.wait_timeout(&self.state, guard, deadline - now).0
+                }
+            };
+        }
+        Ok(guard)
+    }
+}
+
+/// A *thread-safe* ring buffer for multichannel audio that stores every channel in its own
+/// lane, i.e. planar as most DSP code expects it, instead of interleaved.
+///
+/// All lanes share one pair of positions and are written and read a frame at a time, thus
+/// the channels stay sample-aligned by construction. The capacity, the free slots and the
+/// pending values are counted in frames.
+///
+/// ```
+/// use rb::*;
+///
+/// let rb = PlanarRb::new(2, 64);
+/// let (prod, cons) = (rb.producer(), rb.consumer());
+/// prod.write(&[&[1, 2, 3], &[-1, -2, -3]]).unwrap();
+/// let (mut left, mut right) = ([0; 4], [0; 4]);
+/// assert_eq!(cons.read(&mut [&mut left, &mut right]).unwrap(), 3);
+/// assert_eq!(left[..3], [1, 2, 3]);
+/// assert_eq!(right[..3], [-1, -2, -3]);
+/// ```
+pub struct PlanarRb<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T: Clone> PlanarRb<T> {
+    /// Creates a buffer for `size` frames of `channels` samples each.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channels` or `size` is zero.
+    pub fn new(channels: usize, size: usize) -> Self {
+        assert!(channels > 0, "the buffer needs at least one channel");
+        assert!(size > 0, "The capacity of the buffer is zero");
+        PlanarRb {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State {
+                    lanes: (0..channels).map(|_| Vec::with_capacity(size)).collect(),
+                    size,
+                    write_pos: 0,
+                    read_pos: 0,
+                    producers: 0,
+                    consumers: 0,
+                    closed: false,
+                }),
+                slots_free: Condvar::new(),
+                data_available: Condvar::new(),
+            }),
+        }
+    }
+
+    /// Returns the number of channels, i.e. lanes.
+    pub fn channels(&self) -> usize {
+        self.shared.state.lock().lanes.len()
+    }
+
+    /// Closes the buffer, see `SpscRb::close`.
+    pub fn close(&self) {
+        self.shared.close();
+    }
+
+    /// Creates a *producer* view inside the buffer.
+    pub fn producer(&self) -> PlanarProducer<T> {
+        self.shared.state.lock().producers += 1;
+        PlanarProducer {
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Creates a *consumer* view inside the buffer.
+    pub fn consumer(&self) -> PlanarConsumer<T> {
+        self.shared.state.lock().consumers += 1;
+        PlanarConsumer {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> RbInspector for PlanarRb<T> {
+    fn is_empty(&self) -> bool {
+        self.count() == 0
+    }
+    fn is_full(&self) -> bool {
+        self.slots_free() == 0
+    }
+    fn capacity(&self) -> usize {
+        self.shared.state.lock().size
+    }
+    fn slots_free(&self) -> usize {
+        self.shared.state.lock().slots_free()
+    }
+    fn count(&self) -> usize {
+        self.shared.state.lock().count()
+    }
+}
+
+/// Producer view into a `PlanarRb`.
+pub struct PlanarProducer<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> PlanarProducer<T> {
+    /// Closes the buffer, see `SpscRb::close`.
+    pub fn close(&self) {
+        self.shared.close();
+    }
+}
+
+impl<T: Clone> PlanarProducer<T> {
+    /// Writes a frame for each sample of the shortest of the `channels`, one slice per
+    /// channel, as many as there are free slots for.
+    ///
+    /// Returns the number of written frames or an error.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Full`
+    /// - `RbError::Disconnected` all consumers were dropped
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of slices differs from the number of channels.
+    pub fn write(&self, channels: &[&[T]]) -> Result<usize> {
+        let mut state = self.shared.state.lock();
+        let frames = frames(&state, channels);
+        if state.consumers_disconnected() {
+            return Err(RbError::Disconnected);
+        }
+        if frames > 0 && state.slots_free() == 0 {
+            return Err(RbError::Full);
+        }
+        Ok(self.write_locked(&mut state, channels))
+    }
+
+    /// Works analog to `write` but blocks until there are free slots.
+    ///
+    /// Returns `None` if all consumers were dropped.
+    pub fn write_blocking(&self, channels: &[&[T]]) -> Option<usize> {
+        match self.write_until(channels, None) {
+            Ok(cnt) => Some(cnt),
+            Err(RbError::Disconnected) => None,
+            Err(_) => panic!("Max duration should not time out"),
+        }
+    }
+
+    /// Works analog to `write_blocking` but gives up after `timeout`.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::TimedOut`
+    /// - `RbError::Disconnected` all consumers were dropped
+    pub fn write_blocking_timeout(&self, channels: &[&[T]], timeout: Duration) -> Result<usize> {
+        self.write_until(channels, Instant::now().checked_add(timeout))
+    }
+
+    fn write_until(&self, channels: &[&[T]], deadline: Option<Instant>) -> Result<usize> {
+        let mut state = self
+            .shared
+            .wait_until(&self.shared.slots_free, deadline, |state| {
+                frames(state, channels) == 0
+                    || state.slots_free() > 0
+                    || state.consumers_disconnected()
+            })?;
+        if state.consumers_disconnected() {
+            return Err(RbError::Disconnected);
+        }
+        Ok(self.write_locked(&mut state, channels))
+    }
+
+    fn write_locked(&self, state: &mut State<T>, channels: &[&[T]]) -> usize {
+        let cnt = cmp::min(frames(state, channels), state.slots_free());
+        if cnt == 0 {
+            return 0;
+        }
+        let (size, wr_pos) = (state.size, state.index(state.write_pos));
+        for (lane, channel) in state.lanes.iter_mut().zip(channels) {
+            lane.store(size, wr_pos, &channel[..cnt]);
+        }
+        state.write_pos += cnt as u64;
+        self.shared.data_available.notify_all();
+        cnt
+    }
+}
+
+/// Wakes up blocked consumers once the last producer is dropped.
+impl<T> Drop for PlanarProducer<T> {
+    fn drop(&mut self) {
+        self.shared.state.lock().producers -= 1;
+        self.shared.data_available.notify_all();
+    }
+}
+
+/// Consumer view into a `PlanarRb`.
+pub struct PlanarConsumer<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> PlanarConsumer<T> {
+    /// Closes the buffer, see `SpscRb::close`.
+    pub fn close(&self) {
+        self.shared.close();
+    }
+}
+
+impl<T: Clone> PlanarConsumer<T> {
+    /// Reads pending frames into `channels`, one slice per channel, at most as many as
+    /// the shortest slice holds.
+    ///
+    /// Returns the number of read frames or an error.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Empty`
+    /// - `RbError::Disconnected` the buffer is empty and all producers were dropped
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of slices differs from the number of channels.
+    pub fn read(&self, channels: &mut [&mut [T]]) -> Result<usize> {
+        let mut state = self.shared.state.lock();
+        if frames(&state, channels) > 0 && state.count() == 0 {
+            return Err(if state.producers_disconnected() {
+                RbError::Disconnected
+            } else {
+                RbError::Empty
+            });
+        }
+        Ok(self.read_locked(&mut state, channels))
+    }
+
+    /// Works analog to `read` but blocks until there are pending frames.
+    ///
+    /// Returns `None` if the buffer is empty and all producers were dropped.
+    pub fn read_blocking(&self, channels: &mut [&mut [T]]) -> Option<usize> {
+        match self.read_until(channels, None) {
+            Ok(cnt) => Some(cnt),
+            Err(RbError::Disconnected) => None,
+            Err(_) => panic!("Max duration should not time out"),
+        }
+    }
+
+    /// Works analog to `read_blocking` but gives up after `timeout`.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::TimedOut`
+    /// - `RbError::Disconnected` the buffer is empty and all producers were dropped
+    pub fn read_blocking_timeout(
+        &self,
+        channels: &mut [&mut [T]],
+        timeout: Duration,
+    ) -> Result<usize> {
+        self.read_until(channels, Instant::now().checked_add(timeout))
+    }
+
+    fn read_until(&self, channels: &mut [&mut [T]], deadline: Option<Instant>) -> Result<usize> {
+        let wanted = channels.iter().map(|channel| channel.len()).min();
+        let mut state = self
+            .shared
+            .wait_until(&self.shared.data_available, deadline, |state| {
+                wanted == Some(0) || state.count() > 0 || state.producers_disconnected()
+            })?;
+        if frames(&state, channels) > 0 && state.count() == 0 {
+            return Err(RbError::Disconnected);
+        }
+        Ok(self.read_locked(&mut state, channels))
+    }
+
+    fn read_locked(&self, state: &mut State<T>, channels: &mut [&mut [T]]) -> usize {
+        let cnt = cmp::min(frames(state, channels), state.count());
+        if cnt == 0 {
+            return 0;
+        }
+        let re_pos = state.index(state.read_pos);
+        for (lane, channel) in state.lanes.iter().zip(channels) {
+            copy_from_ring(lane, re_pos, &mut channel[..cnt]);
+        }
+        state.read_pos += cnt as u64;
+        self.shared.slots_free.notify_all();
+        cnt
+    }
+}
+
+/// Wakes up blocked producers once the last consumer is dropped.
+impl<T> Drop for PlanarConsumer<T> {
+    fn drop(&mut self) {
+        self.shared.state.lock().consumers -= 1;
+        self.shared.slots_free.notify_all();
+    }
+}
+
+/// Returns the length of the shortest of the `channels`.
+///
+/// # Panics
+///
+/// Panics if the number of slices differs from the number of lanes.
+fn frames<T, C: AsRef<[T]>>(state: &State<T>, channels: &[C]) -> usize {
+    assert_eq!(
+        channels.len(),
+        state.lanes.len(),
+        "one slice per channel is required"
+    );
+    channels
+        .iter()
+        .map(|channel| channel.as_ref().len())
+        .min()
+        .unwrap_or(0)
+}
@@ -0,0 +1,86 @@
+use sync::Mutex;
+use {Consumer, Producer, SpscRb};
+
+/// A pool of buffers of the same capacity that are reused once both of their handles
+/// were dropped, e.g. for per-voice buffers of a synthesizer, which avoids allocating a
+/// new buffer for each of many short-lived streams.
+///
+/// ```
+/// use rb::*;
+///
+/// let pool = RbPool::new(1024, 2);
+/// let (prod, cons) = pool.get();
+/// prod.write(&[1.0f32; 256]).unwrap();
+/// assert_eq!(pool.idle(), 1);
+/// drop((prod, cons));
+/// assert_eq!(pool.idle(), 2);
+/// // the recycled buffer is empty again
+/// let (_prod, cons) = pool.get();
+/// assert!(cons.is_empty());
+/// ```
+pub struct RbPool<T> {
+    buffers: Mutex<Vec<SpscRb<T>>>,
+    capacity: usize,
+}
+
+impl<T: Clone> RbPool<T> {
+    /// Creates a pool of `count` buffers with `capacity` values each, which are
+    /// allocated upfront.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero or too large, see `SpscRb::try_new`.
+    pub fn new(capacity: usize, count: usize) -> Self {
+        RbPool {
+            buffers: Mutex::new((0..count).map(|_| pooled(capacity)).collect()),
+            capacity,
+        }
+    }
+
+    /// Returns the producer and the consumer of an idle buffer, whose pending values
+    /// and counters were reset. A new buffer is added to the pool if all are in use.
+    pub fn get(&self) -> (Producer<T>, Consumer<T>) {
+        let mut buffers = self.buffers.lock();
+        if let Some(handles) = buffers.iter().find_map(SpscRb::recycle) {
+            return handles;
+        }
+        let rb = pooled(self.capacity);
+        let handles = rb.recycle().expect("a new buffer has no handles");
+        buffers.push(rb);
+        handles
+    }
+
+    /// Returns the number of buffers that are available without allocating.
+    pub fn idle(&self) -> usize {
+        self.buffers.lock().iter().filter(|rb| rb.is_idle()).count()
+    }
+
+    /// Returns the number of buffers of the pool, in use or idle.
+    pub fn len(&self) -> usize {
+        self.buffers.lock().len()
+    }
+
+    /// Returns true if the pool has no buffers at all.
+    pub fn is_empty(&self) -> bool {
+        self.buffers.lock().is_empty()
+    }
+
+    /// Returns the capacity of each buffer of the pool.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Drops all idle buffers, e.g. after a burst of streams, the buffers in use stay
+    /// in the pool.
+    pub fn shrink(&self) {
+        self.buffers.lock().retain(|rb| !rb.is_idle());
+    }
+}
+
+/// Creates a buffer for the pool, whose handles disconnect once their peer is dropped since
+/// the pool only hands out both of them at once.
+fn pooled<T: Clone>(capacity: usize) -> SpscRb<T> {
+    let rb = SpscRb::new(capacity);
+    rb.disown();
+    rb
+}
@@ -0,0 +1,161 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use psimple::Simple;
+use pulse::error::{Code, PAErr};
+use pulse::sample::{Format, Spec};
+use pulse::stream::Direction;
+
+use {Consumer, Producer, RbProducer};
+
+const SAMPLE_SIZE: usize = 4;
+
+/// Connects the ring buffer to a PulseAudio (or PipeWire-Pulse) stream.
+///
+/// A background thread takes the role of the stream's process callback:
+/// a playback stream pulls blocks from a `Consumer<f32>`, a capture stream pushes the
+/// recorded blocks into a `Producer<f32>`. The buffer never blocks the audio thread,
+/// instead every block that could not be served completely is counted as xrun, i.e.
+/// an underrun for playback (the missing samples are played as silence) and an overrun
+/// for capture (the samples that did not fit are dropped).
+///
+/// The sample format of the stream must be `Format::FLOAT32NE`. Streams that are driven
+/// by hand can use `playback_callback` and `capture_callback` directly.
+pub struct PulseStream {
+    running: Arc<AtomicBool>,
+    xruns: Arc<AtomicUsize>,
+    handle: Option<JoinHandle<Result<(), PAErr>>>,
+}
+
+impl PulseStream {
+    /// Opens a playback stream that plays everything read from `consumer`.
+    /// `block_frames` is the number of frames that are written to the server at once.
+    pub fn playback(
+        name: &str,
+        spec: &Spec,
+        consumer: Consumer<f32>,
+        block_frames: usize,
+    ) -> Result<Self, PAErr> {
+        let simple = connect(name, spec, Direction::Playback)?;
+        let block_len = block_frames.max(1) * spec.channels as usize;
+        Ok(PulseStream::spawn(move |running, xruns| {
+            let mut process = playback_callback(consumer, xruns);
+            let mut bytes = vec![0u8; block_len * SAMPLE_SIZE];
+            while running.load(Ordering::Relaxed) {
+                process(&mut bytes);
+                simple.write(&bytes)?;
+            }
+            simple.drain()
+        }))
+    }
+
+    /// Opens a capture stream that writes all recorded samples into `producer`.
+    /// `block_frames` is the number of frames that are read from the server at once.
+    pub fn capture(
+        name: &str,
+        spec: &Spec,
+        producer: Producer<f32>,
+        block_frames: usize,
+    ) -> Result<Self, PAErr> {
+        let simple = connect(name, spec, Direction::Record)?;
+        let block_len = block_frames.max(1) * spec.channels as usize;
+        Ok(PulseStream::spawn(move |running, xruns| {
+            let mut process = capture_callback(producer, xruns);
+            let mut bytes = vec![0u8; block_len * SAMPLE_SIZE];
+            while running.load(Ordering::Relaxed) {
+                simple.read(&mut bytes)?;
+                process(&bytes);
+            }
+            Ok(())
+        }))
+    }
+
+    /// Returns the number of blocks that could not be served completely.
+    pub fn xruns(&self) -> usize {
+        self.xruns.load(Ordering::Relaxed)
+    }
+
+    /// Stops the stream thread and returns the first error it encountered.
+    pub fn stop(mut self) -> Result<(), PAErr> {
+        self.join()
+    }
+
+    fn spawn<F>(process: F) -> Self
+    where
+        F: FnOnce(&AtomicBool, Arc<AtomicUsize>) -> Result<(), PAErr> + Send + 'static,
+    {
+        let running = Arc::new(AtomicBool::new(true));
+        let xruns = Arc::new(AtomicUsize::new(0));
+        let (thread_running, thread_xruns) = (running.clone(), xruns.clone());
+        let handle = thread::spawn(move || process(&thread_running, thread_xruns));
+        PulseStream {
+            running,
+            xruns,
+            handle: Some(handle),
+        }
+    }
+
+    fn join(&mut self) -> Result<(), PAErr> {
+        self.running.store(false, Ordering::Relaxed);
+        match self.handle.take() {
+            Some(handle) => handle
+                .join()
+                .unwrap_or_else(|_| Err(PAErr::from(Code::Killed))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for PulseStream {
+    fn drop(&mut self) {
+        let _ = self.join();
+    }
+}
+
+/// Returns the process callback of a playback stream, which fills a block of
+/// `Format::FLOAT32NE` samples with the ones read from `consumer`. If less samples are
+/// pending than fit, the rest is filled with silence and `xruns` is incremented.
+pub fn playback_callback(
+    consumer: Consumer<f32>,
+    xruns: Arc<AtomicUsize>,
+) -> impl FnMut(&mut [u8]) + Send + 'static {
+    let mut block = Vec::new();
+    move |bytes| {
+        block.resize(bytes.len() / SAMPLE_SIZE, 0.0);
+        if consumer.read_or_default(&mut block) < block.len() {
+            xruns.fetch_add(1, Ordering::Relaxed);
+        }
+        for (sample, chunk) in block.iter().zip(bytes.chunks_exact_mut(SAMPLE_SIZE)) {
+            chunk.copy_from_slice(&sample.to_ne_bytes());
+        }
+    }
+}
+
+/// Returns the process callback of a capture stream, which writes a block of
+/// `Format::FLOAT32NE` samples into `producer`. If not all of them fit, the rest is dropped
+/// and `xruns` is incremented.
+pub fn capture_callback(
+    producer: Producer<f32>,
+    xruns: Arc<AtomicUsize>,
+) -> impl FnMut(&[u8]) + Send + 'static {
+    let mut block = Vec::new();
+    move |bytes| {
+        block.clear();
+        block.extend(
+            bytes
+                .chunks_exact(SAMPLE_SIZE)
+                .map(|chunk| f32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]])),
+        );
+        if producer.write(&block).unwrap_or(0) < block.len() {
+            xruns.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+fn connect(name: &str, spec: &Spec, direction: Direction) -> Result<Simple, PAErr> {
+    if spec.format != Format::FLOAT32NE || !spec.is_valid() {
+        return Err(PAErr::from(Code::Invalid));
+    }
+    Simple::new(None, name, direction, None, name, spec, None, None)
+}
@@ -0,0 +1,271 @@
+#[cfg(target_os = "linux")]
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::os::unix::io::{AsRawFd, RawFd};
+#[cfg(not(target_os = "linux"))]
+use std::os::unix::net::UnixStream;
+use std::sync::Arc;
+
+#[cfg(feature = "mio")]
+use mio::event::Source;
+#[cfg(feature = "mio")]
+use mio::unix::SourceFd;
+#[cfg(feature = "mio")]
+use mio::{Interest, Registry, Token};
+
+use wait::Signal;
+use {Consumer, MpmcConsumer, MpscProducer, Producer, RbInspector};
+
+/// OS-level readiness handle of one side of a buffer, for event loops like mio.
+///
+/// Its file descriptor is an eventfd on Linux and one end of a socket pair elsewhere.
+/// The file descriptor of a consumer's handle becomes readable when values are pending
+/// or the producers disconnected. The one of a producer's handle becomes readable when
+/// slots are free or the consumers disconnected. In both cases it stays readable
+/// until `clear` is called, i.e. wait until it's readable and call `clear` before reading
+/// or writing until the buffer is empty or full.
+///
+/// Without registered readiness handles notifications don't cost any syscalls,
+/// with them every write or read of the other side signals the file descriptor.
+pub struct Readiness {
+    signal: Arc<Signal>,
+    notifier: Arc<Notifier>,
+}
+
+impl Readiness {
+    fn new(signal: &Arc<Signal>, ready: bool) -> io::Result<Self> {
+        let notifier = Arc::new(Notifier::new()?);
+        signal.add_notifier(notifier.clone());
+        // Everything before the registration was missed.
+        if ready {
+            notifier.notify();
+        }
+        Ok(Readiness {
+            signal: signal.clone(),
+            notifier,
+        })
+    }
+
+    /// Resets the file descriptor to not readable, until the next write or read of the
+    /// other side. The buffer has to be checked afterwards, it's not reset by itself.
+    pub fn clear(&self) {
+        self.notifier.clear();
+    }
+}
+
+impl AsRawFd for Readiness {
+    fn as_raw_fd(&self) -> RawFd {
+        self.notifier.as_raw_fd()
+    }
+}
+
+/// Register it with `Interest::READABLE`:
+///
+/// ```
+/// # extern crate mio;
+/// use mio::{Events, Interest, Poll, Token};
+/// use rb::*;
+///
+/// let rb = SpscRb::new(4);
+/// let (prod, cons) = (rb.producer(), rb.consumer());
+/// let mut readiness = cons.readiness().unwrap();
+/// let mut poll = Poll::new().unwrap();
+/// poll.registry()
+///     .register(&mut readiness, Token(0), Interest::READABLE)
+///     .unwrap();
+/// prod.write(&[1, 2, 3]).unwrap();
+///
+/// let mut events = Events::with_capacity(1);
+/// poll.poll(&mut events, None).unwrap();
+/// readiness.clear();
+/// let mut buf = [0; 4];
+/// assert_eq!(cons.read(&mut buf).unwrap(), 3);
+/// ```
+#[cfg(feature = "mio")]
+impl Source for Readiness {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        SourceFd(&self.as_raw_fd()).register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        SourceFd(&self.as_raw_fd()).reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        SourceFd(&self.as_raw_fd()).deregister(registry)
+    }
+}
+
+impl Drop for Readiness {
+    fn drop(&mut self) {
+        self.signal.remove_notifier(&self.notifier);
+    }
+}
+
+impl<T, S> Producer<T, S> {
+    /// Returns a readiness handle that signals free slots, see `Readiness`.
+    pub fn readiness(&self) -> io::Result<Readiness> {
+        let _guard = self.buf.lock();
+        Readiness::new(
+            &self.slots_free,
+            !self.inspector.is_full() || self.inspector.consumers_disconnected(),
+        )
+    }
+
+    /// Returns a file descriptor that becomes readable when slots are free or the consumers
+    /// disconnected, for event loops that are not written in Rust, e.g. GLib or libuv.
+    ///
+    /// It stays readable until it's drained by reading from it until the read would block,
+    /// afterwards the buffer has to be checked again. The descriptor is created by the first
+    /// call and stays valid as long as the producer, it must not be closed by the caller.
+    pub fn readiness_fd(&self) -> io::Result<RawFd> {
+        if let Some(readiness) = self.readiness.get() {
+            return Ok(readiness.as_raw_fd());
+        }
+        let readiness = self.readiness()?;
+        Ok(self.readiness.get_or_init(|| readiness).as_raw_fd())
+    }
+}
+
+impl<T, S> Consumer<T, S> {
+    /// Returns a readiness handle that signals pending values, see `Readiness`.
+    pub fn readiness(&self) -> io::Result<Readiness> {
+        let _guard = self.buf.lock();
+        Readiness::new(
+            &self.data_available,
+            !self.inspector.is_empty() || self.inspector.producers_disconnected(),
+        )
+    }
+
+    /// Returns a file descriptor that becomes readable when values are pending or the
+    /// producers disconnected, see `Producer::readiness_fd`.
+    ///
+    /// ```
+    /// # extern crate libc;
+    /// # extern crate rb;
+    /// use rb::*;
+    ///
+    /// # fn main() {
+    /// let rb = SpscRb::new(4);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// let fd = cons.readiness_fd().unwrap();
+    /// prod.write(&[1, 2]).unwrap();
+    /// let mut pollfd = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+    /// assert_eq!(unsafe { libc::poll(&mut pollfd, 1, 1000) }, 1);
+    /// # }
+    /// ```
+    pub fn readiness_fd(&self) -> io::Result<RawFd> {
+        if let Some(readiness) = self.readiness.get() {
+            return Ok(readiness.as_raw_fd());
+        }
+        let readiness = self.readiness()?;
+        Ok(self.readiness.get_or_init(|| readiness).as_raw_fd())
+    }
+}
+
+impl<T> MpscProducer<T> {
+    /// Returns a readiness handle that signals free slots, see `Readiness`.
+    pub fn readiness(&self) -> io::Result<Readiness> {
+        self.producer.readiness()
+    }
+
+    /// Returns a file descriptor that signals free slots, see `Producer::readiness_fd`.
+    pub fn readiness_fd(&self) -> io::Result<RawFd> {
+        self.producer.readiness_fd()
+    }
+}
+
+impl<T> MpmcConsumer<T> {
+    /// Returns a readiness handle that signals pending values, see `Readiness`.
+    pub fn readiness(&self) -> io::Result<Readiness> {
+        self.consumer.readiness()
+    }
+
+    /// Returns a file descriptor that signals pending values, see `Consumer::readiness_fd`.
+    pub fn readiness_fd(&self) -> io::Result<RawFd> {
+        self.consumer.readiness_fd()
+    }
+}
+
+/// Counter of an eventfd, it's readable while it's not zero.
+#[cfg(target_os = "linux")]
+pub(crate) struct Notifier(File);
+
+/// Connected pair of sockets, the reading end is readable while there are bytes to read.
+#[cfg(not(target_os = "linux"))]
+pub(crate) struct Notifier {
+    reader: UnixStream,
+    writer: UnixStream,
+}
+
+impl Notifier {
+    #[cfg(target_os = "linux")]
+    fn new() -> io::Result<Self> {
+        use std::os::unix::io::{FromRawFd, OwnedFd};
+
+        // Safety: a plain syscall without pointer arguments.
+        let fd = unsafe { libc::eventfd(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // Safety: `fd` was just opened and is owned by nothing else.
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        Ok(Notifier(File::from(fd)))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn new() -> io::Result<Self> {
+        let (reader, writer) = UnixStream::pair()?;
+        reader.set_nonblocking(true)?;
+        writer.set_nonblocking(true)?;
+        Ok(Notifier { reader, writer })
+    }
+
+    /// Makes the file descriptor readable.
+    #[cfg(target_os = "linux")]
+    pub(crate) fn notify(&self) {
+        // Only fails if the counter would overflow, it's readable anyway then.
+        let _ = (&self.0).write(&1u64.to_ne_bytes());
+    }
+
+    /// Makes the file descriptor readable.
+    #[cfg(not(target_os = "linux"))]
+    pub(crate) fn notify(&self) {
+        // Only fails if the socket buffer is full, it's readable anyway then.
+        let _ = (&self.writer).write(&[1]);
+    }
+
+    #[cfg(target_os = "linux")]
+    fn clear(&self) {
+        // Reading resets the counter to zero, or fails if it's zero already.
+        let _ = (&self.0).read(&mut [0; 8]);
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn clear(&self) {
+        let mut buf = [0; 64];
+        while let Ok(1..) = (&self.reader).read(&mut buf) {}
+    }
+}
+
+impl AsRawFd for Notifier {
+    #[cfg(target_os = "linux")]
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn as_raw_fd(&self) -> RawFd {
+        self.reader.as_raw_fd()
+    }
+}
@@ -0,0 +1,65 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use wait::park_timeout;
+use {Consumer, RbError, RbInspector, Result};
+
+/// Blocks until any of the `consumers` has pending values or `timeout` has elapsed,
+/// e.g. for a mixer thread that is fed by several buffers.
+///
+/// Returns the index of the first consumer with pending values, the following read of it
+/// does not block. Consumers whose producers disconnected and that have nothing left
+/// to read are skipped. The thread is parked while waiting, regardless of the
+/// `WaitStrategy` of the consumers.
+///
+/// ```
+/// use std::thread;
+/// use std::time::Duration;
+/// use rb::*;
+///
+/// let (rb1, rb2) = (SpscRb::new(4), SpscRb::new(4));
+/// let (cons1, cons2) = (rb1.consumer(), rb2.consumer());
+/// let prod2 = rb2.producer();
+/// thread::spawn(move || prod2.write(&[1]).unwrap());
+/// assert_eq!(select(&[&cons1, &cons2], Duration::from_secs(1)).unwrap(), 1);
+/// ```
+///
+/// Possible errors:
+///
+/// - `RbError::TimedOut`
+/// - `RbError::Disconnected` the producers of all buffers were dropped
+///   and nothing is left to read
+pub fn select<T, S>(consumers: &[&Consumer<T, S>], timeout: Duration) -> Result<usize> {
+    let start = Instant::now();
+    let current = thread::current();
+    let slots: Vec<_> = consumers
+        .iter()
+        .map(|consumer| consumer.data_available.add_parked(current.clone()))
+        .collect();
+    // Without a slot at any of the buffers, its notifications are missed.
+    let registered = slots.iter().all(Option::is_some);
+    let result = loop {
+        let mut disconnected = true;
+        let ready = consumers.iter().position(|consumer| {
+            disconnected &= consumer.inspector.producers_disconnected();
+            !consumer.inspector.is_empty()
+        });
+        if let Some(idx) = ready {
+            break Ok(idx);
+        }
+        if disconnected {
+            break Err(RbError::Disconnected);
+        }
+        let elapsed = start.elapsed();
+        if elapsed >= timeout {
+            break Err(RbError::TimedOut);
+        }
+        park_timeout(registered, timeout - elapsed);
+    };
+    for (consumer, slot) in consumers.iter().zip(slots) {
+        if let Some(slot) = slot {
+            consumer.data_available.remove_parked(slot);
+        }
+    }
+    result
+}
@@ -0,0 +1,588 @@
+use std::cmp;
+use std::ffi::CString;
+use std::fs::File;
+use std::io;
+use std::marker::PhantomData;
+use std::mem;
+use std::os::unix::io::FromRawFd;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use mmap::Mapping;
+use {RbConsumer, RbError, RbInspector, RbProducer, Result};
+
+/// Values that are plain bytes, valid for any bit pattern and without pointers, thus they
+/// can be passed to another process, see `ShmSpscRb`.
+///
+/// # Safety
+///
+/// Implementors must not contain padding, pointers or references and every bit pattern
+/// must be a valid value.
+pub unsafe trait Pod: Copy + 'static {}
+
+unsafe impl Pod for u8 {}
+unsafe impl Pod for u16 {}
+unsafe impl Pod for u32 {}
+unsafe impl Pod for u64 {}
+unsafe impl Pod for i8 {}
+unsafe impl Pod for i16 {}
+unsafe impl Pod for i32 {}
+unsafe impl Pod for i64 {}
+unsafe impl Pod for f32 {}
+unsafe impl Pod for f64 {}
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+
+/// Identifies the shared memory of a `ShmSpscRb`.
+const MAGIC: u64 = u64::from_ne_bytes(*b"rb-shm\0\0");
+
+/// Beginning of the shared memory, followed by the `size` slots.
+#[repr(C)]
+struct Header {
+    // Published last by the creator, the other fields are valid once it is set.
+    magic: AtomicU64,
+    size: u64,
+    value_size: u64,
+    read_pos: AtomicU64,
+    write_pos: AtomicU64,
+    // Set once a process took the respective handle.
+    producer_taken: AtomicBool,
+    consumer_taken: AtomicBool,
+    // Set when the respective handle is dropped or the buffer is closed.
+    producer_dropped: AtomicBool,
+    consumer_dropped: AtomicBool,
+}
+
+/// A *process-safe* Single-Producer-Single-Consumer RingBuffer in POSIX shared memory,
+/// e.g. for a capture daemon that feeds an analysis process. Unix only.
+///
+/// One process creates the buffer with `create` and the other one opens it by its name
+/// with `open`, then each of them takes its handle, which provide the same slice-based
+/// methods as the ones of the other buffers. The slots, positions and the state of the
+/// handles are in the shared memory, the handles are lock-free and their blocking calls
+/// yield the thread until they can make progress.
+///
+/// A process that exits without dropping its handle, e.g. because it crashed, doesn't
+/// disconnect the other side. A position in the shared memory that is out of bounds
+/// disconnects the handles as well.
+///
+/// ```
+/// use rb::*;
+///
+/// let name = format!("/rb-doc-{}", std::process::id());
+/// let rb = ShmSpscRb::<f32>::create(&name, 1024).unwrap();
+/// let prod = rb.producer().unwrap();
+/// prod.write(&[1.0, 2.0, 3.0]).unwrap();
+///
+/// // usually in another process
+/// let other = ShmSpscRb::<f32>::open(&name).unwrap();
+/// let cons = other.consumer().unwrap();
+/// let mut data = [0.0; 4];
+/// assert_eq!(cons.read(&mut data).unwrap(), 3);
+/// assert_eq!(data[..3], [1.0, 2.0, 3.0]);
+/// ```
+pub struct ShmSpscRb<T> {
+    map: Mapping,
+    size: usize,
+    // Set for the creator, which removes the name once it drops the buffer.
+    name: Option<CString>,
+    marker: PhantomData<T>,
+}
+
+impl<T: Pod> ShmSpscRb<T> {
+    /// Creates the shared memory `name` for `capacity` values, it must not exist yet.
+    /// The name starts with a slash, e.g. `"/capture"`, and is removed again once the
+    /// returned buffer is dropped, processes that opened it keep their mapping.
+    pub fn create(name: &str, capacity: usize) -> io::Result<Self> {
+        if capacity == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The capacity of the buffer is zero",
+            ));
+        }
+        let size = capacity + 1;
+        let len = mem::size_of::<T>()
+            .checked_mul(size)
+            .and_then(|len| len.checked_add(Self::slots_offset()))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "The capacity of the buffer is too large",
+                )
+            })?;
+        let c_name = shm_name(name)?;
+        let file = shm_open(&c_name, libc::O_RDWR | libc::O_CREAT | libc::O_EXCL)?;
+        let rb = file
+            .set_len(len as u64)
+            .and_then(|_| Mapping::new(&file, len))
+            .map(|map| ShmSpscRb {
+                map,
+                size,
+                name: Some(c_name.clone()),
+                marker: PhantomData,
+            });
+        let rb = match rb {
+            Ok(rb) => rb,
+            Err(err) => {
+                // Safety: `c_name` is a valid C string.
+                unsafe { libc::shm_unlink(c_name.as_ptr()) };
+                return Err(err);
+            }
+        };
+        let header = rb.map.as_ptr() as *mut Header;
+        // Safety: the mapping starts with the header and is aligned to a page. The other
+        // process ignores the memory until the magic is set, which comes last.
+        unsafe {
+            (*header).size = size as u64;
+            (*header).value_size = mem::size_of::<T>() as u64;
+        }
+        rb.header().magic.store(MAGIC, Ordering::Release);
+        Ok(rb)
+    }
+
+    /// Opens the shared memory `name` that was created by `create` for the same `T`.
+    ///
+    /// Fails with `io::ErrorKind::InvalidData` if it isn't a buffer for `T`.
+    pub fn open(name: &str) -> io::Result<Self> {
+        let file = shm_open(&shm_name(name)?, libc::O_RDWR)?;
+        let len = file.metadata()?.len() as usize;
+        if len <= Self::slots_offset() || mem::size_of::<T>() == 0 {
+            return Err(invalid_memory());
+        }
+        let size = (len - Self::slots_offset()) / mem::size_of::<T>();
+        let rb = ShmSpscRb {
+            map: Mapping::new(&file, len)?,
+            size,
+            name: None,
+            marker: PhantomData,
+        };
+        let header = rb.header();
+        if header.magic.load(Ordering::Acquire) != MAGIC
+            || header.size != size as u64
+            || header.value_size != mem::size_of::<T>() as u64
+            || header.read_pos.load(Ordering::Acquire) >= size as u64
+            || header.write_pos.load(Ordering::Acquire) >= size as u64
+        {
+            return Err(invalid_memory());
+        }
+        Ok(rb)
+    }
+
+    /// Returns the producer of the buffer, `None` if a process took it already.
+    pub fn producer(&self) -> Option<ShmProducer<'_, T>> {
+        if self.header().producer_taken.swap(true, Ordering::AcqRel) {
+            return None;
+        }
+        Some(ShmProducer {
+            rb: self,
+            marker: PhantomData,
+        })
+    }
+
+    /// Returns the consumer of the buffer, `None` if a process took it already.
+    pub fn consumer(&self) -> Option<ShmConsumer<'_, T>> {
+        if self.header().consumer_taken.swap(true, Ordering::AcqRel) {
+            return None;
+        }
+        Some(ShmConsumer {
+            rb: self,
+            marker: PhantomData,
+        })
+    }
+
+    /// Closes the buffer for both processes, like `StaticSpscRb::close`.
+    pub fn close(&self) {
+        self.header()
+            .producer_dropped
+            .store(true, Ordering::Release);
+        self.header()
+            .consumer_dropped
+            .store(true, Ordering::Release);
+    }
+
+    /// Offset of the first slot, the header padded to the alignment of `T`.
+    fn slots_offset() -> usize {
+        mem::size_of::<Header>().next_multiple_of(mem::align_of::<T>())
+    }
+
+    fn slots(&self) -> *mut T {
+        // Safety: the slots follow the header within the mapping.
+        unsafe { self.map.as_ptr().add(Self::slots_offset()) as *mut T }
+    }
+
+    /// Returns the number of free slots for the given positions.
+    #[inline(always)]
+    fn slots_free_between(&self, wr_pos: usize, re_pos: usize) -> usize {
+        if wr_pos < re_pos {
+            re_pos - wr_pos - 1
+        } else {
+            self.size - 1 - wr_pos + re_pos
+        }
+    }
+
+    /// Copies `data` into the slots starting at `wr_pos`.
+    ///
+    /// Only the producer may call this and `data` must fit into the free slots.
+    #[inline(always)]
+    fn copy_to_ring(&self, wr_pos: usize, data: &[T]) {
+        let first = cmp::min(data.len(), self.size - wr_pos);
+        // Safety: the free slots are not accessed by the consumer and both ranges are in bounds.
+        unsafe {
+            ptr::copy_nonoverlapping(data.as_ptr(), self.slots().add(wr_pos), first);
+            ptr::copy_nonoverlapping(data.as_ptr().add(first), self.slots(), data.len() - first);
+        }
+    }
+
+    /// Copies the slots starting at `re_pos` into `data`.
+    ///
+    /// Only the consumer may call this and `data` must not be longer than the pending values.
+    #[inline(always)]
+    fn copy_from_ring(&self, re_pos: usize, data: &mut [T]) {
+        let first = cmp::min(data.len(), self.size - re_pos);
+        // Safety: the pending slots are not accessed by the producer until the read position
+        // is advanced, both ranges are in bounds and any bit pattern is a valid `T`.
+        unsafe {
+            ptr::copy_nonoverlapping(self.slots().add(re_pos), data.as_mut_ptr(), first);
+            ptr::copy_nonoverlapping(
+                self.slots(),
+                data.as_mut_ptr().add(first),
+                data.len() - first,
+            );
+        }
+    }
+
+    /// Loads a position from the shared memory, which any process might have changed.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Disconnected` the position is out of bounds
+    #[inline(always)]
+    fn load_pos(&self, pos: &AtomicU64, order: Ordering) -> Result<usize> {
+        match pos.load(order) {
+            pos if pos < self.size as u64 => Ok(pos as usize),
+            _ => Err(RbError::Disconnected),
+        }
+    }
+
+    /// Returns the number of pending values, zero if a position is out of bounds.
+    fn pending_count(&self) -> usize {
+        let header = self.header();
+        match (
+            self.load_pos(&header.write_pos, Ordering::Acquire),
+            self.load_pos(&header.read_pos, Ordering::Acquire),
+        ) {
+            (Ok(wr_pos), Ok(re_pos)) => self.size - 1 - self.slots_free_between(wr_pos, re_pos),
+            _ => 0,
+        }
+    }
+}
+
+impl<T> ShmSpscRb<T> {
+    fn header(&self) -> &Header {
+        // Safety: the mapping starts with the header and is aligned to a page.
+        unsafe { &*(self.map.as_ptr() as *const Header) }
+    }
+}
+
+impl<T> Drop for ShmSpscRb<T> {
+    fn drop(&mut self) {
+        if let Some(ref name) = self.name {
+            // Safety: `name` is a valid C string.
+            unsafe { libc::shm_unlink(name.as_ptr()) };
+        }
+    }
+}
+
+impl<T: Pod> RbInspector for ShmSpscRb<T> {
+    fn is_empty(&self) -> bool {
+        self.count() == 0
+    }
+    fn is_full(&self) -> bool {
+        self.slots_free() == 0
+    }
+    fn capacity(&self) -> usize {
+        self.size - 1
+    }
+    fn slots_free(&self) -> usize {
+        self.capacity() - self.count()
+    }
+    fn count(&self) -> usize {
+        self.pending_count()
+    }
+}
+
+fn shm_name(name: &str) -> io::Result<CString> {
+    CString::new(name).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))
+}
+
+fn shm_open(name: &CString, flags: libc::c_int) -> io::Result<File> {
+    // Safety: `name` is a valid C string.
+    let fd = unsafe { libc::shm_open(name.as_ptr(), flags, 0o600 as libc::mode_t) };
+    if fd == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    // Safety: the file descriptor was just opened and is owned by nothing else.
+    Ok(unsafe { File::from_raw_fd(fd) })
+}
+
+fn invalid_memory() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "not a ring buffer for this type",
+    )
+}
+
+/// Producer view into a `ShmSpscRb`.
+///
+/// It can be moved to another thread, but not shared between threads.
+pub struct ShmProducer<'a, T: 'a> {
+    rb: &'a ShmSpscRb<T>,
+    marker: PhantomData<*const ()>,
+}
+
+// Only one thread accesses the producer's slots at a time.
+unsafe impl<'a, T: Pod> Send for ShmProducer<'a, T> {}
+
+/// Consumer view into a `ShmSpscRb`.
+///
+/// It can be moved to another thread, but not shared between threads.
+pub struct ShmConsumer<'a, T: 'a> {
+    rb: &'a ShmSpscRb<T>,
+    marker: PhantomData<*const ()>,
+}
+
+// Only one thread accesses the consumer's slots at a time.
+unsafe impl<'a, T: Pod> Send for ShmConsumer<'a, T> {}
+
+impl<'a, T: Pod> ShmProducer<'a, T> {
+    /// Closes the buffer, see `ShmSpscRb::close`.
+    pub fn close(&self) {
+        self.rb.close();
+    }
+}
+
+impl<'a, T> Drop for ShmProducer<'a, T> {
+    fn drop(&mut self) {
+        // Publishes the final write position along with the flag.
+        self.rb
+            .header()
+            .producer_dropped
+            .store(true, Ordering::Release);
+    }
+}
+
+impl<'a, T> Drop for ShmConsumer<'a, T> {
+    fn drop(&mut self) {
+        self.rb
+            .header()
+            .consumer_dropped
+            .store(true, Ordering::Release);
+    }
+}
+
+impl<'a, T: Pod> RbProducer<T> for ShmProducer<'a, T> {
+    fn write(&self, data: &[T]) -> Result<usize> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        let header = self.rb.header();
+        if header.consumer_dropped.load(Ordering::Acquire) {
+            return Err(RbError::Disconnected);
+        }
+        let wr_pos = self.rb.load_pos(&header.write_pos, Ordering::Relaxed)?;
+        let re_pos = self.rb.load_pos(&header.read_pos, Ordering::Acquire)?;
+        let cnt = cmp::min(data.len(), self.rb.slots_free_between(wr_pos, re_pos));
+        if cnt == 0 {
+            return Err(RbError::Full);
+        }
+        self.rb.copy_to_ring(wr_pos, &data[..cnt]);
+        header
+            .write_pos
+            .store(((wr_pos + cnt) % self.rb.size) as u64, Ordering::Release);
+        Ok(cnt)
+    }
+
+    fn write_exact(&self, data: &[T]) -> Result<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        let header = self.rb.header();
+        if header.consumer_dropped.load(Ordering::Acquire) {
+            return Err(RbError::Disconnected);
+        }
+        let wr_pos = self.rb.load_pos(&header.write_pos, Ordering::Relaxed)?;
+        let re_pos = self.rb.load_pos(&header.read_pos, Ordering::Acquire)?;
+        if self.rb.slots_free_between(wr_pos, re_pos) < data.len() {
+            return Err(RbError::Full);
+        }
+        self.rb.copy_to_ring(wr_pos, data);
+        header.write_pos.store(
+            ((wr_pos + data.len()) % self.rb.size) as u64,
+            Ordering::Release,
+        );
+        Ok(())
+    }
+
+    fn write_blocking(&self, data: &[T]) -> Option<usize> {
+        match self.write_blocking_timeout(data, Duration::MAX) {
+            Ok(cnt) => cnt,
+            Err(RbError::Disconnected) => None,
+            Err(_) => panic!("Max duration should not time out"),
+        }
+    }
+
+    fn write_blocking_timeout(&self, data: &[T], timeout: Duration) -> Result<Option<usize>> {
+        if data.is_empty() {
+            return Ok(None);
+        }
+        let start = Instant::now();
+        loop {
+            match self.write(data) {
+                Ok(cnt) => return Ok(Some(cnt)),
+                Err(RbError::Full) => {}
+                Err(err) => return Err(err),
+            }
+            if start.elapsed() >= timeout {
+                return Err(RbError::TimedOut);
+            }
+            thread::yield_now();
+        }
+    }
+
+    fn write_blocking_deadline(&self, data: &[T], deadline: Instant) -> Result<Option<usize>> {
+        self.write_blocking_timeout(data, deadline.saturating_duration_since(Instant::now()))
+    }
+}
+
+impl<'a, T: Pod> ShmConsumer<'a, T> {
+    /// Closes the buffer, see `ShmSpscRb::close`.
+    pub fn close(&self) {
+        self.rb.close();
+    }
+
+    /// Returns the read position and the number of pending values.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Disconnected` a position is out of bounds
+    #[inline(always)]
+    fn pending(&self) -> Result<(usize, usize)> {
+        let header = self.rb.header();
+        let re_pos = self.rb.load_pos(&header.read_pos, Ordering::Relaxed)?;
+        let wr_pos = self.rb.load_pos(&header.write_pos, Ordering::Acquire)?;
+        Ok((
+            re_pos,
+            self.rb.size - 1 - self.rb.slots_free_between(wr_pos, re_pos),
+        ))
+    }
+
+    /// Returns the error for an empty buffer, which is `RbError::Disconnected` if the producer
+    /// was dropped or the buffer was closed and there is nothing left to read.
+    fn empty_error(&self) -> RbError {
+        let dropped = self.rb.header().producer_dropped.load(Ordering::Acquire);
+        match self.pending() {
+            Ok((_, 0)) if dropped => RbError::Disconnected,
+            Ok(_) => RbError::Empty,
+            Err(err) => err,
+        }
+    }
+
+    fn advance(&self, re_pos: usize, cnt: usize) {
+        self.rb
+            .header()
+            .read_pos
+            .store(((re_pos + cnt) % self.rb.size) as u64, Ordering::Release);
+    }
+}
+
+impl<'a, T: Pod> RbConsumer<T> for ShmConsumer<'a, T> {
+    fn skip_pending(&self) -> Result<usize> {
+        self.skip(self.rb.size)
+    }
+
+    fn skip(&self, cnt: usize) -> Result<usize> {
+        let (re_pos, available) = self.pending()?;
+        if available == 0 {
+            return Err(self.empty_error());
+        }
+        let count = cmp::min(cnt, available);
+        self.advance(re_pos, count);
+        Ok(count)
+    }
+
+    fn get(&self, data: &mut [T]) -> Result<usize> {
+        self.peek(0, data)
+    }
+
+    fn peek(&self, offset: usize, data: &mut [T]) -> Result<usize> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        let (re_pos, available) = self.pending()?;
+        let cnt = cmp::min(data.len(), available.saturating_sub(offset));
+        if cnt == 0 {
+            return Err(self.empty_error());
+        }
+        self.rb
+            .copy_from_ring((re_pos + offset) % self.rb.size, &mut data[..cnt]);
+        Ok(cnt)
+    }
+
+    fn read(&self, data: &mut [T]) -> Result<usize> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        let (re_pos, available) = self.pending()?;
+        let cnt = cmp::min(data.len(), available);
+        if cnt == 0 {
+            return Err(self.empty_error());
+        }
+        self.rb.copy_from_ring(re_pos, &mut data[..cnt]);
+        self.advance(re_pos, cnt);
+        Ok(cnt)
+    }
+
+    fn read_blocking(&self, data: &mut [T]) -> Option<usize> {
+        match self.read_blocking_timeout(data, Duration::MAX) {
+            Ok(cnt) => cnt,
+            Err(RbError::Disconnected) => None,
+            Err(_) => panic!("Max duration shouldn't time out"),
+        }
+    }
+
+    fn read_blocking_timeout(&self, data: &mut [T], timeout: Duration) -> Result<Option<usize>> {
+        if data.is_empty() {
+            return Ok(None);
+        }
+        let start = Instant::now();
+        loop {
+            match self.read(data) {
+                Ok(cnt) => return Ok(Some(cnt)),
+                Err(RbError::Empty) => {}
+                Err(err) => return Err(err),
+            }
+            if start.elapsed() >= timeout {
+                return Err(RbError::TimedOut);
+            }
+            thread::yield_now();
+        }
+    }
+
+    fn read_blocking_deadline(&self, data: &mut [T], deadline: Instant) -> Result<Option<usize>> {
+        self.read_blocking_timeout(data, deadline.saturating_duration_since(Instant::now()))
+    }
+
+    fn read_at_least(&self, data: &mut [T], min: usize) -> Option<usize> {
+        if data.is_empty() {
+            return None;
+        }
+        let min = cmp::min(cmp::max(min, 1), cmp::min(data.len(), self.rb.size - 1));
+        while self.pending().is_ok_and(|(_, count)| count < min)
+            && !self.rb.header().producer_dropped.load(Ordering::Acquire)
+        {
+            thread::yield_now();
+        }
+        self.read(data).ok()
+    }
+}
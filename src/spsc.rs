@@ -0,0 +1,3159 @@
+#[cfg(feature = "allocator_api")]
+use alloc::alloc::Allocator;
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::array;
+use core::cmp;
+use core::marker::PhantomData;
+use core::mem;
+use core::ops::{Deref, DerefMut};
+use core::slice;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+#[cfg(feature = "std")]
+use core::time::Duration;
+#[cfg(all(feature = "readiness", unix))]
+use std::sync::OnceLock;
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+use aligned::AlignedStorage;
+#[cfg(feature = "std")]
+use builder::SpscRbBuilder;
+use copy::{copy_from_ring, copy_to_ring};
+#[cfg(feature = "std")]
+use hooks::{Hooks, RbHooks, Side};
+#[cfg(feature = "std")]
+use latency::{Latency, LatencyCollector};
+use overflow::OverflowPolicy;
+#[cfg(all(feature = "readiness", unix))]
+use readiness::Readiness;
+#[cfg(feature = "std")]
+use stats::{Stats, StatsCollector};
+#[cfg(feature = "std")]
+use sync::Occupancy;
+#[cfg(not(feature = "std"))]
+use sync::Signal;
+use sync::{Mutex, MutexGuard};
+#[cfg(feature = "std")]
+use wait::{Signal, WaitStrategy};
+#[cfg(feature = "std")]
+use watchdog::XrunWatchdog;
+use watermark::{Watermark, Watermarks};
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, Zeroizing};
+use {CapacityError, RbConsumer, RbError, RbInspector, RbProducer, Result};
+
+/// Backing storage of a ring buffer, a `Vec<T>` for `SpscRb`, an array for `ConstSpscRb`
+/// a boxed or static slice for buffers created by `SpscRb::from_storage`,
+/// `SpscRb::from_raw_parts` or `SpscRb::new_in`, `AlignedStorage` for `SpscRb::new_aligned`
+/// and a `Zeroizing<Vec<T>>` for `SpscRb::new_zeroizing`.
+pub trait Storage<T> {
+    /// Returns the slots that were written so far.
+    fn slots(&self) -> &[T];
+    /// Stores `data` starting at slot `wr_pos`, wrapping around after `size` slots.
+    fn store(&mut self, size: usize, wr_pos: usize, data: &[T]);
+    /// Returns all `size` slots, slots that were never written are set to the default value.
+    fn slots_mut(&mut self, size: usize) -> &mut [T]
+    where
+        T: Default;
+    /// Drops the stored elements.
+    fn reset(&mut self);
+    /// Moves the `count` values starting at slot `re_pos` to the front of `new_size` new
+    /// slots, `size` is the current number of slots.
+    /// Returns false if the storage has a fixed size, which is the default.
+    fn reallocate(&mut self, size: usize, re_pos: usize, count: usize, new_size: usize) -> bool {
+        let _ = (size, re_pos, count, new_size);
+        false
+    }
+    /// Scrubs the `count` slots starting at slot `pos`, whose values were discarded.
+    /// Does nothing by default, the values are dropped once their slots are overwritten.
+    fn scrub(&mut self, size: usize, pos: usize, count: usize) {
+        let _ = (size, pos, count);
+    }
+    /// Retracts the `count` values that were stored last, starting at slot `wr_pos`, which
+    /// becomes the write position again. Does nothing by default, the values are dropped
+    /// once their slots are overwritten.
+    fn retract(&mut self, size: usize, wr_pos: usize, count: usize) {
+        let _ = (size, wr_pos, count);
+    }
+}
+
+// The slots of a `Vec` are initialized lazily. Data is always written in order, thus
+// the vector grows by appending until it spans the whole buffer and the write position
+// equals its length until then.
+impl<T: Clone> Storage<T> for Vec<T> {
+    fn slots(&self) -> &[T] {
+        self
+    }
+
+    fn store(&mut self, size: usize, wr_pos: usize, data: &[T]) {
+        if self.len() < size {
+            debug_assert_eq!(self.len(), wr_pos);
+            let cnt = cmp::min(data.len(), size - wr_pos);
+            self.extend_from_slice(&data[..cnt]);
+            copy_to_ring(self, 0, &data[cnt..]);
+        } else {
+            copy_to_ring(self, wr_pos, data);
+        }
+    }
+
+    fn slots_mut(&mut self, size: usize) -> &mut [T]
+    where
+        T: Default,
+    {
+        if self.len() < size {
+            self.resize_with(size, T::default);
+        }
+        self
+    }
+
+    fn reset(&mut self) {
+        self.clear();
+    }
+
+    fn reallocate(&mut self, size: usize, re_pos: usize, count: usize, new_size: usize) -> bool {
+        *self = pending_to_vec(self, size, re_pos, count, new_size);
+        true
+    }
+
+    fn retract(&mut self, size: usize, wr_pos: usize, _count: usize) {
+        // The values didn't wrap around yet, thus they are the last ones of the `Vec`.
+        if self.len() < size {
+            self.truncate(wr_pos);
+        }
+    }
+}
+
+/// Copies the `count` values starting at slot `re_pos` into a `Vec` with `new_size` slots.
+/// The values fill the new slots up to the write position, like the ones of any lazily
+/// initialized `Vec`.
+fn pending_to_vec<T: Clone>(
+    slots: &[T],
+    size: usize,
+    re_pos: usize,
+    count: usize,
+    new_size: usize,
+) -> Vec<T> {
+    let mut data = Vec::with_capacity(new_size);
+    let end = re_pos + count;
+    if end <= size {
+        data.extend_from_slice(&slots[re_pos..end]);
+    } else {
+        data.extend_from_slice(&slots[re_pos..]);
+        data.extend_from_slice(&slots[..end - size]);
+    }
+    data
+}
+
+// Scrubs every value before its slot is reused, when the buffer is cleared or reallocated
+// and when it's dropped. The `Vec` is allocated with all slots upfront, thus its lazy
+// growth never leaves a copy of the slots behind.
+#[cfg(feature = "zeroize")]
+impl<T: Clone + Zeroize> Storage<T> for Zeroizing<Vec<T>> {
+    fn slots(&self) -> &[T] {
+        self
+    }
+
+    fn store(&mut self, size: usize, wr_pos: usize, data: &[T]) {
+        self.scrub(size, wr_pos, data.len());
+        Storage::store(&mut **self, size, wr_pos, data);
+    }
+
+    fn slots_mut(&mut self, size: usize) -> &mut [T]
+    where
+        T: Default,
+    {
+        Storage::slots_mut(&mut **self, size)
+    }
+
+    fn reset(&mut self) {
+        self.zeroize();
+    }
+
+    fn reallocate(&mut self, size: usize, re_pos: usize, count: usize, new_size: usize) -> bool {
+        let data = pending_to_vec(self, size, re_pos, count, new_size);
+        self.zeroize();
+        **self = data;
+        true
+    }
+
+    fn retract(&mut self, size: usize, wr_pos: usize, count: usize) {
+        self.scrub(size, wr_pos, count);
+        Storage::retract(&mut **self, size, wr_pos, count);
+    }
+
+    fn scrub(&mut self, size: usize, pos: usize, count: usize) {
+        let len = self.len();
+        for pos in (pos..pos + count).map(|pos| pos % size) {
+            // Slots beyond the length were never written.
+            if pos < len {
+                self[pos].zeroize();
+            }
+        }
+    }
+}
+
+impl<T: Clone + Default, const N: usize> Storage<T> for [T; N] {
+    fn slots(&self) -> &[T] {
+        self
+    }
+
+    fn store(&mut self, _size: usize, wr_pos: usize, data: &[T]) {
+        copy_to_ring(self, wr_pos, data);
+    }
+
+    fn slots_mut(&mut self, _size: usize) -> &mut [T] {
+        self
+    }
+
+    fn reset(&mut self) {
+        self.iter_mut().for_each(|x| *x = T::default());
+    }
+}
+
+// Like arrays, slices that are owned by the caller have a fixed size and their slots were
+// initialized by their owner. The allocator of a box is only generic with the
+// `allocator_api` feature.
+macro_rules! slice_storage {
+    ($slice:ty $(, $param:ident: $bound:path)?) => {
+        impl<T: Clone + Default $(, $param: $bound)?> Storage<T> for $slice {
+            fn slots(&self) -> &[T] {
+                self
+            }
+
+            fn store(&mut self, _size: usize, wr_pos: usize, data: &[T]) {
+                copy_to_ring(self, wr_pos, data);
+            }
+
+            fn slots_mut(&mut self, _size: usize) -> &mut [T] {
+                self
+            }
+
+            fn reset(&mut self) {
+                self.iter_mut().for_each(|x| *x = T::default());
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "allocator_api"))]
+slice_storage!(Box<[T]>);
+#[cfg(feature = "allocator_api")]
+slice_storage!(Box<[T], A>, A: Allocator);
+slice_storage!(&'static mut [T]);
+slice_storage!(AlignedStorage<T>);
+
+/// Managment interface for the ring buffer.
+pub trait RB<T: Clone, S = Vec<T>> {
+    /// Drops all elements of the buffer, the slots of a fixed-size storage are set to the
+    /// default value. This takes time proportional to the capacity, `reset` only discards
+    /// the pending values.
+    /// The buffer is empty after this call.
+    fn clear(&self);
+    /// Discards the pending values in constant time by moving the read position to the
+    /// write position. The values stay in their slots until they are overwritten.
+    /// The buffer is empty after this call.
+    fn reset(&self);
+    /// Creates the *producer* view inside the buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a producer of the buffer is alive, there is only a single one, see
+    /// `SpscRb::try_producer`. Use an `MpscRb` for several producers.
+    fn producer(&self) -> Producer<T, S>;
+    /// Creates the *consumer* view inside the buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a consumer of the buffer is alive, there is only a single one, see
+    /// `SpscRb::try_consumer`. Use an `MpmcRb` for several consumers.
+    fn consumer(&self) -> Consumer<T, S>;
+}
+
+// Memory model of the positions:
+//
+// Each position is only ever advanced by its owner, `write_pos` by the producer and
+// `read_pos` by the consumer. The owner publishes a new position with `Release` after it
+// has finished accessing the slots in between, and every load of the peer's position uses
+// `Acquire`, which makes the slots the peer released visible.
+// Loads of the own position can be `Relaxed` since no other thread modifies it.
+// The positions therefore stay consistent without relying on the buffer's mutex.
+// The only exceptions are `Producer::write_overwriting` and `Producer::discard_oldest`,
+// which advance `read_pos`, `Producer::rollback`, which moves `write_pos` back, and
+// `Consumer::unread`, which moves `read_pos` back. They hold the lock, and both sides hold
+// it while they access their positions as well.
+pub(crate) struct Inspector {
+    read_pos: Arc<AtomicUsize>,
+    write_pos: Arc<AtomicUsize>,
+    // Number of slots, only changed by `SpscRb::resize` while holding the lock.
+    size: AtomicUsize,
+    pub(crate) producers: Handles,
+    pub(crate) consumers: Handles,
+    // Set by `close`, disconnects both sides for good.
+    closed: AtomicBool,
+    // Set while an `SpscRb` can create more handles, see `Inspector::producers_disconnected`.
+    owned: AtomicBool,
+    // Number of values lost by `Producer::write_overwriting`.
+    overwritten: AtomicUsize,
+    // Number of read values in the slots right before the read position that weren't
+    // overwritten yet, see `Consumer::unread`.
+    read_history: AtomicUsize,
+    // Number of values discarded by `Producer::write_dropping`.
+    dropped: AtomicUsize,
+    // Number of values that writes could not store, see `SpscRb::overruns`.
+    overruns: AtomicUsize,
+    // Number of reads that found the buffer empty, see `SpscRb::underruns`.
+    underruns: AtomicUsize,
+    #[cfg(feature = "std")]
+    stats: StatsCollector,
+    #[cfg(feature = "std")]
+    latency: LatencyCollector,
+    #[cfg(feature = "std")]
+    hooks: Hooks,
+    watermarks: Watermarks,
+    // Futex word that the blocked handles wait on, see `Inspector::publish`.
+    #[cfg(feature = "std")]
+    occupancy: Occupancy,
+}
+
+/// Keeps track of the live handles of one side of the buffer.
+///
+/// The handles are only attached and detached while holding the buffer's lock, this way
+/// a blocked peer can't miss the wake-up that follows the drop of the last handle.
+#[derive(Default)]
+pub(crate) struct Handles {
+    live: AtomicUsize,
+    // Set when the last handle is dropped, a new handle connects the side again.
+    dropped: AtomicBool,
+}
+
+impl Handles {
+    fn attach(&self) {
+        self.live.fetch_add(1, Ordering::Relaxed);
+        self.dropped.store(false, Ordering::Relaxed);
+    }
+
+    /// Returns true if at least one handle is alive.
+    fn is_attached(&self) -> bool {
+        self.live.load(Ordering::Relaxed) > 0
+    }
+
+    /// Returns true if the last handle was detached.
+    fn detach(&self) -> bool {
+        let last = self.live.fetch_sub(1, Ordering::Relaxed) == 1;
+        if last {
+            self.dropped.store(true, Ordering::Relaxed);
+        }
+        last
+    }
+
+    /// Returns true if all handles were dropped, a side without any handles so far is
+    /// not disconnected since they might still be created.
+    fn is_disconnected(&self) -> bool {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// A *thread-safe* Single-Producer-Single-Consumer RingBuffer
+///
+/// - blocking and non-blocking IO, without `std` only the non-blocking calls are available
+///   and the handles synchronize through a spin lock, use `StaticSpscRb` in interrupt handlers
+/// - mutually exclusive access for producer and consumer
+/// - no use of `unsafe`, except for `from_raw_parts` and `AlignedStorage`
+/// - never under- or overflows
+/// - elements only need to implement `Clone`, they are cloned in and out of the buffer
+///   and a read element is dropped once its slot is overwritten or the buffer is cleared
+/// - slots are initialized when they are written for the first time
+/// - a side disconnects once all of its handles and the `SpscRb` were dropped, or by `close`,
+///   the non-blocking writes only fail with `RbError::Disconnected` once it was closed
+///
+/// ```
+/// # #[cfg(feature = "std")] {
+/// use std::thread;
+/// use rb::*;
+///
+/// let rb = SpscRb::new(1024);
+/// let (prod, cons) = (rb.producer(), rb.consumer());
+/// thread::spawn(move || {
+///     let gen = || {(-16..16+1).cycle().map(|x| x as f32/16.0)};
+///     loop {
+///         prod.write_from_iter(gen().take(32)).unwrap();
+///     }
+/// });
+/// let mut data = Vec::with_capacity(1024);
+/// let mut buf = [0.0f32; 256];
+/// while data.len() < 1024 {
+///     let cnt = cons.read_blocking(&mut buf).unwrap();
+///     data.extend_from_slice(&buf[..cnt]);
+/// }
+/// # }
+/// ```
+pub struct SpscRb<T, S = Vec<T>> {
+    buf: Arc<Mutex<S>>,
+    inspector: Arc<Inspector>,
+    slots_free: Arc<Signal>,
+    data_available: Arc<Signal>,
+    #[cfg(feature = "std")]
+    wait_strategy: WaitStrategy,
+    overflow_policy: OverflowPolicy,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Clone> SpscRb<T> {
+    /// Creates a buffer for `size` elements.
+    ///
+    /// The memory is allocated upfront, but the slots are only initialized once they are written.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero or too large, see `try_new`.
+    pub fn new(size: usize) -> Self {
+        match SpscRb::try_new(size) {
+            Ok(rb) => rb,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Works analog to `new` but fails if the buffer can't be created with `size`.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// assert_eq!(SpscRb::<u8>::try_new(0).err(), Some(CapacityError::Zero));
+    /// assert_eq!(SpscRb::<u64>::try_new(usize::MAX / 4).err(), Some(CapacityError::TooLarge));
+    /// assert!(SpscRb::<u8>::try_new(4).is_ok());
+    /// ```
+    ///
+    /// Possible errors:
+    ///
+    /// - `CapacityError::Zero` the buffer could never hold a value
+    /// - `CapacityError::TooLarge` the slots would take more than `isize::MAX` bytes
+    pub fn try_new(size: usize) -> ::core::result::Result<Self, CapacityError> {
+        check_capacity::<T>(size)?;
+        // the additional element is used to distinct between empty and full state
+        Ok(SpscRb::with_storage(Vec::with_capacity(size + 1), size + 1))
+    }
+
+    /// Creates a buffer for `size` elements whose slots are all initialized with `value`,
+    /// e.g. silence at a non-zero DC offset or a sentinel. It shows through in the slots of
+    /// grants that were never written, instead of the default value.
+    /// `clear` and `resize` drop the values, the slots are initialized lazily afterwards.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new_with(4, 0.5f32);
+    /// let prod = rb.producer();
+    /// assert_eq!(*prod.grant(2).unwrap(), [0.5, 0.5]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero or too large, see `try_new`.
+    pub fn new_with(size: usize, value: T) -> Self {
+        if let Err(err) = check_capacity::<T>(size) {
+            panic!("{}", err);
+        }
+        let mut buf = Vec::with_capacity(size + 1);
+        buf.resize(size + 1, value);
+        SpscRb::with_storage(buf, size + 1)
+    }
+
+    /// Creates a buffer for `capacity` elements whose pending values are `data`,
+    /// e.g. to prime a playback buffer with preroll data before the producer starts.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::from_vec(vec![0.0f32; 256], 1024);
+    /// assert_eq!(rb.count(), 256);
+    /// assert_eq!(rb.slots_free(), 768);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is longer than `capacity` or `capacity` is zero or too large,
+    /// see `try_new`.
+    pub fn from_vec(mut data: Vec<T>, capacity: usize) -> Self {
+        if let Err(err) = check_capacity::<T>(capacity) {
+            panic!("{}", err);
+        }
+        assert!(
+            data.len() <= capacity,
+            "the data exceeds the capacity of the buffer"
+        );
+        let len = data.len();
+        data.reserve_exact(capacity + 1 - len);
+        let rb = SpscRb::with_storage(data, capacity + 1);
+        rb.inspector.write_pos.store(len, Ordering::Relaxed);
+        rb
+    }
+
+    /// Changes the capacity of the buffer while keeping the pending values, e.g. to adapt
+    /// the buffering to the observed latency. The handles stay connected and use the new
+    /// capacity for their next call, blocked producers wake up if the buffer grew.
+    ///
+    /// The pending values are moved to a newly allocated ring. Inspecting the buffer
+    /// concurrently might report inaccurate numbers while the resize is in progress.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(2);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write(&[1, 2]).unwrap();
+    /// rb.resize(4).unwrap();
+    /// prod.write(&[3, 4]).unwrap();
+    /// assert_eq!(cons.read_vec(4), vec![1, 2, 3, 4]);
+    /// ```
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Full` more values are pending than fit into the new capacity
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero or too large, see `try_new`.
+    pub fn resize(&self, capacity: usize) -> Result<()> {
+        if let Err(err) = check_capacity::<T>(capacity) {
+            panic!("{}", err);
+        }
+        let mut buf = self.buf.lock();
+        let count = self.inspector.count();
+        if count > capacity {
+            return Err(RbError::Full);
+        }
+        reallocate(&mut *buf, &self.inspector, &self.slots_free, capacity);
+        Ok(())
+    }
+
+    /// Returns a fresh producer and consumer of the buffer if no handle of it is alive,
+    /// after dropping the pending values and resetting its state, see `RbPool`.
+    /// The allocation and the configuration of the buffer are kept.
+    #[cfg(feature = "std")]
+    pub(crate) fn recycle(&self) -> Option<(Producer<T>, Consumer<T>)> {
+        let mut buf = self.buf.lock();
+        if !self.is_idle() {
+            return None;
+        }
+        buf.reset();
+        self.inspector.read_pos.store(0, Ordering::Release);
+        self.inspector.write_pos.store(0, Ordering::Release);
+        self.inspector.read_history.store(0, Ordering::Relaxed);
+        #[cfg(feature = "std")]
+        self.inspector.latency.discard_all();
+        self.inspector.closed.store(false, Ordering::Relaxed);
+        self.inspector.overwritten.store(0, Ordering::Relaxed);
+        self.inspector.dropped.store(0, Ordering::Relaxed);
+        self.reset_xruns();
+        self.inspector.stats.reset(0);
+        self.inspector.watermarks.update(0);
+        Some((self.attach_producer(), self.attach_consumer()))
+    }
+
+    /// Returns a builder to configure a buffer, which collects the options of the `with_*`
+    /// methods and a few more.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::<f32>::builder()
+    ///     .capacity(1024)
+    ///     .overwrite_oldest()
+    ///     .with_stats()
+    ///     .build();
+    /// assert_eq!(rb.capacity(), 1024);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn builder() -> SpscRbBuilder<T> {
+        SpscRbBuilder::default()
+    }
+}
+
+impl<T, S> SpscRb<T, S> {
+    /// Creates a buffer that uses the `size` slots of `buf`.
+    pub(crate) fn with_storage(buf: S, size: usize) -> Self {
+        SpscRb {
+            buf: Arc::new(Mutex::new(buf)),
+            slots_free: Arc::new(Signal::new()),
+            data_available: Arc::new(Signal::new()),
+            inspector: Arc::new(Inspector {
+                read_pos: Arc::new(AtomicUsize::new(0)),
+                write_pos: Arc::new(AtomicUsize::new(0)),
+                size: AtomicUsize::new(size),
+                producers: Handles::default(),
+                consumers: Handles::default(),
+                closed: AtomicBool::new(false),
+                owned: AtomicBool::new(true),
+                overwritten: AtomicUsize::new(0),
+                read_history: AtomicUsize::new(0),
+                dropped: AtomicUsize::new(0),
+                overruns: AtomicUsize::new(0),
+                underruns: AtomicUsize::new(0),
+                #[cfg(feature = "std")]
+                stats: StatsCollector::default(),
+                #[cfg(feature = "std")]
+                latency: LatencyCollector::default(),
+                #[cfg(feature = "std")]
+                hooks: Hooks::default(),
+                watermarks: Watermarks::default(),
+                #[cfg(feature = "std")]
+                occupancy: Occupancy::new(),
+            }),
+            #[cfg(feature = "std")]
+            wait_strategy: WaitStrategy::Block,
+            overflow_policy: OverflowPolicy::Partial,
+            marker: PhantomData,
+        }
+    }
+
+    /// Installs boxed hooks, see `with_hooks`.
+    #[cfg(feature = "std")]
+    pub(crate) fn with_boxed_hooks(self, hooks: Box<dyn RbHooks>) -> Self {
+        self.inspector.hooks.install(hooks);
+        self
+    }
+}
+
+/// Moves the pending values into the slots for `capacity` values, at most `capacity` values
+/// must be pending. Returns false if the storage has a fixed size.
+/// Must be called while holding the buffer's lock.
+fn reallocate<T, S: Storage<T>>(
+    buf: &mut S,
+    inspector: &Inspector,
+    slots_free: &Signal,
+    capacity: usize,
+) -> bool {
+    let count = inspector.count();
+    let re_pos = inspector.read_pos.load(Ordering::Relaxed);
+    if !buf.reallocate(inspector.size(), re_pos, count, capacity + 1) {
+        return false;
+    }
+    inspector.size.store(capacity + 1, Ordering::Relaxed);
+    inspector.read_pos.store(0, Ordering::Release);
+    inspector.write_pos.store(count, Ordering::Release);
+    inspector.read_history.store(0, Ordering::Relaxed);
+    inspector.publish_event();
+    slots_free.notify_all();
+    true
+}
+
+/// Fails if a buffer with `capacity` values of `T` could never hold a value or its slots,
+/// which include the one that distinguishes the empty and full state, can't be allocated.
+fn check_capacity<T>(capacity: usize) -> ::core::result::Result<(), CapacityError> {
+    if capacity == 0 {
+        return Err(CapacityError::Zero);
+    }
+    let bytes = capacity
+        .checked_add(1)
+        .and_then(|size| size.checked_mul(mem::size_of::<T>()));
+    match bytes {
+        Some(bytes) if bytes <= isize::MAX as usize => Ok(()),
+        _ => Err(CapacityError::TooLarge),
+    }
+}
+
+/// A ring buffer whose size is known at compile time.
+///
+/// The `N` slots are stored inline in an array instead of a `Vec`,
+/// like for `SpscRb` one slot is used to distinguish between the empty and full state,
+/// thus the capacity is `N - 1`. Buffers with less than two slots, which could never hold
+/// a value, fail to compile.
+///
+/// ```
+/// use rb::*;
+///
+/// let rb = ConstSpscRb::<f32, 5>::default();
+/// let (prod, cons) = (rb.producer(), rb.consumer());
+/// assert_eq!(rb.capacity(), 4);
+/// assert_eq!(prod.write(&[1.0, 2.0, 3.0, 4.0, 5.0]).unwrap(), 4);
+/// let mut data = [0.0; 4];
+/// assert_eq!(cons.read(&mut data).unwrap(), 4);
+/// ```
+///
+/// ```compile_fail
+/// let rb = rb::ConstSpscRb::<f32, 1>::default();
+/// ```
+pub type ConstSpscRb<T, const N: usize> = SpscRb<T, [T; N]>;
+
+impl<T: Clone + Default, const N: usize> Default for SpscRb<T, [T; N]> {
+    fn default() -> Self {
+        const { assert!(N > 1, "the storage must provide at least two slots") };
+        SpscRb::with_storage(array::from_fn(|_| T::default()), N)
+    }
+}
+
+impl<T: Clone + Default, S: Storage<T> + AsRef<[T]>> SpscRb<T, S> {
+    /// Creates a buffer that uses the slots of `buf`, memory that is owned by the caller,
+    /// e.g. a boxed slice or a `&'static mut [T]`. Like for `ConstSpscRb`, one slot is used
+    /// to distinguish between the empty and full state, thus the capacity is `buf.len() - 1`.
+    /// See `new_in` for buffers in the memory of a custom allocator.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::from_storage(vec![0u8; 5].into_boxed_slice());
+    /// assert_eq!(rb.capacity(), 4);
+    /// assert_eq!(rb.producer().write(&[1, 2, 3, 4, 5]).unwrap(), 4);
+    /// assert_eq!(rb.consumer().read_vec(8), vec![1, 2, 3, 4]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buf` has less than two slots, since the buffer could never hold a value.
+    pub fn from_storage(buf: S) -> Self {
+        let size = buf.as_ref().len();
+        assert!(size > 1, "the storage must provide at least two slots");
+        SpscRb::with_storage(buf, size)
+    }
+}
+
+impl<T: Clone + Default> SpscRb<T, &'static mut [T]> {
+    /// Creates a buffer over the `len` values at `ptr`, e.g. memory at a fixed address on
+    /// an embedded target or memory that is shared with foreign code, see `from_storage`.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let slots = Box::leak(Box::new([0.0f32; 5]));
+    /// let rb = unsafe { SpscRb::from_raw_parts(slots.as_mut_ptr(), slots.len()) };
+    /// assert_eq!(rb.capacity(), 4);
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// The same as for `slice::from_raw_parts_mut`, the `len` values must be initialized
+    /// and, for the rest of the program, neither freed nor accessed other than through the
+    /// buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is less than two, see `from_storage`.
+    pub unsafe fn from_raw_parts(ptr: *mut T, len: usize) -> Self {
+        SpscRb::from_storage(slice::from_raw_parts_mut(ptr, len))
+    }
+}
+
+impl<T: Clone + Default> SpscRb<T, AlignedStorage<T>> {
+    /// Creates a buffer for `size` elements whose first slot starts at a multiple of
+    /// `align`, see `AlignedStorage`.
+    ///
+    /// Unlike `new`, all slots are initialized upfront with the default value.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// // 2 MiB, the size of a huge page on x86_64
+    /// let rb = SpscRb::<u8, _>::new_aligned(1 << 24, 2 << 20);
+    /// assert_eq!(rb.capacity(), 1 << 24);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero or too large, see `try_new`, or if `align` is not a
+    /// power of two.
+    pub fn new_aligned(size: usize, align: usize) -> Self {
+        if let Err(err) = check_capacity::<T>(size) {
+            panic!("{}", err);
+        }
+        SpscRb::with_storage(AlignedStorage::new(size + 1, align), size + 1)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<T: Clone + Zeroize> SpscRb<T, Zeroizing<Vec<T>>> {
+    /// Creates a buffer for `size` elements that scrubs the memory of its values, e.g. for
+    /// keys or the audio of confidential calls. Values are zeroized with `Zeroize`, which
+    /// the compiler can't optimize away, before their slot is overwritten by a later
+    /// write, including the values lost by `Producer::write_overwriting`, and when the
+    /// buffer is cleared, resized or dropped.
+    ///
+    /// Read values stay in their slots until they are overwritten, call `clear` to scrub
+    /// them earlier. `reset` scrubs the pending values that it discards.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new_zeroizing(4);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write(&[1u8, 2, 3]).unwrap();
+    /// rb.clear();
+    /// assert!(cons.is_empty());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero or too large, see `try_new`.
+    pub fn new_zeroizing(size: usize) -> Self {
+        if let Err(err) = check_capacity::<T>(size) {
+            panic!("{}", err);
+        }
+        SpscRb::with_storage(Zeroizing::new(Vec::with_capacity(size + 1)), size + 1)
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<T: Clone + Default, A: Allocator> SpscRb<T, Box<[T], A>> {
+    /// Creates a buffer for `size` elements in the memory of `alloc`, e.g. an arena or
+    /// DMA-capable memory. Needs the `allocator_api` feature and thus a nightly compiler,
+    /// `from_storage` accepts memory that was allocated by the caller on stable.
+    ///
+    /// Unlike `new`, all slots are initialized upfront with the default value.
+    ///
+    /// ```
+    /// #![feature(allocator_api)]
+    /// use std::alloc::Global;
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new_in(4, Global);
+    /// assert_eq!(rb.producer().write(&[1, 2, 3, 4, 5]).unwrap(), 4);
+    /// assert_eq!(rb.consumer().read_vec(8), vec![1, 2, 3, 4]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero or too large, see `try_new`.
+    pub fn new_in(size: usize, alloc: A) -> Self {
+        if let Err(err) = check_capacity::<T>(size) {
+            panic!("{}", err);
+        }
+        let mut buf = Vec::with_capacity_in(size + 1, alloc);
+        buf.resize_with(size + 1, T::default);
+        SpscRb::with_storage(buf.into_boxed_slice(), size + 1)
+    }
+}
+
+impl<T: Clone, S: Storage<T>> RB<T, S> for SpscRb<T, S> {
+    fn clear(&self) {
+        let mut buf = self.buf.lock();
+        #[cfg(feature = "tracing")]
+        ::tracing::debug!(dropped = self.inspector.count(), "ring buffer cleared");
+        buf.reset();
+        self.inspector.read_pos.store(0, Ordering::Release);
+        self.inspector.write_pos.store(0, Ordering::Release);
+        self.inspector.read_history.store(0, Ordering::Relaxed);
+        #[cfg(feature = "std")]
+        self.inspector.latency.discard_all();
+        self.inspector.publish();
+        self.slots_free.notify_all();
+        self.inspector.watermarks.update(0);
+    }
+
+    fn reset(&self) {
+        let mut buf = self.buf.lock();
+        let count = self.inspector.count();
+        #[cfg(feature = "tracing")]
+        ::tracing::debug!(dropped = count, "ring buffer reset");
+        let re_pos = self.inspector.read_pos.load(Ordering::Relaxed);
+        buf.scrub(self.inspector.size(), re_pos, count);
+        let wr_pos = self.inspector.write_pos.load(Ordering::Relaxed);
+        self.inspector.read_pos.store(wr_pos, Ordering::Release);
+        self.inspector.read_history.store(0, Ordering::Relaxed);
+        #[cfg(feature = "std")]
+        self.inspector.latency.discard_all();
+        self.inspector.publish();
+        self.slots_free.notify_all();
+        self.inspector.watermarks.update(0);
+    }
+
+    fn producer(&self) -> Producer<T, S> {
+        match self.try_producer() {
+            Some(producer) => producer,
+            None => panic!("the buffer already has a producer"),
+        }
+    }
+
+    fn consumer(&self) -> Consumer<T, S> {
+        match self.try_consumer() {
+            Some(consumer) => consumer,
+            None => panic!("the buffer already has a consumer"),
+        }
+    }
+}
+
+impl<T, S> SpscRb<T, S> {
+    /// Works analog to `producer` but returns `None` instead of panicking while another
+    /// producer of the buffer is alive, e.g. if the buffer is handed to a library that
+    /// might have created its own producer.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::<u8>::new(4);
+    /// let producer = rb.try_producer().unwrap();
+    /// assert!(rb.try_producer().is_none());
+    /// drop(producer);
+    /// assert!(rb.try_producer().is_some());
+    /// ```
+    pub fn try_producer(&self) -> Option<Producer<T, S>> {
+        let _guard = self.buf.lock();
+        if self.inspector.producers.is_attached() {
+            return None;
+        }
+        Some(self.attach_producer())
+    }
+
+    /// Works analog to `consumer` but returns `None` instead of panicking while another
+    /// consumer of the buffer is alive, see `try_producer`.
+    pub fn try_consumer(&self) -> Option<Consumer<T, S>> {
+        let _guard = self.buf.lock();
+        if self.inspector.consumers.is_attached() {
+            return None;
+        }
+        Some(self.attach_consumer())
+    }
+
+    /// Creates another producer even if one is alive, for `MpscRb` and `MpmcRb`.
+    #[cfg(feature = "std")]
+    pub(crate) fn add_producer(&self) -> Producer<T, S> {
+        let _guard = self.buf.lock();
+        self.attach_producer()
+    }
+
+    /// Creates another consumer even if one is alive, for `MpscRb` and `MpmcRb`.
+    #[cfg(feature = "std")]
+    pub(crate) fn add_consumer(&self) -> Consumer<T, S> {
+        let _guard = self.buf.lock();
+        self.attach_consumer()
+    }
+
+    /// Gives up creating handles other than by `recycle`, the sides disconnect once their
+    /// handles were dropped although the buffer is still alive, e.g. in an `RbPool`.
+    pub(crate) fn disown(&self) {
+        let _guard = self.buf.lock();
+        self.inspector.owned.store(false, Ordering::Relaxed);
+        self.inspector.publish_event();
+        self.slots_free.notify_all();
+        self.data_available.notify_all();
+    }
+
+    /// Returns true if neither a producer nor a consumer of the buffer is alive.
+    #[cfg(feature = "std")]
+    pub(crate) fn is_idle(&self) -> bool {
+        !self.inspector.producers.is_attached() && !self.inspector.consumers.is_attached()
+    }
+
+    /// Consumes the buffer and returns its only producer and consumer, e.g. to move them
+    /// to their threads right away. More handles can't be created afterwards, not even
+    /// once these are dropped, unlike with `producer` and `consumer`.
+    ///
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// use std::thread;
+    /// use rb::*;
+    ///
+    /// let (prod, cons) = SpscRb::new(4).into_split();
+    /// let writer = thread::spawn(move || prod.write_blocking(&[1, 2, 3]));
+    /// assert_eq!(writer.join().unwrap(), Some(3));
+    /// assert_eq!(cons.read_vec(4), vec![1, 2, 3]);
+    /// # }
+    /// ```
+    pub fn into_split(self) -> (Producer<T, S>, Consumer<T, S>) {
+        let _guard = self.buf.lock();
+        (self.attach_producer(), self.attach_consumer())
+    }
+
+    /// Creates a producer, must be called while holding the buffer's lock.
+    fn attach_producer(&self) -> Producer<T, S> {
+        self.inspector.producers.attach();
+        Producer {
+            buf: self.buf.clone(),
+            inspector: self.inspector.clone(),
+            slots_free: self.slots_free.clone(),
+            data_available: self.data_available.clone(),
+            #[cfg(feature = "std")]
+            wait_strategy: self.wait_strategy,
+            overflow_policy: self.overflow_policy,
+            #[cfg(all(feature = "readiness", unix))]
+            readiness: OnceLock::new(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Creates a consumer, must be called while holding the buffer's lock.
+    fn attach_consumer(&self) -> Consumer<T, S> {
+        self.inspector.consumers.attach();
+        Consumer {
+            buf: self.buf.clone(),
+            inspector: self.inspector.clone(),
+            slots_free: self.slots_free.clone(),
+            data_available: self.data_available.clone(),
+            #[cfg(feature = "std")]
+            nonblocking: false,
+            #[cfg(feature = "std")]
+            io_buf: Vec::new(),
+            #[cfg(feature = "std")]
+            io_pos: 0,
+            #[cfg(feature = "std")]
+            wait_strategy: self.wait_strategy,
+            overwritten_seen: AtomicUsize::new(self.inspector.overwritten.load(Ordering::Relaxed)),
+            skipped: AtomicUsize::new(0),
+            #[cfg(all(feature = "readiness", unix))]
+            readiness: OnceLock::new(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Sets how the blocking calls of the handles wait, `WaitStrategy::Block` by default.
+    /// Only handles that are created afterwards use the new strategy.
+    #[cfg(feature = "std")]
+    pub fn with_wait_strategy(mut self, wait_strategy: WaitStrategy) -> Self {
+        self.wait_strategy = wait_strategy;
+        self
+    }
+
+    /// Returns the number of values that writes could not store because the buffer was full,
+    /// including the values that were discarded or overwritten by the lossy writes.
+    /// Blocking writes wait instead, thus they never overrun.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(2);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// assert_eq!(prod.write(&[1, 2, 3]).unwrap(), 2);
+    /// cons.read_vec(2);
+    /// assert!(cons.read(&mut [0]).is_err());
+    /// assert_eq!((rb.overruns(), rb.underruns()), (1, 1));
+    /// rb.reset_xruns();
+    /// assert_eq!((rb.overruns(), rb.underruns()), (0, 0));
+    /// ```
+    pub fn overruns(&self) -> usize {
+        self.inspector.overruns.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of non-blocking reads that found the buffer empty,
+    /// i.e. calls of `read`, `read_access` and `read_grant` that failed with `RbError::Empty`.
+    pub fn underruns(&self) -> usize {
+        self.inspector.underruns.load(Ordering::Relaxed)
+    }
+
+    /// Resets the overrun and underrun counters to zero.
+    pub fn reset_xruns(&self) {
+        self.inspector.overruns.store(0, Ordering::Relaxed);
+        self.inspector.underruns.store(0, Ordering::Relaxed);
+    }
+
+    /// Returns a watchdog that reports the overruns and underruns of the buffer from
+    /// another thread, see `XrunWatchdog`.
+    #[cfg(feature = "std")]
+    pub fn watchdog(&self) -> XrunWatchdog {
+        XrunWatchdog::new(self.inspector.clone())
+    }
+
+    /// Enables the statistics of the buffer, which are updated by all handles afterwards,
+    /// see `stats`. Without them, the handles skip the bookkeeping.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(8).with_stats();
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write(&[1, 2, 3]).unwrap();
+    /// cons.read_vec(2);
+    /// prod.write(&[4]).unwrap();
+    /// let stats = rb.stats();
+    /// assert_eq!((stats.written, stats.read, stats.max_count), (4, 2, 3));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn with_stats(self) -> Self {
+        self.inspector.stats.enable();
+        self
+    }
+
+    /// Installs `hooks` that are called on the writes, reads and blocking waits of all
+    /// handles, see `RbHooks`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if hooks were already installed.
+    #[cfg(feature = "std")]
+    pub fn with_hooks<H: RbHooks + 'static>(self, hooks: H) -> Self {
+        self.with_boxed_hooks(Box::new(hooks))
+    }
+
+    /// Returns a snapshot of the statistics, all of them are zero unless they were enabled
+    /// by `with_stats`.
+    #[cfg(feature = "std")]
+    pub fn stats(&self) -> Stats {
+        self.inspector.stats.snapshot()
+    }
+
+    /// Samples the number of pending values after every write and read into a histogram
+    /// of `bins` equally sized ranges of `0..=capacity`, e.g. to find out how close to
+    /// overflowing the buffer runs. This works independently of `with_stats`.
+    /// The ranges keep referring to the capacity at this point if the buffer is resized.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// // one bin per count
+    /// let rb = SpscRb::new(4).with_histogram(5);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write(&[1, 2, 3]).unwrap();
+    /// cons.skip(2).unwrap();
+    /// prod.write(&[4]).unwrap();
+    /// assert_eq!(rb.histogram(), [0, 1, 1, 1, 0]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bins` is zero or the histogram was already enabled.
+    #[cfg(feature = "std")]
+    pub fn with_histogram(self, bins: usize) -> Self {
+        self.inspector
+            .stats
+            .enable_histogram(bins, self.inspector.capacity());
+        self
+    }
+
+    /// Measures how long values spend in the buffer, see `latency`. Every write records its
+    /// time, thus this costs a clock read and a push into a queue per write.
+    /// Values that are pending already are assumed to be written now.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(8).with_latency();
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write(&[1, 2, 3]).unwrap();
+    /// cons.skip(2).unwrap();
+    /// cons.skip(1).unwrap();
+    /// let latency = rb.latency();
+    /// assert_eq!(latency.samples, 2);
+    /// assert!(latency.min <= latency.avg && latency.avg <= latency.max);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn with_latency(self) -> Self {
+        {
+            let _guard = self.buf.lock();
+            self.inspector.latency.enable(self.inspector.count());
+        }
+        self
+    }
+
+    /// Returns the minimum, average and maximum time that the read values spent in the
+    /// buffer, all of them are zero unless the measurement was enabled by `with_latency`.
+    #[cfg(feature = "std")]
+    pub fn latency(&self) -> Latency {
+        self.inspector.latency.snapshot()
+    }
+
+    /// Resets the latency measurement, the times of the pending values are kept.
+    #[cfg(feature = "std")]
+    pub fn reset_latency(&self) {
+        self.inspector.latency.reset();
+    }
+
+    /// Returns the number of samples per bin of the histogram, which is empty unless it was
+    /// enabled by `with_histogram`.
+    #[cfg(feature = "std")]
+    pub fn histogram(&self) -> Vec<usize> {
+        self.inspector.stats.histogram()
+    }
+
+    /// Resets the statistics and the histogram, the maximum count to the current number
+    /// of pending values.
+    #[cfg(feature = "std")]
+    pub fn reset_stats(&self) {
+        let _guard = self.buf.lock();
+        self.inspector.stats.reset(self.inspector.count());
+    }
+
+    /// Sets how `write` handles a full buffer, `OverflowPolicy::Partial` by default.
+    /// Only producers that are created afterwards use the new policy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `overflow_policy` is `OverflowPolicy::Block` without the `std` feature,
+    /// the handles can't block then.
+    pub fn with_overflow_policy(mut self, overflow_policy: OverflowPolicy) -> Self {
+        #[cfg(not(feature = "std"))]
+        assert!(
+            overflow_policy != OverflowPolicy::Block,
+            "OverflowPolicy::Block requires the std feature"
+        );
+        self.overflow_policy = overflow_policy;
+        self
+    }
+
+    /// Closes the buffer, e.g. to tear down an audio pipeline.
+    ///
+    /// Threads blocked in one of its handles wake up and all further writes fail with
+    /// `RbError::Disconnected`, as do reads once the pending values were read.
+    /// A closed buffer can't be reopened.
+    ///
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// use std::thread;
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::<f32>::new(16);
+    /// let cons = rb.consumer();
+    /// let _prod = rb.producer();
+    /// let reader = thread::spawn(move || cons.read_blocking(&mut [0.0; 16]));
+    /// rb.close();
+    /// assert_eq!(reader.join().unwrap(), None);
+    /// # }
+    /// ```
+    pub fn close(&self) {
+        close(
+            &self.buf,
+            &self.inspector,
+            &self.slots_free,
+            &self.data_available,
+        );
+    }
+
+    /// Calls `callback` whenever the number of pending values crosses one of the watermarks,
+    /// e.g. to notify a UI thread once the buffer is 75% full.
+    ///
+    /// `Watermark::High` is reported once the count rises to `high` or above, after that
+    /// `Watermark::Low` once it falls to `low` or below, and so on. The callbacks run on the
+    /// thread that wrote or read while the buffer is locked, thus they should return quickly
+    /// and must not access the buffer.
+    ///
+    /// ```
+    /// use std::sync::mpsc;
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(8);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// let (tx, rx) = mpsc::channel();
+    /// rb.on_watermark(2, 6, move |watermark| tx.send(watermark).unwrap());
+    /// prod.write(&[0; 6]).unwrap();
+    /// cons.skip(4).unwrap();
+    /// assert_eq!(rx.try_iter().collect::<Vec<_>>(), [Watermark::High, Watermark::Low]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `low` is not below `high`.
+    pub fn on_watermark<F>(&self, low: usize, high: usize, callback: F)
+    where
+        F: FnMut(Watermark) + Send + 'static,
+    {
+        let _guard = self.buf.lock();
+        self.inspector
+            .watermarks
+            .register(low, high, self.inspector.count(), callback);
+    }
+}
+
+impl<T, S> RbInspector for SpscRb<T, S> {
+    fn is_empty(&self) -> bool {
+        self.inspector.is_empty()
+    }
+    fn is_full(&self) -> bool {
+        self.inspector.is_full()
+    }
+    fn capacity(&self) -> usize {
+        self.inspector.capacity()
+    }
+    fn slots_free(&self) -> usize {
+        self.inspector.slots_free()
+    }
+    fn count(&self) -> usize {
+        self.inspector.count()
+    }
+}
+
+/// Allows threads that only own a handle to make flow-control decisions.
+impl<T, S> RbInspector for Producer<T, S> {
+    fn is_empty(&self) -> bool {
+        self.inspector.is_empty()
+    }
+    fn is_full(&self) -> bool {
+        self.inspector.is_full()
+    }
+    fn capacity(&self) -> usize {
+        self.inspector.capacity()
+    }
+    fn slots_free(&self) -> usize {
+        self.inspector.slots_free()
+    }
+    fn count(&self) -> usize {
+        self.inspector.count()
+    }
+}
+
+impl<T, S> RbInspector for Consumer<T, S> {
+    fn is_empty(&self) -> bool {
+        self.inspector.is_empty()
+    }
+    fn is_full(&self) -> bool {
+        self.inspector.is_full()
+    }
+    fn capacity(&self) -> usize {
+        self.inspector.capacity()
+    }
+    fn slots_free(&self) -> usize {
+        self.inspector.slots_free()
+    }
+    fn count(&self) -> usize {
+        self.inspector.count()
+    }
+}
+
+impl Inspector {
+    /// Returns the number of free slots for the given positions.
+    #[inline(always)]
+    fn slots_free_between(&self, wr_pos: usize, re_pos: usize) -> usize {
+        // Saturating, without the lock the positions might belong to another size
+        // than the one loaded during a concurrent `SpscRb::resize`.
+        if wr_pos < re_pos {
+            re_pos - wr_pos - 1
+        } else {
+            (self.capacity() + re_pos).saturating_sub(wr_pos)
+        }
+    }
+
+    /// Returns the number of pending values for the given positions.
+    #[inline(always)]
+    fn count_between(&self, wr_pos: usize, re_pos: usize) -> usize {
+        self.capacity()
+            .saturating_sub(self.slots_free_between(wr_pos, re_pos))
+    }
+
+    /// Limits `read_history` to the slots in front of the `count` pending values, which
+    /// weren't written since they were read.
+    #[inline(always)]
+    fn forget_read(&self, count: usize) {
+        let free = self.capacity().saturating_sub(count);
+        if self.read_history.load(Ordering::Relaxed) > free {
+            self.read_history.store(free, Ordering::Relaxed);
+        }
+    }
+
+    /// Publishes the number of pending values to the handles that block on the futex word,
+    /// must be called while holding the buffer's lock after moving a position.
+    #[cfg(all(feature = "futex", any(target_os = "linux", windows)))]
+    #[inline(always)]
+    fn publish(&self) {
+        self.occupancy.publish(self.count(), self.capacity(), false);
+    }
+
+    /// Like `publish`, after a change of the capacity or the disconnection of a side.
+    #[cfg(all(feature = "futex", any(target_os = "linux", windows)))]
+    fn publish_event(&self) {
+        self.occupancy.publish(self.count(), self.capacity(), true);
+    }
+
+    /// Without the `futex` feature the blocked handles wait on the signals instead.
+    #[cfg(not(all(feature = "futex", any(target_os = "linux", windows))))]
+    #[inline(always)]
+    fn publish(&self) {}
+
+    #[cfg(not(all(feature = "futex", any(target_os = "linux", windows))))]
+    #[inline(always)]
+    fn publish_event(&self) {}
+
+    /// Returns the number of overruns and underruns, see `SpscRb::overruns`.
+    #[cfg(feature = "std")]
+    pub(crate) fn xruns(&self) -> (usize, usize) {
+        (
+            self.overruns.load(Ordering::Relaxed),
+            self.underruns.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Returns the number of slots.
+    #[inline(always)]
+    pub(crate) fn size(&self) -> usize {
+        self.size.load(Ordering::Relaxed)
+    }
+
+    /// Returns true if all producers were dropped and no new one can be created, since the
+    /// `SpscRb` was dropped as well, or the buffer was closed.
+    #[inline(always)]
+    pub(crate) fn producers_disconnected(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+            || (self.producers.is_disconnected() && !self.owned.load(Ordering::Relaxed))
+    }
+
+    /// Returns true if all consumers were dropped and no new one can be created, since the
+    /// `SpscRb` was dropped as well, or the buffer was closed.
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    pub(crate) fn consumers_disconnected(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+            || (self.consumers.is_disconnected() && !self.owned.load(Ordering::Relaxed))
+    }
+}
+
+/// Closes the buffer and wakes up all threads that are blocked in one of its handles.
+fn close<S>(buf: &Mutex<S>, inspector: &Inspector, slots_free: &Signal, data_available: &Signal) {
+    // Set while holding the lock, so that no blocked thread misses the wake-up.
+    let _guard = buf.lock();
+    #[cfg(feature = "tracing")]
+    ::tracing::debug!(pending = inspector.count(), "ring buffer closed");
+    inspector.closed.store(true, Ordering::Relaxed);
+    inspector.publish_event();
+    slots_free.notify_all();
+    data_available.notify_all();
+}
+
+impl RbInspector for Inspector {
+    #[inline(always)]
+    fn is_empty(&self) -> bool {
+        self.slots_free() == self.capacity()
+    }
+
+    #[inline(always)]
+    fn is_full(&self) -> bool {
+        self.slots_free() == 0
+    }
+
+    #[inline(always)]
+    fn capacity(&self) -> usize {
+        self.size() - 1
+    }
+
+    #[inline(always)]
+    fn slots_free(&self) -> usize {
+        // The inspector is used by both sides, thus both positions are loaded with `Acquire`.
+        let wr_pos = self.write_pos.load(Ordering::Acquire);
+        let re_pos = self.read_pos.load(Ordering::Acquire);
+        self.slots_free_between(wr_pos, re_pos)
+    }
+
+    #[inline(always)]
+    fn count(&self) -> usize {
+        self.capacity() - self.slots_free()
+    }
+}
+
+/// Producer view into the ring buffer.
+///
+/// A buffer has a single producer, use an `MpscRb` to obtain cloneable producers.
+pub struct Producer<T, S = Vec<T>> {
+    pub(crate) buf: Arc<Mutex<S>>,
+    pub(crate) inspector: Arc<Inspector>,
+    pub(crate) slots_free: Arc<Signal>,
+    pub(crate) data_available: Arc<Signal>,
+    #[cfg(feature = "std")]
+    pub(crate) wait_strategy: WaitStrategy,
+    pub(crate) overflow_policy: OverflowPolicy,
+    // Created by the first call of `readiness_fd`.
+    #[cfg(all(feature = "readiness", unix))]
+    pub(crate) readiness: OnceLock<Readiness>,
+    pub(crate) marker: PhantomData<fn() -> T>,
+}
+
+/// Consumer view into the ring buffer.
+///
+/// A buffer has a single consumer, use an `MpmcRb` to obtain cloneable consumers.
+pub struct Consumer<T, S = Vec<T>> {
+    pub(crate) buf: Arc<Mutex<S>>,
+    pub(crate) inspector: Arc<Inspector>,
+    pub(crate) slots_free: Arc<Signal>,
+    pub(crate) data_available: Arc<Signal>,
+    // Whether `io::Read` returns `WouldBlock` instead of blocking on an empty buffer.
+    #[cfg(feature = "std")]
+    pub(crate) nonblocking: bool,
+    // Bytes taken from the ring by `io::BufRead::fill_buf` that were not consumed yet.
+    #[cfg(feature = "std")]
+    pub(crate) io_buf: Vec<T>,
+    #[cfg(feature = "std")]
+    pub(crate) io_pos: usize,
+    #[cfg(feature = "std")]
+    pub(crate) wait_strategy: WaitStrategy,
+    // Value of `Inspector::overwritten` at the last read and the difference to the one
+    // before, see `skipped`. Only updated while holding the buffer's lock.
+    pub(crate) overwritten_seen: AtomicUsize,
+    pub(crate) skipped: AtomicUsize,
+    // Created by the first call of `readiness_fd`.
+    #[cfg(all(feature = "readiness", unix))]
+    pub(crate) readiness: OnceLock<Readiness>,
+    pub(crate) marker: PhantomData<fn() -> T>,
+}
+
+impl<T, S> Producer<T, S> {
+    /// Creates another producer of the same buffer.
+    #[cfg(feature = "std")]
+    pub(crate) fn share(&self) -> Self {
+        let _guard = self.buf.lock();
+        self.inspector.producers.attach();
+        Producer {
+            buf: self.buf.clone(),
+            inspector: self.inspector.clone(),
+            slots_free: self.slots_free.clone(),
+            data_available: self.data_available.clone(),
+            #[cfg(feature = "std")]
+            wait_strategy: self.wait_strategy,
+            overflow_policy: self.overflow_policy,
+            #[cfg(all(feature = "readiness", unix))]
+            readiness: OnceLock::new(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Closes the buffer, see `SpscRb::close`.
+    pub fn close(&self) {
+        close(
+            &self.buf,
+            &self.inspector,
+            &self.slots_free,
+            &self.data_available,
+        );
+    }
+
+    /// Blocks until at least `n` slots are free or `timeout` has elapsed, so that a block of
+    /// `n` values can be written at once. `n` is limited to the capacity of the buffer.
+    ///
+    /// Returns the number of free slots, which might be more than `n`.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(4);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write(&[1, 2, 3, 4]).unwrap();
+    /// cons.skip(2).unwrap();
+    /// assert_eq!(prod.wait_for_slots(2, Duration::from_secs(1)).unwrap(), 2);
+    /// prod.write_exact(&[5, 6]).unwrap();
+    /// ```
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::TimedOut`
+    /// - `RbError::Disconnected` all consumers were dropped
+    #[cfg(feature = "std")]
+    pub fn wait_for_slots(&self, n: usize, timeout: Duration) -> Result<usize> {
+        let n = cmp::min(n, self.inspector.capacity());
+        let start = Instant::now();
+        let mut guard = self.buf.lock();
+        loop {
+            self.check_connected()?;
+            let wr_pos = self.inspector.write_pos.load(Ordering::Relaxed);
+            let free = self.slots_free_from(wr_pos);
+            if free >= n {
+                return Ok(free);
+            }
+            let elapsed = start.elapsed();
+            if elapsed >= timeout {
+                return Err(RbError::TimedOut);
+            }
+            guard = self.wait_for(guard, n, timeout - elapsed);
+        }
+    }
+
+    /// Blocks until the consumers have read all pending values, e.g. before tearing down
+    /// or switching streams.
+    ///
+    /// `io::Write::flush` on the other hand returns right away,
+    /// since written bytes are visible to the consumer immediately.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Disconnected` all consumers were dropped before the buffer was drained
+    #[cfg(feature = "std")]
+    pub fn flush(&self) -> Result<()> {
+        match self.flush_timeout(Duration::MAX) {
+            Err(RbError::TimedOut) => panic!("Max duration shouldn't time out"),
+            result => result,
+        }
+    }
+
+    /// Blocks until the consumers have read all pending values or `timeout` has elapsed.
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(16);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write(&[1, 2, 3]).unwrap();
+    /// thread::spawn(move || while cons.read_blocking(&mut [0; 2]).is_some() {});
+    /// prod.flush_timeout(Duration::from_secs(1)).unwrap();
+    /// assert!(rb.is_empty());
+    /// ```
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::TimedOut`
+    /// - `RbError::Disconnected` all consumers were dropped before the buffer was drained
+    #[cfg(feature = "std")]
+    pub fn flush_timeout(&self, timeout: Duration) -> Result<()> {
+        match self.wait_for_slots(self.inspector.capacity(), timeout) {
+            // Nothing is lost if the consumers were dropped after reading everything.
+            Err(RbError::Disconnected) if self.inspector.is_empty() => Ok(()),
+            result => result.map(|_| ()),
+        }
+    }
+
+    /// Waits according to the wait strategy until `min` slots are free,
+    /// the consumers disconnected or `timeout` has elapsed.
+    ///
+    /// The wait might also end spuriously, callers check their condition again in a loop.
+    #[cfg(feature = "std")]
+    fn wait_for<'a>(
+        &'a self,
+        guard: MutexGuard<'a, S>,
+        min: usize,
+        timeout: Duration,
+    ) -> MutexGuard<'a, S> {
+        self.inspector.stats.on_producer_wait();
+        self.inspector.hooks.on_block_start(Side::Producer);
+        #[cfg(feature = "tracing")]
+        let _span = ::tracing::debug_span!("ring buffer wait", side = ?Side::Producer).entered();
+        let guard = self
+            .wait_strategy
+            .wait(
+                &self.buf,
+                guard,
+                &self.slots_free,
+                &self.inspector.occupancy,
+                timeout,
+                || self.inspector.slots_free() >= min || self.inspector.consumers_disconnected(),
+            )
+            .0;
+        self.inspector.hooks.on_block_end(Side::Producer);
+        guard
+    }
+
+    /// Wakes up the waiting consumers after a write of `cnt` values, reports crossed
+    /// watermarks and records the statistics, must be called while holding the buffer's lock.
+    #[inline(always)]
+    #[cfg_attr(not(feature = "std"), allow(unused_variables))]
+    fn notify_written(&self, cnt: usize) {
+        self.inspector.publish();
+        self.data_available.notify_all();
+        let count = self.inspector.count();
+        self.inspector.forget_read(count);
+        #[cfg(feature = "std")]
+        {
+            self.inspector.stats.on_write(cnt, count);
+            self.inspector.latency.on_write(cnt);
+            self.inspector.hooks.on_write(cnt);
+        }
+        self.inspector.watermarks.update(count);
+    }
+
+    /// Counts `cnt` values that a write could not store.
+    #[inline(always)]
+    fn overrun(&self, cnt: usize) {
+        if cnt > 0 {
+            #[cfg(feature = "tracing")]
+            ::tracing::debug!(lost = cnt, "ring buffer write truncated");
+            self.inspector.overruns.fetch_add(cnt, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns true if all consumers were dropped and no new one can be created, or the
+    /// buffer was closed.
+    #[cfg(feature = "std")]
+    pub(crate) fn is_disconnected(&self) -> bool {
+        self.inspector.consumers_disconnected()
+    }
+
+    /// Fails with `RbError::Disconnected` if all consumers were dropped or the buffer
+    /// was closed, must be called while holding the buffer's lock.
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    fn check_connected(&self) -> Result<()> {
+        if self.inspector.consumers_disconnected() {
+            return Err(RbError::Disconnected);
+        }
+        Ok(())
+    }
+
+    /// Fails with `RbError::Disconnected` if the buffer was closed, must be called while
+    /// holding the buffer's lock. Unlike the blocking writes, the non-blocking ones keep
+    /// storing values once the consumers were dropped.
+    #[inline(always)]
+    fn check_open(&self) -> Result<()> {
+        if self.inspector.closed.load(Ordering::Relaxed) {
+            return Err(RbError::Disconnected);
+        }
+        Ok(())
+    }
+
+    /// Returns the number of free slots in front of the write position `wr_pos`.
+    #[inline(always)]
+    fn slots_free_from(&self, wr_pos: usize) -> usize {
+        let re_pos = self.inspector.read_pos.load(Ordering::Acquire);
+        self.inspector.slots_free_between(wr_pos, re_pos)
+    }
+}
+
+impl<T, S> Consumer<T, S> {
+    /// Creates another consumer of the same buffer, which starts in the same mode.
+    #[cfg(feature = "std")]
+    pub(crate) fn share(&self) -> Self {
+        let _guard = self.buf.lock();
+        self.inspector.consumers.attach();
+        Consumer {
+            buf: self.buf.clone(),
+            inspector: self.inspector.clone(),
+            slots_free: self.slots_free.clone(),
+            data_available: self.data_available.clone(),
+            #[cfg(feature = "std")]
+            nonblocking: self.nonblocking,
+            #[cfg(feature = "std")]
+            io_buf: Vec::new(),
+            #[cfg(feature = "std")]
+            io_pos: 0,
+            #[cfg(feature = "std")]
+            wait_strategy: self.wait_strategy,
+            overwritten_seen: AtomicUsize::new(self.inspector.overwritten.load(Ordering::Relaxed)),
+            skipped: AtomicUsize::new(0),
+            #[cfg(all(feature = "readiness", unix))]
+            readiness: OnceLock::new(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Closes the buffer, see `SpscRb::close`.
+    pub fn close(&self) {
+        close(
+            &self.buf,
+            &self.inspector,
+            &self.slots_free,
+            &self.data_available,
+        );
+    }
+
+    /// Blocks until at least `n` values are pending or `timeout` has elapsed, without
+    /// reading anything. `n` is limited to the capacity of the buffer.
+    ///
+    /// Returns the number of pending values, which might be more than `n`.
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(16);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// thread::spawn(move || prod.write_blocking(&[1, 2, 3, 4]));
+    /// assert_eq!(cons.wait_for_count(4, Duration::from_secs(1)).unwrap(), 4);
+    /// ```
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::TimedOut`
+    /// - `RbError::Disconnected` all producers were dropped while less than `n` values are pending
+    #[cfg(feature = "std")]
+    pub fn wait_for_count(&self, n: usize, timeout: Duration) -> Result<usize> {
+        let n = cmp::min(n, self.inspector.capacity());
+        let start = Instant::now();
+        let mut guard = self.buf.lock();
+        loop {
+            let re_pos = self.inspector.read_pos.load(Ordering::Relaxed);
+            let count = self.count_from(re_pos);
+            if count >= n {
+                return Ok(count);
+            }
+            if self.inspector.producers_disconnected() {
+                return Err(RbError::Disconnected);
+            }
+            let elapsed = start.elapsed();
+            if elapsed >= timeout {
+                return Err(RbError::TimedOut);
+            }
+            guard = self.wait_for(guard, n, timeout - elapsed);
+        }
+    }
+
+    /// Returns the number of values that were lost because the producer overwrote or
+    /// discarded them, see `Producer::write_overwriting` and `Producer::discard_oldest`.
+    pub fn overwritten(&self) -> usize {
+        self.inspector.overwritten.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of values that the producer discarded because the buffer was full,
+    /// see `Producer::write_dropping`.
+    pub fn dropped(&self) -> usize {
+        self.inspector.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of values that were overwritten between the previous read of this
+    /// consumer and the last one, i.e. the gap right before the values it read last,
+    /// e.g. to insert a marker when the producer uses `Producer::write_overwriting`.
+    ///
+    /// `overwritten` is the sequence of all lost values, the gaps add up to its increase
+    /// since the consumer was created.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(4);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write_overwriting(&[1, 2]).unwrap();
+    /// assert_eq!(cons.read_vec(1), vec![1]);
+    /// assert_eq!(cons.skipped(), 0);
+    /// prod.write_overwriting(&[3, 4, 5, 6]).unwrap();
+    /// assert_eq!(cons.read_vec(4), vec![3, 4, 5, 6]);
+    /// assert_eq!(cons.skipped(), 1);
+    /// ```
+    pub fn skipped(&self) -> usize {
+        self.skipped.load(Ordering::Relaxed)
+    }
+
+    /// Moves the read position back over up to `cnt` values that were already read but
+    /// not overwritten by the producer yet, which are pending again, e.g. for a parser that
+    /// consumed more than it could process. Values that weren't read, e.g. the ones that
+    /// were discarded by `RB::reset` or `Producer::write_overwriting`, can't be unread.
+    ///
+    /// Returns the number of values that are pending again.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(4);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write(&[1, 2, 3]).unwrap();
+    /// assert_eq!(cons.read_vec(2), vec![1, 2]);
+    /// assert_eq!(cons.unread(1), 1);
+    /// assert_eq!(cons.read_vec(4), vec![2, 3]);
+    /// ```
+    pub fn unread(&self, cnt: usize) -> usize {
+        let _guard = self.buf.lock();
+        let history = self.inspector.read_history.load(Ordering::Relaxed);
+        let cnt = cmp::min(cnt, history);
+        if cnt == 0 {
+            return 0;
+        }
+        // Writes hold the buffer's lock as well, thus the slots can't be overwritten
+        // concurrently.
+        let size = self.inspector.size();
+        let re_pos = self.inspector.read_pos.load(Ordering::Relaxed);
+        let re_pos = (re_pos + size - cnt) % size;
+        self.inspector.read_pos.store(re_pos, Ordering::Release);
+        self.inspector
+            .read_history
+            .store(history - cnt, Ordering::Relaxed);
+        #[cfg(feature = "std")]
+        self.inspector.latency.unread(cnt);
+        self.inspector.publish();
+
+        self.data_available.notify_all();
+        self.inspector.watermarks.update(self.inspector.count());
+        cnt
+    }
+
+    /// Waits according to the wait strategy until `min` values are pending,
+    /// the producers disconnected or `timeout` has elapsed.
+    ///
+    /// The wait might also end spuriously, callers check their condition again in a loop.
+    #[cfg(feature = "std")]
+    fn wait_for<'a>(
+        &'a self,
+        guard: MutexGuard<'a, S>,
+        min: usize,
+        timeout: Duration,
+    ) -> MutexGuard<'a, S> {
+        self.inspector.stats.on_consumer_wait();
+        self.inspector.hooks.on_block_start(Side::Consumer);
+        #[cfg(feature = "tracing")]
+        let _span = ::tracing::debug_span!("ring buffer wait", side = ?Side::Consumer).entered();
+        let guard = self
+            .wait_strategy
+            .wait(
+                &self.buf,
+                guard,
+                &self.data_available,
+                &self.inspector.occupancy,
+                timeout,
+                || self.inspector.count() >= min || self.inspector.producers_disconnected(),
+            )
+            .0;
+        self.inspector.hooks.on_block_end(Side::Consumer);
+        guard
+    }
+
+    /// Wakes up the waiting producers after a read of `cnt` values, reports crossed
+    /// watermarks, records the statistics and updates `skipped`, must be called while
+    /// holding the buffer's lock.
+    #[inline(always)]
+    fn notify_read(&self, cnt: usize) {
+        // `write_overwriting` holds the lock as well, thus all values that it overwrote
+        // since the previous read preceded the values of this one.
+        let overwritten = self.inspector.overwritten.load(Ordering::Relaxed);
+        let seen = self.overwritten_seen.swap(overwritten, Ordering::Relaxed);
+        self.skipped
+            .store(overwritten.wrapping_sub(seen), Ordering::Relaxed);
+        self.inspector.publish();
+        self.slots_free.notify_all();
+        let count = self.inspector.count();
+        let history = self.inspector.read_history.load(Ordering::Relaxed);
+        self.inspector
+            .read_history
+            .store(history.saturating_add(cnt), Ordering::Relaxed);
+        self.inspector.forget_read(count);
+        #[cfg(feature = "std")]
+        {
+            self.inspector.stats.on_read(cnt, count);
+            self.inspector.latency.on_read(cnt);
+            self.inspector.hooks.on_read(cnt);
+        }
+        self.inspector.watermarks.update(count);
+    }
+
+    /// Returns the error for an empty buffer, which is `RbError::Disconnected` if all
+    /// producers were dropped or the buffer was closed.
+    /// Must be called while holding the buffer's lock.
+    #[inline(always)]
+    fn empty_error(&self) -> RbError {
+        if self.inspector.producers_disconnected() {
+            RbError::Disconnected
+        } else {
+            RbError::Empty
+        }
+    }
+
+    /// Works analog to `empty_error` but counts an underrun if the producers are connected.
+    fn underrun_error(&self) -> RbError {
+        let err = self.empty_error();
+        if let RbError::Empty = err {
+            self.inspector.underruns.fetch_add(1, Ordering::Relaxed);
+        }
+        err
+    }
+
+    /// Returns the number of pending values behind the read position `re_pos`.
+    #[inline(always)]
+    fn count_from(&self, re_pos: usize) -> usize {
+        let wr_pos = self.inspector.write_pos.load(Ordering::Acquire);
+        self.inspector.count_between(wr_pos, re_pos)
+    }
+}
+
+/// Wakes up the blocked handles, a side whose handles were all dropped is disconnected
+/// from now on since no new handles can be created.
+impl<T, S> Drop for SpscRb<T, S> {
+    fn drop(&mut self) {
+        self.disown();
+    }
+}
+
+/// Wakes up blocked consumers once the last producer is dropped.
+impl<T, S> Drop for Producer<T, S> {
+    fn drop(&mut self) {
+        let _guard = self.buf.lock();
+        if self.inspector.producers.detach() {
+            self.inspector.publish_event();
+            self.data_available.notify_all();
+        }
+    }
+}
+
+/// Wakes up blocked producers once the last consumer is dropped.
+impl<T, S> Drop for Consumer<T, S> {
+    fn drop(&mut self) {
+        let _guard = self.buf.lock();
+        if self.inspector.consumers.detach() {
+            self.inspector.publish_event();
+            self.slots_free.notify_all();
+        }
+    }
+}
+
+impl<T: Clone + Default, S: Storage<T>> Producer<T, S> {
+    /// Passes the free slots to `f`, which writes into them in place instead of copying from a slice.
+    /// The slots are passed as two slices since they might wrap around the end of the buffer,
+    /// `f` returns the number of values it has written to the beginning of them.
+    /// Slots that were never written hold the default value of `T`.
+    ///
+    /// The buffer is locked while `f` runs, thus it should return quickly.
+    ///
+    /// Returns the number of written values or an error.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Full`
+    /// - `RbError::Disconnected` the buffer was closed
+    pub fn write_access<F>(&self, f: F) -> Result<usize>
+    where
+        F: FnOnce(&mut [T], &mut [T]) -> usize,
+    {
+        let mut buf = self.buf.lock();
+        self.check_open()?;
+        let wr_pos = self.inspector.write_pos.load(Ordering::Relaxed);
+        let free = self.slots_free_from(wr_pos);
+        if free == 0 {
+            return Err(RbError::Full);
+        }
+        let size = self.inspector.size();
+        let (head, tail) = buf.slots_mut(size).split_at_mut(wr_pos);
+        let end = wr_pos + free;
+        let written = if end <= size {
+            f(&mut tail[..free], &mut [])
+        } else {
+            f(tail, &mut head[..end - size])
+        };
+        let cnt = cmp::min(written, free);
+
+        self.inspector
+            .write_pos
+            .store((wr_pos + cnt) % size, Ordering::Release);
+        self.notify_written(cnt);
+        Ok(cnt)
+    }
+
+    /// Reserves up to `cnt` contiguous free slots that can be written in place.
+    /// Fewer slots are granted if the buffer has less free slots or they wrap around its end.
+    /// Slots that were never written hold the default value of `T`.
+    ///
+    /// The written values are published when the grant is dropped or committed,
+    /// the buffer stays locked until then.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(4);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// let mut grant = prod.grant(3).unwrap();
+    /// grant[..2].copy_from_slice(&[1, 2]);
+    /// grant.commit(2);
+    /// assert_eq!(rb.count(), 2);
+    /// ```
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Full`
+    /// - `RbError::Disconnected` the buffer was closed
+    pub fn grant(&self, cnt: usize) -> Result<WriteGrant<'_, T, S>> {
+        let mut buf = self.buf.lock();
+        self.check_open()?;
+        let wr_pos = self.inspector.write_pos.load(Ordering::Relaxed);
+        let free = self.slots_free_from(wr_pos);
+        if free == 0 {
+            return Err(RbError::Full);
+        }
+        let size = self.inspector.size();
+        buf.slots_mut(size);
+        Ok(WriteGrant {
+            len: cmp::min(cnt, cmp::min(free, size - wr_pos)),
+            wr_pos,
+            buf,
+            producer: self,
+        })
+    }
+}
+
+impl<T: Clone, S: Storage<T>> Consumer<T, S> {
+    /// Reads up to `max` pending values into a new `Vec`, which is empty if there are none.
+    pub fn read_vec(&self, max: usize) -> Vec<T> {
+        let buf = self.buf.lock();
+        let re_pos = self.inspector.read_pos.load(Ordering::Relaxed);
+        let cnt = cmp::min(max, self.count_from(re_pos));
+        if cnt == 0 {
+            return Vec::new();
+        }
+        let size = self.inspector.size();
+        let data = pending_to_vec(buf.slots(), size, re_pos, cnt, cnt);
+
+        self.inspector
+            .read_pos
+            .store((re_pos + cnt) % size, Ordering::Release);
+        self.notify_read(cnt);
+        data
+    }
+
+    /// Reads all pending values into a new `Vec`, which is empty if there are none.
+    pub fn read_all_vec(&self) -> Vec<T> {
+        self.read_vec(usize::MAX)
+    }
+
+    /// Works analog to `read` but converts each value with `f` while it's copied into
+    /// `data`, e.g. `i16` samples to `f32` or network to host byte order, which avoids a
+    /// second pass over `data`.
+    ///
+    /// Returns the number of converted values or an error.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(4);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write(&[i16::MIN, 0, i16::MAX / 2]).unwrap();
+    /// let mut data = [0.0f32; 4];
+    /// assert_eq!(cons.read_map(&mut data, |&x| f32::from(x) / 32768.0).unwrap(), 3);
+    /// assert_eq!(data[..3], [-1.0, 0.0, 16383.0 / 32768.0]);
+    /// ```
+    ///
+    /// Possible errors:
+    ///
+    /// - RbError::Empty
+    /// - RbError::Disconnected the buffer is empty and all producers were dropped
+    pub fn read_map<U, F>(&self, data: &mut [U], mut f: F) -> Result<usize>
+    where
+        F: FnMut(&T) -> U,
+    {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        let buf = self.buf.lock();
+        let re_pos = self.inspector.read_pos.load(Ordering::Relaxed);
+        let cnt = cmp::min(data.len(), self.count_from(re_pos));
+        if cnt == 0 {
+            return Err(self.underrun_error());
+        }
+
+        let size = self.inspector.size();
+        let slots = buf.slots();
+        let first = cmp::min(cnt, size - re_pos);
+        let (head, tail) = data[..cnt].split_at_mut(first);
+        for (dst, src) in head.iter_mut().zip(&slots[re_pos..]) {
+            *dst = f(src);
+        }
+        for (dst, src) in tail.iter_mut().zip(slots) {
+            *dst = f(src);
+        }
+
+        self.inspector
+            .read_pos
+            .store((re_pos + cnt) % size, Ordering::Release);
+        self.notify_read(cnt);
+        Ok(cnt)
+    }
+
+    /// Reads the pending values into a new `Vec` as long as `pred` returns true for them,
+    /// e.g. to split events at a delimiter. The first value that doesn't match stays pending.
+    ///
+    /// The buffer is locked while `pred` runs, thus it should return quickly.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(16);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write(b"key=value;").unwrap();
+    /// assert_eq!(cons.read_while(|&b| b != b'='), b"key");
+    /// cons.skip(1).unwrap();
+    /// assert_eq!(cons.read_while(|&b| b != b';'), b"value");
+    /// ```
+    pub fn read_while<F>(&self, mut pred: F) -> Vec<T>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let buf = self.buf.lock();
+        let re_pos = self.inspector.read_pos.load(Ordering::Relaxed);
+        let count = self.count_from(re_pos);
+        let size = self.inspector.size();
+        let slots = buf.slots();
+        let data: Vec<T> = (0..count)
+            .map(|i| &slots[(re_pos + i) % size])
+            .take_while(|value| pred(value))
+            .cloned()
+            .collect();
+        if data.is_empty() {
+            return data;
+        }
+
+        self.inspector
+            .read_pos
+            .store((re_pos + data.len()) % size, Ordering::Release);
+        self.notify_read(data.len());
+        data
+    }
+
+    /// Fills `data` with the next `data.len()` pending values but only consumes the first
+    /// `hop` of them, the following ones are read again by the next call. This is the access
+    /// pattern of overlap-add processing, e.g. a STFT with windows of `data.len()` samples.
+    ///
+    /// Either the whole window is read or nothing, thus it must not be larger than the
+    /// capacity of the buffer, which it could never fill.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(8);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write(&[1, 2, 3, 4, 5, 6]).unwrap();
+    /// let mut window = [0; 4];
+    /// cons.read_overlapped(&mut window, 2).unwrap();
+    /// assert_eq!(window, [1, 2, 3, 4]);
+    /// cons.read_overlapped(&mut window, 2).unwrap();
+    /// assert_eq!(window, [3, 4, 5, 6]);
+    /// assert_eq!(cons.count(), 2);
+    /// ```
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Empty` less pending values than `data.len()`
+    /// - `RbError::Disconnected` less pending values than `data.len()` and all producers
+    ///   were dropped
+    ///
+    /// # Panics
+    ///
+    /// Panics if `hop` is larger than `data.len()`, since the skipped values would never be
+    /// read, or if `data.len()` is larger than the current capacity of the buffer.
+    pub fn read_overlapped(&self, data: &mut [T], hop: usize) -> Result<()> {
+        assert!(hop <= data.len(), "the hop is larger than the window");
+        if data.is_empty() {
+            return Ok(());
+        }
+        let buf = self.buf.lock();
+        assert!(
+            data.len() <= self.inspector.capacity(),
+            "the window is larger than the capacity of the buffer"
+        );
+        let re_pos = self.inspector.read_pos.load(Ordering::Relaxed);
+        if self.count_from(re_pos) < data.len() {
+            return Err(self.underrun_error());
+        }
+
+        copy_from_ring(buf.slots(), re_pos, data);
+        if hop == 0 {
+            return Ok(());
+        }
+
+        self.inspector
+            .read_pos
+            .store((re_pos + hop) % self.inspector.size(), Ordering::Release);
+        self.notify_read(hop);
+        Ok(())
+    }
+
+    /// Reads pending values into `data` and fills the rest of it with `T::default()`, e.g.
+    /// silence, so that an audio callback always produces a complete block. Underruns are
+    /// counted like by `read`.
+    ///
+    /// Returns the number of values that were actually read.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(8);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write(&[0.5, -0.5]).unwrap();
+    /// let mut block = [1.0; 4];
+    /// assert_eq!(cons.read_or_default(&mut block), 2);
+    /// assert_eq!(block, [0.5, -0.5, 0.0, 0.0]);
+    /// ```
+    pub fn read_or_default(&self, data: &mut [T]) -> usize
+    where
+        T: Default,
+    {
+        let cnt = self.read(data).unwrap_or(0);
+        data[cnt..].iter_mut().for_each(|x| *x = T::default());
+        cnt
+    }
+
+    /// Returns a copy of all pending values without consuming them, e.g. for debugging or
+    /// a scope display. The buffer is locked while copying, thus the copy never contains a
+    /// partial write. Use `get` to copy them into an existing slice instead.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(4);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write(&[1, 2, 3]).unwrap();
+    /// assert_eq!(cons.snapshot(), [1, 2, 3]);
+    /// assert_eq!(cons.count(), 3);
+    /// ```
+    pub fn snapshot(&self) -> Vec<T> {
+        let buf = self.buf.lock();
+        let re_pos = self.inspector.read_pos.load(Ordering::Relaxed);
+        let cnt = self.count_from(re_pos);
+        pending_to_vec(buf.slots(), self.inspector.size(), re_pos, cnt, cnt)
+    }
+
+    /// Passes the pending values to `f`, which processes them in place instead of copying them.
+    /// The values are passed as two slices since they might wrap around the end of the buffer,
+    /// `f` returns the number of values it has consumed from the beginning of them.
+    ///
+    /// The buffer is locked while `f` runs, thus it should return quickly.
+    ///
+    /// Returns the number of consumed values or an error.
+    ///
+    /// Possible errors:
+    ///
+    /// - RbError::Empty
+    /// - RbError::Disconnected the buffer is empty and all producers were dropped
+    pub fn read_access<F>(&self, f: F) -> Result<usize>
+    where
+        F: FnOnce(&[T], &[T]) -> usize,
+    {
+        let buf = self.buf.lock();
+        let re_pos = self.inspector.read_pos.load(Ordering::Relaxed);
+        let pending = self.count_from(re_pos);
+        if pending == 0 {
+            return Err(self.underrun_error());
+        }
+        let size = self.inspector.size();
+        let slots = buf.slots();
+        let end = re_pos + pending;
+        let consumed = if end <= size {
+            f(&slots[re_pos..end], &[])
+        } else {
+            f(&slots[re_pos..], &slots[..end - size])
+        };
+        let cnt = cmp::min(consumed, pending);
+
+        self.inspector
+            .read_pos
+            .store((re_pos + cnt) % size, Ordering::Release);
+        self.notify_read(cnt);
+        Ok(cnt)
+    }
+
+    /// Returns an iterator over clones of the pending values, the read pointer isn't moved.
+    ///
+    /// The buffer stays locked while the iterator is alive.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(4);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write(&[0.5, -0.5]).unwrap();
+    /// let energy: f32 = cons.iter_pending().map(|x| x * x).sum();
+    /// assert_eq!(energy, 0.5);
+    /// assert_eq!(rb.count(), 2);
+    /// ```
+    pub fn iter_pending(&self) -> PendingIter<'_, T, S> {
+        let buf = self.buf.lock();
+        let re_pos = self.inspector.read_pos.load(Ordering::Relaxed);
+        PendingIter {
+            remaining: self.count_from(re_pos),
+            pos: re_pos,
+            size: self.inspector.size(),
+            buf,
+            marker: PhantomData,
+        }
+    }
+
+    /// Folds the pending values into an accumulator in place, without cloning or consuming
+    /// them, e.g. to compute the peak or RMS level for a meter on every UI tick.
+    ///
+    /// The buffer is locked while `f` runs, thus it should return quickly.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(4);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write(&[0.25f32, -0.5, 0.125]).unwrap();
+    /// let peak = cons.fold_pending(0.0, |peak: f32, x| peak.max(x.abs()));
+    /// assert_eq!(peak, 0.5);
+    /// assert_eq!(rb.count(), 3);
+    /// ```
+    pub fn fold_pending<B, F>(&self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, &T) -> B,
+    {
+        let buf = self.buf.lock();
+        let re_pos = self.inspector.read_pos.load(Ordering::Relaxed);
+        let count = self.count_from(re_pos);
+        let size = self.inspector.size();
+        let slots = buf.slots();
+        let end = re_pos + count;
+        if end <= size {
+            slots[re_pos..end].iter().fold(init, f)
+        } else {
+            let acc = slots[re_pos..].iter().fold(init, &mut f);
+            slots[..end - size].iter().fold(acc, f)
+        }
+    }
+
+    /// Grants access to the contiguous pending values, i.e. the values up to the end of the
+    /// buffer if they wrap around it.
+    ///
+    /// The values are consumed when the grant is dropped or released,
+    /// the buffer stays locked until then.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(4);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write(&[1, 2, 3]).unwrap();
+    /// let grant = cons.read_grant().unwrap();
+    /// assert_eq!(*grant, [1, 2, 3]);
+    /// grant.release(2);
+    /// assert_eq!(rb.count(), 1);
+    /// ```
+    ///
+    /// Possible errors:
+    ///
+    /// - RbError::Empty
+    /// - RbError::Disconnected the buffer is empty and all producers were dropped
+    pub fn read_grant(&self) -> Result<ReadGrant<'_, T, S>> {
+        let buf = self.buf.lock();
+        let re_pos = self.inspector.read_pos.load(Ordering::Relaxed);
+        let pending = self.count_from(re_pos);
+        if pending == 0 {
+            return Err(self.underrun_error());
+        }
+        Ok(ReadGrant {
+            len: cmp::min(pending, self.inspector.size() - re_pos),
+            re_pos,
+            buf,
+            consumer: self,
+        })
+    }
+}
+
+impl<T: Clone, S: Storage<T>> Producer<T, S> {
+    /// Works analog to `write` but takes the values from an iterator.
+    /// Only as many values as there are free slots are taken from the iterator,
+    /// pass `iter.by_ref()` to write the remaining values later on.
+    ///
+    /// Returns the number of written values or an error.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Full`
+    /// - `RbError::Disconnected` the buffer was closed
+    pub fn write_from_iter<I>(&self, iter: I) -> Result<usize>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut buf = self.buf.lock();
+        self.check_open()?;
+        let wr_pos = self.inspector.write_pos.load(Ordering::Relaxed);
+        let free = self.slots_free_from(wr_pos);
+        if free == 0 {
+            return Err(RbError::Full);
+        }
+        let size = self.inspector.size();
+        let mut cnt = 0;
+        for value in iter.into_iter().take(free) {
+            buf.store(size, (wr_pos + cnt) % size, slice::from_ref(&value));
+            cnt += 1;
+        }
+
+        self.inspector
+            .write_pos
+            .store((wr_pos + cnt) % size, Ordering::Release);
+        self.notify_written(cnt);
+        Ok(cnt)
+    }
+
+    /// Writes all of `data` and never blocks or fails on a full buffer, instead the oldest
+    /// pending values are overwritten, e.g. for scope displays or telemetry that only care
+    /// about the most recent values. If `data` is longer than the capacity only its
+    /// last values are written.
+    ///
+    /// Returns the number of values that were lost, i.e. overwritten pending values and
+    /// values of `data` that did not fit, which are also added to `overwritten`.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(4);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write(&[1, 2, 3]).unwrap();
+    /// assert_eq!(prod.write_overwriting(&[4, 5, 6]).unwrap(), 2);
+    /// assert_eq!(cons.read_vec(4), vec![3, 4, 5, 6]);
+    /// assert_eq!(cons.overwritten(), 2);
+    /// ```
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Disconnected` the buffer was closed
+    pub fn write_overwriting(&self, data: &[T]) -> Result<usize> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        let mut buf = self.buf.lock();
+        self.check_open()?;
+        let size = self.inspector.size();
+        // Values that would be overwritten by later values of `data` are skipped.
+        let skipped = data.len().saturating_sub(self.inspector.capacity());
+        let data = &data[skipped..];
+        let wr_pos = self.inspector.write_pos.load(Ordering::Relaxed);
+        let free = self.slots_free_from(wr_pos);
+        let overwritten = data.len().saturating_sub(free);
+        if overwritten > 0 {
+            // Reads hold the buffer's lock as well, thus the read position can't change
+            // concurrently.
+            let re_pos = self.inspector.read_pos.load(Ordering::Relaxed);
+            let re_pos = (re_pos + overwritten) % size;
+            self.inspector.read_pos.store(re_pos, Ordering::Release);
+            self.inspector.read_history.store(0, Ordering::Relaxed);
+            #[cfg(feature = "std")]
+            self.inspector.latency.discard(overwritten);
+        }
+
+        buf.store(size, wr_pos, data);
+        let wr_pos = (wr_pos + data.len()) % size;
+        self.inspector.write_pos.store(wr_pos, Ordering::Release);
+        if overwritten > 0 {
+            // `data` took the slots of all lost values but the last one, which is in front
+            // of the new read position.
+            buf.scrub(size, wr_pos, 1);
+        }
+
+        let lost = skipped + overwritten;
+        self.inspector
+            .overwritten
+            .fetch_add(lost, Ordering::Relaxed);
+        self.overrun(lost);
+        self.notify_written(data.len());
+        Ok(lost)
+    }
+
+    /// Discards the oldest `cnt` pending values, at most all of them, e.g. for a real-time
+    /// producer to bound the latency while the consumer stalls. The discarded values are
+    /// added to `overwritten` like the ones of `write_overwriting`.
+    ///
+    /// Returns the number of discarded values.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(8);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write(&[1, 2, 3, 4, 5, 6]).unwrap();
+    /// // keep at most 4 pending values
+    /// let excess = prod.count().saturating_sub(4);
+    /// assert_eq!(prod.discard_oldest(excess).unwrap(), 2);
+    /// assert_eq!(cons.read_vec(8), vec![3, 4, 5, 6]);
+    /// assert_eq!(cons.skipped(), 2);
+    /// ```
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Disconnected` the buffer was closed
+    pub fn discard_oldest(&self, cnt: usize) -> Result<usize> {
+        let mut buf = self.buf.lock();
+        self.check_open()?;
+        let cnt = cmp::min(cnt, self.inspector.count());
+        if cnt == 0 {
+            return Ok(0);
+        }
+        // Reads hold the buffer's lock as well, thus the read position can't change
+        // concurrently.
+        let size = self.inspector.size();
+        let re_pos = self.inspector.read_pos.load(Ordering::Relaxed);
+        buf.scrub(size, re_pos, cnt);
+        let re_pos = (re_pos + cnt) % size;
+        self.inspector.read_pos.store(re_pos, Ordering::Release);
+        self.inspector.read_history.store(0, Ordering::Relaxed);
+        #[cfg(feature = "std")]
+        self.inspector.latency.discard(cnt);
+
+        self.inspector.overwritten.fetch_add(cnt, Ordering::Relaxed);
+        self.inspector.publish();
+        self.slots_free.notify_all();
+        self.inspector.watermarks.update(self.inspector.count());
+        Ok(cnt)
+    }
+
+    /// Retracts the newest `cnt` pending values, at most all of them, e.g. the partial frame
+    /// of a producer that detected an error in the middle of a block. Values that were
+    /// already read can't be retracted, write the block into a `grant` instead if the
+    /// consumer must never see a part of it, `commit(0)` publishes none of its values.
+    /// With several producers the newest values might have been written by another one.
+    ///
+    /// Returns the number of retracted values.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(8);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write(&[1, 2]).unwrap();
+    /// prod.write(&[3, 4, 5]).unwrap();
+    /// assert_eq!(prod.rollback(3), 3);
+    /// assert_eq!(cons.read_vec(8), vec![1, 2]);
+    /// ```
+    pub fn rollback(&self, cnt: usize) -> usize {
+        let mut buf = self.buf.lock();
+        let cnt = cmp::min(cnt, self.inspector.count());
+        if cnt == 0 {
+            return 0;
+        }
+        // Reads hold the buffer's lock as well, thus none of the values can be read
+        // concurrently.
+        let size = self.inspector.size();
+        let wr_pos = self.inspector.write_pos.load(Ordering::Relaxed);
+        let wr_pos = (wr_pos + size - cnt) % size;
+        buf.retract(size, wr_pos, cnt);
+        self.inspector.write_pos.store(wr_pos, Ordering::Release);
+        #[cfg(feature = "std")]
+        self.inspector.latency.retract(cnt);
+        self.inspector.publish();
+
+        self.slots_free.notify_all();
+        self.inspector.watermarks.update(self.inspector.count());
+        cnt
+    }
+
+    /// Writes as many values of `data` as there are free slots, i.e. `write` with the
+    /// default `OverflowPolicy::Partial`.
+    pub(crate) fn write_partial(&self, data: &[T]) -> Result<usize> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        // The positions are evaluated while holding the lock,
+        // this allows several producers to share the buffer (see `MpscRb`).
+        let mut buf = self.buf.lock();
+        self.check_open()?;
+        let wr_pos = self.inspector.write_pos.load(Ordering::Relaxed);
+        let cnt = cmp::min(data.len(), self.slots_free_from(wr_pos));
+        self.overrun(data.len() - cnt);
+        if cnt == 0 {
+            return Err(RbError::Full);
+        }
+
+        buf.store(self.inspector.size(), wr_pos, &data[..cnt]);
+        self.inspector
+            .write_pos
+            .store((wr_pos + cnt) % self.inspector.size(), Ordering::Release);
+
+        self.notify_written(cnt);
+        Ok(cnt)
+    }
+
+    /// Writes `data` like `write_partial` after doubling the capacity as often as needed
+    /// for all values to fit, but not beyond `max`, i.e. `write` with `OverflowPolicy::Grow`.
+    fn write_growing(&self, data: &[T], max: usize) -> Result<usize> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        {
+            let mut buf = self.buf.lock();
+            self.check_open()?;
+            let wr_pos = self.inspector.write_pos.load(Ordering::Relaxed);
+            if self.slots_free_from(wr_pos) < data.len() {
+                let wanted = self.inspector.count().saturating_add(data.len());
+                let mut capacity = self.inspector.capacity();
+                while capacity < wanted && capacity < max {
+                    capacity = capacity.saturating_mul(2);
+                }
+                let capacity = cmp::min(capacity, max);
+                if capacity > self.inspector.capacity() && check_capacity::<T>(capacity).is_ok() {
+                    reallocate(&mut *buf, &self.inspector, &self.slots_free, capacity);
+                }
+            }
+        }
+        // Another producer might fill the grown buffer in between, which is fine since
+        // the values that don't fit are reported like for the other partial writes.
+        self.write_partial(data)
+    }
+
+    /// Returns the number of values that were lost by `write_overwriting` and
+    /// `discard_oldest` so far.
+    pub fn overwritten(&self) -> usize {
+        self.inspector.overwritten.load(Ordering::Relaxed)
+    }
+
+    /// Writes as many values of `data` as there are free slots and silently discards the
+    /// others instead of failing on a full buffer, e.g. for best-effort logging.
+    /// It's the complement of `write_overwriting`, which keeps the newest values instead.
+    ///
+    /// Returns the number of discarded values, which are also added to `dropped`.
+    ///
+    /// ```
+    /// use rb::*;
+    ///
+    /// let rb = SpscRb::new(4);
+    /// let (prod, cons) = (rb.producer(), rb.consumer());
+    /// prod.write(&[1, 2, 3]).unwrap();
+    /// assert_eq!(prod.write_dropping(&[4, 5, 6]).unwrap(), 2);
+    /// assert_eq!(cons.read_vec(4), vec![1, 2, 3, 4]);
+    /// assert_eq!(cons.dropped(), 2);
+    /// ```
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Disconnected` the buffer was closed
+    pub fn write_dropping(&self, data: &[T]) -> Result<usize> {
+        let written = match self.write_partial(data) {
+            Ok(cnt) => cnt,
+            Err(RbError::Full) => 0,
+            Err(err) => return Err(err),
+        };
+        let dropped = data.len() - written;
+        self.inspector.dropped.fetch_add(dropped, Ordering::Relaxed);
+        Ok(dropped)
+    }
+
+    /// Returns the number of values that were discarded by `write_dropping` so far.
+    pub fn dropped(&self) -> usize {
+        self.inspector.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Blocks until there is at least one free slot or all consumers were dropped.
+    #[cfg(feature = "std")]
+    fn wait_for_slots_free(&self) {
+        let mut guard = self.buf.lock();
+        while self.check_connected().is_ok() {
+            let wr_pos = self.inspector.write_pos.load(Ordering::Relaxed);
+            if self.slots_free_from(wr_pos) > 0 {
+                break;
+            }
+            guard = self.wait_for(guard, 1, Duration::MAX);
+        }
+    }
+}
+
+/// Writes all values of the iterator and blocks whenever the buffer is full.
+/// The remaining values are discarded if all consumers were dropped.
+#[cfg(feature = "std")]
+impl<T: Clone, S: Storage<T>> Extend<T> for Producer<T, S> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut iter = iter.into_iter().peekable();
+        while iter.peek().is_some() {
+            match self.write_from_iter(&mut iter) {
+                Ok(_) => {}
+                Err(RbError::Disconnected) => return,
+                Err(_) => self.wait_for_slots_free(),
+            }
+        }
+    }
+}
+
+impl<T: Clone, S: Storage<T>> RbProducer<T> for Producer<T, S> {
+    fn write(&self, data: &[T]) -> Result<usize> {
+        match self.overflow_policy {
+            OverflowPolicy::Error => self.write_exact(data).map(|_| data.len()),
+            OverflowPolicy::Partial => self.write_partial(data),
+            OverflowPolicy::DropNewest => self.write_dropping(data).map(|_| data.len()),
+            OverflowPolicy::OverwriteOldest => self.write_overwriting(data).map(|_| data.len()),
+            OverflowPolicy::Grow { max } => self.write_growing(data, max),
+            #[cfg(feature = "std")]
+            OverflowPolicy::Block => match self.write_blocking(data) {
+                Some(cnt) => Ok(cnt),
+                None if data.is_empty() => Ok(0),
+                None => Err(RbError::Disconnected),
+            },
+            #[cfg(not(feature = "std"))]
+            OverflowPolicy::Block => unreachable!("rejected by `SpscRb::with_overflow_policy`"),
+        }
+    }
+
+    fn write_exact(&self, data: &[T]) -> Result<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        let mut buf = self.buf.lock();
+        self.check_open()?;
+        let wr_pos = self.inspector.write_pos.load(Ordering::Relaxed);
+        if self.slots_free_from(wr_pos) < data.len() {
+            self.overrun(data.len());
+            return Err(RbError::Full);
+        }
+
+        buf.store(self.inspector.size(), wr_pos, data);
+        self.inspector.write_pos.store(
+            (wr_pos + data.len()) % self.inspector.size(),
+            Ordering::Release,
+        );
+
+        self.notify_written(data.len());
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    fn write_blocking(&self, data: &[T]) -> Option<usize> {
+        match self.write_blocking_timeout(data, Duration::MAX) {
+            Ok(cnt) => cnt,
+            Err(RbError::Disconnected) => None,
+            Err(_) => panic!("Max duration should not time out"),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn write_blocking_timeout(&self, data: &[T], timeout: Duration) -> Result<Option<usize>> {
+        if data.is_empty() {
+            return Ok(None);
+        }
+
+        let start = Instant::now();
+        let mut buf = self.buf.lock();
+        // A wake-up doesn't guarantee a free slot, it might be spurious or another producer
+        // might have been faster, thus the state is checked again after every wait.
+        let (wr_pos, free) = loop {
+            // The last consumer might have been dropped while waiting.
+            self.check_connected()?;
+            let wr_pos = self.inspector.write_pos.load(Ordering::Relaxed);
+            let free = self.slots_free_from(wr_pos);
+            if free > 0 {
+                break (wr_pos, free);
+            }
+            let elapsed = start.elapsed();
+            if elapsed >= timeout {
+                return Err(RbError::TimedOut);
+            }
+            buf = self.wait_for(buf, 1, timeout - elapsed);
+        };
+        let cnt = cmp::min(data.len(), free);
+
+        buf.store(self.inspector.size(), wr_pos, &data[..cnt]);
+        self.inspector
+            .write_pos
+            .store((wr_pos + cnt) % self.inspector.size(), Ordering::Release);
+
+        self.notify_written(cnt);
+        Ok(Some(cnt))
+    }
+
+    #[cfg(feature = "std")]
+    fn write_blocking_deadline(&self, data: &[T], deadline: Instant) -> Result<Option<usize>> {
+        self.write_blocking_timeout(data, deadline.saturating_duration_since(Instant::now()))
+    }
+}
+
+impl<T: Clone, S: Storage<T>> RbConsumer<T> for Consumer<T, S> {
+    fn skip_pending(&self) -> Result<usize> {
+        let _guard = self.buf.lock();
+        let read_pos = self.inspector.read_pos.load(Ordering::Relaxed);
+        let write_pos = self.inspector.write_pos.load(Ordering::Acquire);
+        // Computed from the loaded position, the producer might have written more since.
+        let count = self.inspector.count_between(write_pos, read_pos);
+        if count == 0 {
+            Err(self.empty_error())
+        } else {
+            self.inspector.read_pos.store(write_pos, Ordering::Release);
+            self.notify_read(count);
+            Ok(count)
+        }
+    }
+
+    fn skip(&self, cnt: usize) -> Result<usize> {
+        let _guard = self.buf.lock();
+        let prev_read_pos = self.inspector.read_pos.load(Ordering::Relaxed);
+        let available = self.count_from(prev_read_pos);
+        if available == 0 {
+            Err(self.empty_error())
+        } else {
+            let count = cmp::min(cnt, available);
+            self.inspector.read_pos.store(
+                (prev_read_pos + count) % self.inspector.size(),
+                Ordering::Release,
+            );
+            self.notify_read(count);
+            Ok(count)
+        }
+    }
+
+    fn get(&self, data: &mut [T]) -> Result<usize> {
+        self.peek(0, data)
+    }
+
+    fn peek(&self, offset: usize, data: &mut [T]) -> Result<usize> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        // The positions are evaluated while holding the lock,
+        // this allows several consumers to share the buffer (see `MpmcRb`).
+        let buf = self.buf.lock();
+        let re_pos = self.inspector.read_pos.load(Ordering::Relaxed);
+        let available = self.count_from(re_pos).saturating_sub(offset);
+        let cnt = cmp::min(data.len(), available);
+        if cnt == 0 {
+            return Err(self.empty_error());
+        }
+
+        let pos = (re_pos + offset) % self.inspector.size();
+        copy_from_ring(buf.slots(), pos, &mut data[..cnt]);
+
+        Ok(cnt)
+    }
+
+    fn read(&self, data: &mut [T]) -> Result<usize> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        // The positions are evaluated while holding the lock,
+        // this allows several consumers to share the buffer (see `MpmcRb`).
+        let buf = self.buf.lock();
+        let re_pos = self.inspector.read_pos.load(Ordering::Relaxed);
+        let cnt = cmp::min(data.len(), self.count_from(re_pos));
+        if cnt == 0 {
+            return Err(self.underrun_error());
+        }
+
+        copy_from_ring(buf.slots(), re_pos, &mut data[..cnt]);
+
+        self.inspector
+            .read_pos
+            .store((re_pos + cnt) % self.inspector.size(), Ordering::Release);
+        self.notify_read(cnt);
+        Ok(cnt)
+    }
+
+    #[cfg(feature = "std")]
+    fn read_blocking(&self, data: &mut [T]) -> Option<usize> {
+        match self.read_blocking_timeout(data, Duration::MAX) {
+            Ok(cnt) => cnt,
+            Err(RbError::Disconnected) => None,
+            Err(_) => panic!("Max duration shouldn't time out"),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn read_blocking_timeout(&self, data: &mut [T], timeout: Duration) -> Result<Option<usize>> {
+        if data.is_empty() {
+            return Ok(None);
+        }
+
+        let start = Instant::now();
+        let mut buf = self.buf.lock();
+        // A wake-up doesn't guarantee pending values, it might be spurious or another consumer
+        // might have been faster, thus the state is checked again after every wait.
+        let (re_pos, cnt) = loop {
+            let re_pos = self.inspector.read_pos.load(Ordering::Relaxed);
+            let cnt = cmp::min(data.len(), self.count_from(re_pos));
+            if cnt > 0 {
+                break (re_pos, cnt);
+            }
+            if self.inspector.producers_disconnected() {
+                return Err(RbError::Disconnected);
+            }
+            let elapsed = start.elapsed();
+            if elapsed >= timeout {
+                return Err(RbError::TimedOut);
+            }
+            buf = self.wait_for(buf, 1, timeout - elapsed);
+        };
+
+        copy_from_ring(buf.slots(), re_pos, &mut data[..cnt]);
+
+        self.inspector
+            .read_pos
+            .store((re_pos + cnt) % self.inspector.size(), Ordering::Release);
+        self.notify_read(cnt);
+        Ok(Some(cnt))
+    }
+
+    #[cfg(feature = "std")]
+    fn read_blocking_deadline(&self, data: &mut [T], deadline: Instant) -> Result<Option<usize>> {
+        self.read_blocking_timeout(data, deadline.saturating_duration_since(Instant::now()))
+    }
+
+    #[cfg(feature = "std")]
+    fn read_at_least(&self, data: &mut [T], min: usize) -> Option<usize> {
+        if data.is_empty() {
+            return None;
+        }
+        let min = cmp::min(
+            cmp::max(min, 1),
+            cmp::min(data.len(), self.inspector.capacity()),
+        );
+
+        let mut buf = self.buf.lock();
+        let mut re_pos = self.inspector.read_pos.load(Ordering::Relaxed);
+        while self.count_from(re_pos) < min && !self.inspector.producers_disconnected() {
+            buf = self.wait_for(buf, min, Duration::MAX);
+            // Another consumer might have read while waiting.
+            re_pos = self.inspector.read_pos.load(Ordering::Relaxed);
+        }
+        let cnt = cmp::min(data.len(), self.count_from(re_pos));
+        if cnt == 0 {
+            return None;
+        }
+
+        copy_from_ring(buf.slots(), re_pos, &mut data[..cnt]);
+
+        self.inspector
+            .read_pos
+            .store((re_pos + cnt) % self.inspector.size(), Ordering::Release);
+        self.notify_read(cnt);
+        Some(cnt)
+    }
+}
+
+/// Moves up to `max` pending values from the buffer of `consumer` into the buffer of
+/// `producer`, as many as are pending and fit. The values are copied from the slots of one
+/// buffer into the slots of the other one directly, without an intermediate buffer.
+///
+/// Both buffers are locked while copying, in the same order regardless of the direction,
+/// thus transfers between two buffers in both directions can run concurrently.
+///
+/// Returns the number of moved values.
+///
+/// ```
+/// use rb::*;
+///
+/// let (input, output) = (SpscRb::new(8), SpscRb::new(2));
+/// let (input_prod, input_cons) = (input.producer(), input.consumer());
+/// let (output_prod, output_cons) = (output.producer(), output.consumer());
+/// input_prod.write(&[1, 2, 3]).unwrap();
+/// assert_eq!(transfer(&input_cons, &output_prod, 8).unwrap(), 2);
+/// assert_eq!(output_cons.read_vec(8), vec![1, 2]);
+/// assert_eq!(input_cons.read_vec(8), vec![3]);
+/// ```
+///
+/// Possible errors:
+///
+/// - `RbError::Disconnected` all consumers of the destination were dropped, or the source
+///   is empty and all of its producers were dropped
+/// - `RbError::Empty` the source is empty
+/// - `RbError::Full` the destination is full
+///
+/// # Panics
+///
+/// Panics if `consumer` and `producer` belong to the same buffer.
+pub fn transfer<T, S, D>(
+    consumer: &Consumer<T, S>,
+    producer: &Producer<T, D>,
+    max: usize,
+) -> Result<usize>
+where
+    T: Clone,
+    S: Storage<T>,
+    D: Storage<T>,
+{
+    let src_addr = Arc::as_ptr(&consumer.buf) as *const u8;
+    let dst_addr = Arc::as_ptr(&producer.buf) as *const u8;
+    assert!(src_addr != dst_addr, "the values are already in the buffer");
+    if max == 0 {
+        return Ok(0);
+    }
+    // Locked by address, two concurrent transfers in opposite directions can't deadlock.
+    let (src, mut dst) = if src_addr < dst_addr {
+        let src = consumer.buf.lock();
+        (src, producer.buf.lock())
+    } else {
+        let dst = producer.buf.lock();
+        (consumer.buf.lock(), dst)
+    };
+    producer.check_open()?;
+    let re_pos = consumer.inspector.read_pos.load(Ordering::Relaxed);
+    let pending = cmp::min(max, consumer.count_from(re_pos));
+    if pending == 0 {
+        return Err(consumer.empty_error());
+    }
+    let wr_pos = producer.inspector.write_pos.load(Ordering::Relaxed);
+    let cnt = cmp::min(pending, producer.slots_free_from(wr_pos));
+    if cnt == 0 {
+        return Err(RbError::Full);
+    }
+
+    // The pending values might wrap around the end of the source.
+    let (src_size, dst_size) = (consumer.inspector.size(), producer.inspector.size());
+    let head = cmp::min(cnt, src_size - re_pos);
+    dst.store(dst_size, wr_pos, &src.slots()[re_pos..re_pos + head]);
+    if head < cnt {
+        dst.store(
+            dst_size,
+            (wr_pos + head) % dst_size,
+            &src.slots()[..cnt - head],
+        );
+    }
+    producer
+        .inspector
+        .write_pos
+        .store((wr_pos + cnt) % dst_size, Ordering::Release);
+    consumer
+        .inspector
+        .read_pos
+        .store((re_pos + cnt) % src_size, Ordering::Release);
+
+    producer.notify_written(cnt);
+    consumer.notify_read(cnt);
+    Ok(cnt)
+}
+
+/// Free slots of the buffer reserved by `Producer::grant`.
+///
+/// Dereferences to the reserved slots and publishes them when it is dropped,
+/// use `commit` to publish only part of them.
+pub struct WriteGrant<'a, T: 'a, S: 'a + Storage<T>> {
+    buf: MutexGuard<'a, S>,
+    producer: &'a Producer<T, S>,
+    wr_pos: usize,
+    len: usize,
+}
+
+impl<'a, T, S: Storage<T>> WriteGrant<'a, T, S> {
+    /// Publishes the first `written` slots of the grant.
+    pub fn commit(mut self, written: usize) {
+        self.len = cmp::min(written, self.len);
+    }
+}
+
+impl<'a, T, S: Storage<T>> Deref for WriteGrant<'a, T, S> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.buf.slots()[self.wr_pos..self.wr_pos + self.len]
+    }
+}
+
+impl<'a, T: Default, S: Storage<T>> DerefMut for WriteGrant<'a, T, S> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        let size = self.producer.inspector.size();
+        &mut self.buf.slots_mut(size)[self.wr_pos..self.wr_pos + self.len]
+    }
+}
+
+impl<'a, T, S: Storage<T>> Drop for WriteGrant<'a, T, S> {
+    fn drop(&mut self) {
+        let inspector = &self.producer.inspector;
+        inspector.write_pos.store(
+            (self.wr_pos + self.len) % inspector.size(),
+            Ordering::Release,
+        );
+        self.producer.notify_written(self.len);
+    }
+}
+
+/// Pending values of the buffer granted by `Consumer::read_grant`.
+///
+/// Dereferences to the granted values and consumes them when it is dropped,
+/// use `release` to consume only part of them.
+pub struct ReadGrant<'a, T: 'a, S: 'a + Storage<T>> {
+    buf: MutexGuard<'a, S>,
+    consumer: &'a Consumer<T, S>,
+    re_pos: usize,
+    len: usize,
+}
+
+impl<'a, T, S: Storage<T>> ReadGrant<'a, T, S> {
+    /// Consumes the first `consumed` values of the grant.
+    pub fn release(mut self, consumed: usize) {
+        self.len = cmp::min(consumed, self.len);
+    }
+}
+
+impl<'a, T, S: Storage<T>> Deref for ReadGrant<'a, T, S> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.buf.slots()[self.re_pos..self.re_pos + self.len]
+    }
+}
+
+impl<'a, T, S: Storage<T>> Drop for ReadGrant<'a, T, S> {
+    fn drop(&mut self) {
+        let inspector = &self.consumer.inspector;
+        inspector.read_pos.store(
+            (self.re_pos + self.len) % inspector.size(),
+            Ordering::Release,
+        );
+        self.consumer.notify_read(self.len);
+    }
+}
+
+/// Iterator over the pending values of a buffer, created by `Consumer::iter_pending`.
+pub struct PendingIter<'a, T, S: 'a> {
+    buf: MutexGuard<'a, S>,
+    pos: usize,
+    remaining: usize,
+    size: usize,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: Clone, S: Storage<T>> Iterator for PendingIter<'a, T, S> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let value = self.buf.slots()[self.pos].clone();
+        self.pos = (self.pos + 1) % self.size;
+        self.remaining -= 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T: Clone, S: Storage<T>> ExactSizeIterator for PendingIter<'a, T, S> {}
@@ -0,0 +1,534 @@
+use core::cell::{Cell, UnsafeCell};
+use core::cmp;
+#[cfg(feature = "static-async")]
+use core::future::Future;
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+#[cfg(feature = "static-async")]
+use core::pin::Pin;
+use core::ptr;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+#[cfg(feature = "static-async")]
+use core::task::{Context, Poll};
+#[cfg(feature = "std")]
+use std::thread;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "static-async")]
+use atomic_waker::AtomicWaker;
+
+use {RbConsumer, RbError, RbInspector, RbProducer, Result};
+
+/// A *thread-safe* Single-Producer-Single-Consumer RingBuffer without heap allocations.
+///
+/// The `N` slots are stored inline and the buffer can be created in a `static`,
+/// e.g. to pass samples from an interrupt handler to the main loop.
+/// One slot is used to distinguish between the empty and full state, thus the capacity is `N - 1`.
+/// Buffers with less than two slots, which could never hold a value, fail to compile.
+///
+/// Producer and consumer are lock-free, hence the blocking calls spin and yield the thread
+/// until they can make progress. This is the only buffer available without the `std` feature.
+///
+/// With the `static-async` feature the handles can also wait as futures, which works without
+/// `std` and with any executor, e.g. an embassy task can await the samples that an interrupt
+/// handler writes.
+///
+/// ```
+/// use rb::*;
+///
+/// static RB: StaticSpscRb<u8, 64> = StaticSpscRb::new();
+///
+/// let (prod, cons) = RB.split().unwrap();
+/// assert!(RB.split().is_none());
+/// prod.write(&[1, 2, 3]).unwrap();
+/// let mut data = [0; 3];
+/// assert_eq!(cons.read(&mut data).unwrap(), 3);
+/// assert_eq!(data, [1, 2, 3]);
+/// ```
+pub struct StaticSpscRb<T, const N: usize> {
+    buf: UnsafeCell<[MaybeUninit<T>; N]>,
+    read_pos: AtomicUsize,
+    write_pos: AtomicUsize,
+    split: AtomicBool,
+    // Set when the respective handle is dropped or the buffer is closed.
+    producer_dropped: AtomicBool,
+    consumer_dropped: AtomicBool,
+    // Wakers of the tasks that wait for the futures of the consumer and the producer.
+    #[cfg(feature = "static-async")]
+    data_available: AtomicWaker,
+    #[cfg(feature = "static-async")]
+    slots_free: AtomicWaker,
+}
+
+// `split` hands out a single producer and consumer. The slots between the read and the write
+// position are only accessed by the consumer, all others only by the producer.
+unsafe impl<T: Send, const N: usize> Sync for StaticSpscRb<T, N> {}
+
+impl<T: Copy, const N: usize> StaticSpscRb<T, N> {
+    pub const fn new() -> Self {
+        const { assert!(N > 1, "the buffer must provide at least two slots") };
+        StaticSpscRb {
+            buf: UnsafeCell::new([MaybeUninit::uninit(); N]),
+            read_pos: AtomicUsize::new(0),
+            write_pos: AtomicUsize::new(0),
+            split: AtomicBool::new(false),
+            producer_dropped: AtomicBool::new(false),
+            consumer_dropped: AtomicBool::new(false),
+            #[cfg(feature = "static-async")]
+            data_available: AtomicWaker::new(),
+            #[cfg(feature = "static-async")]
+            slots_free: AtomicWaker::new(),
+        }
+    }
+
+    /// Returns the producer and consumer of the buffer.
+    ///
+    /// Only the first call succeeds, all further calls return `None`.
+    pub fn split<'a>(&'a self) -> Option<(StaticProducer<'a, T, N>, StaticConsumer<'a, T, N>)> {
+        if self.split.swap(true, Ordering::AcqRel) {
+            return None;
+        }
+        Some((
+            StaticProducer {
+                rb: self,
+                marker: PhantomData,
+            },
+            StaticConsumer {
+                rb: self,
+                marker: PhantomData,
+            },
+        ))
+    }
+
+    /// Closes the buffer, the blocked handles return and further writes fail with
+    /// `RbError::Disconnected`, as do reads once the pending values were read.
+    /// A closed buffer can't be reopened.
+    pub fn close(&self) {
+        self.producer_dropped.store(true, Ordering::Release);
+        self.consumer_dropped.store(true, Ordering::Release);
+        self.notify_written();
+        self.notify_read();
+    }
+
+    /// Returns the number of free slots for the given positions.
+    #[inline(always)]
+    fn slots_free_between(wr_pos: usize, re_pos: usize) -> usize {
+        if wr_pos < re_pos {
+            re_pos - wr_pos - 1
+        } else {
+            N - 1 - wr_pos + re_pos
+        }
+    }
+
+    /// Copies `data` into the slots starting at `wr_pos`.
+    ///
+    /// Only the producer may call this and `data` must fit into the free slots.
+    #[inline(always)]
+    fn copy_to_ring(&self, wr_pos: usize, data: &[T]) {
+        let first = cmp::min(data.len(), N - wr_pos);
+        let buf = self.buf.get() as *mut T;
+        // Safety: the free slots are not accessed by the consumer and both ranges are in bounds.
+        unsafe {
+            ptr::copy_nonoverlapping(data.as_ptr(), buf.add(wr_pos), first);
+            ptr::copy_nonoverlapping(data.as_ptr().add(first), buf, data.len() - first);
+        }
+    }
+
+    /// Copies the slots starting at `re_pos` into `data`.
+    ///
+    /// Only the consumer may call this and `data` must not be longer than the pending values.
+    #[inline(always)]
+    fn copy_from_ring(&self, re_pos: usize, data: &mut [T]) {
+        let first = cmp::min(data.len(), N - re_pos);
+        let buf = self.buf.get() as *const T;
+        // Safety: the pending slots were initialized by the producer and are not accessed by it
+        // until the read position is advanced, both ranges are in bounds.
+        unsafe {
+            ptr::copy_nonoverlapping(buf.add(re_pos), data.as_mut_ptr(), first);
+            ptr::copy_nonoverlapping(buf, data.as_mut_ptr().add(first), data.len() - first);
+        }
+    }
+}
+
+impl<T, const N: usize> StaticSpscRb<T, N> {
+    /// Wakes up the task that waits for the consumer, e.g. after a write.
+    #[inline(always)]
+    fn notify_written(&self) {
+        #[cfg(feature = "static-async")]
+        self.data_available.wake();
+    }
+
+    /// Wakes up the task that waits for the producer, e.g. after a read.
+    #[inline(always)]
+    fn notify_read(&self) {
+        #[cfg(feature = "static-async")]
+        self.slots_free.wake();
+    }
+}
+
+impl<T: Copy, const N: usize> Default for StaticSpscRb<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Copy, const N: usize> RbInspector for StaticSpscRb<T, N> {
+    fn is_empty(&self) -> bool {
+        self.slots_free() == self.capacity()
+    }
+    fn is_full(&self) -> bool {
+        self.slots_free() == 0
+    }
+    fn capacity(&self) -> usize {
+        N - 1
+    }
+    fn slots_free(&self) -> usize {
+        let wr_pos = self.write_pos.load(Ordering::Acquire);
+        let re_pos = self.read_pos.load(Ordering::Acquire);
+        Self::slots_free_between(wr_pos, re_pos)
+    }
+    fn count(&self) -> usize {
+        self.capacity() - self.slots_free()
+    }
+}
+
+/// Producer view into a `StaticSpscRb`.
+///
+/// It can be moved to another thread, but not shared between threads.
+pub struct StaticProducer<'a, T: 'a, const N: usize> {
+    rb: &'a StaticSpscRb<T, N>,
+    marker: PhantomData<Cell<()>>,
+}
+
+/// Consumer view into a `StaticSpscRb`.
+///
+/// It can be moved to another thread, but not shared between threads.
+pub struct StaticConsumer<'a, T: 'a, const N: usize> {
+    rb: &'a StaticSpscRb<T, N>,
+    marker: PhantomData<Cell<()>>,
+}
+
+impl<'a, T: Copy, const N: usize> StaticProducer<'a, T, N> {
+    /// Closes the buffer, see `StaticSpscRb::close`.
+    pub fn close(&self) {
+        self.rb.close();
+    }
+
+    /// Writes as many values of `data` as there are free slots,
+    /// the future waits until at least one slot is free.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Disconnected` the consumer was dropped
+    #[cfg(feature = "static-async")]
+    pub fn write_async<'b>(&'b mut self, data: &'b [T]) -> StaticWriteFuture<'a, 'b, T, N> {
+        StaticWriteFuture {
+            producer: self,
+            data,
+        }
+    }
+}
+
+/// Future returned by `StaticProducer::write_async`.
+#[cfg(feature = "static-async")]
+pub struct StaticWriteFuture<'a, 'b, T: 'a, const N: usize> {
+    producer: &'b StaticProducer<'a, T, N>,
+    data: &'b [T],
+}
+
+#[cfg(feature = "static-async")]
+impl<'a, 'b, T: Copy, const N: usize> Future for StaticWriteFuture<'a, 'b, T, N> {
+    type Output = Result<usize>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.producer.write(self.data) {
+            Err(RbError::Full) => {}
+            result => return Poll::Ready(result),
+        }
+        self.producer.rb.slots_free.register(cx.waker());
+        // The consumer might have read before the waker was registered.
+        match self.producer.write(self.data) {
+            Err(RbError::Full) => Poll::Pending,
+            result => Poll::Ready(result),
+        }
+    }
+}
+
+impl<'a, T, const N: usize> Drop for StaticProducer<'a, T, N> {
+    fn drop(&mut self) {
+        // Publishes the final write position along with the flag.
+        self.rb.producer_dropped.store(true, Ordering::Release);
+        self.rb.notify_written();
+    }
+}
+
+impl<'a, T, const N: usize> Drop for StaticConsumer<'a, T, N> {
+    fn drop(&mut self) {
+        self.rb.consumer_dropped.store(true, Ordering::Release);
+        self.rb.notify_read();
+    }
+}
+
+impl<'a, T: Copy, const N: usize> RbProducer<T> for StaticProducer<'a, T, N> {
+    fn write(&self, data: &[T]) -> Result<usize> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        if self.rb.consumer_dropped.load(Ordering::Acquire) {
+            return Err(RbError::Disconnected);
+        }
+        let wr_pos = self.rb.write_pos.load(Ordering::Relaxed);
+        let re_pos = self.rb.read_pos.load(Ordering::Acquire);
+        let cnt = cmp::min(
+            data.len(),
+            StaticSpscRb::<T, N>::slots_free_between(wr_pos, re_pos),
+        );
+        if cnt == 0 {
+            return Err(RbError::Full);
+        }
+        self.rb.copy_to_ring(wr_pos, &data[..cnt]);
+        self.rb
+            .write_pos
+            .store((wr_pos + cnt) % N, Ordering::Release);
+        self.rb.notify_written();
+        Ok(cnt)
+    }
+
+    fn write_exact(&self, data: &[T]) -> Result<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        if self.rb.consumer_dropped.load(Ordering::Acquire) {
+            return Err(RbError::Disconnected);
+        }
+        let wr_pos = self.rb.write_pos.load(Ordering::Relaxed);
+        let re_pos = self.rb.read_pos.load(Ordering::Acquire);
+        if StaticSpscRb::<T, N>::slots_free_between(wr_pos, re_pos) < data.len() {
+            return Err(RbError::Full);
+        }
+        self.rb.copy_to_ring(wr_pos, data);
+        self.rb
+            .write_pos
+            .store((wr_pos + data.len()) % N, Ordering::Release);
+        self.rb.notify_written();
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    fn write_blocking(&self, data: &[T]) -> Option<usize> {
+        match self.write_blocking_timeout(data, Duration::MAX) {
+            Ok(cnt) => cnt,
+            Err(RbError::Disconnected) => None,
+            Err(_) => panic!("Max duration should not time out"),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn write_blocking_timeout(&self, data: &[T], timeout: Duration) -> Result<Option<usize>> {
+        if data.is_empty() {
+            return Ok(None);
+        }
+        let start = Instant::now();
+        loop {
+            match self.write(data) {
+                Ok(cnt) => return Ok(Some(cnt)),
+                Err(RbError::Full) => {}
+                Err(err) => return Err(err),
+            }
+            if start.elapsed() >= timeout {
+                return Err(RbError::TimedOut);
+            }
+            thread::yield_now();
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn write_blocking_deadline(&self, data: &[T], deadline: Instant) -> Result<Option<usize>> {
+        self.write_blocking_timeout(data, deadline.saturating_duration_since(Instant::now()))
+    }
+}
+
+impl<'a, T: Copy, const N: usize> StaticConsumer<'a, T, N> {
+    /// Closes the buffer, see `StaticSpscRb::close`.
+    pub fn close(&self) {
+        self.rb.close();
+    }
+
+    /// Returns the read position and the number of pending values.
+    #[inline(always)]
+    fn pending(&self) -> (usize, usize) {
+        let re_pos = self.rb.read_pos.load(Ordering::Relaxed);
+        let wr_pos = self.rb.write_pos.load(Ordering::Acquire);
+        (
+            re_pos,
+            N - 1 - StaticSpscRb::<T, N>::slots_free_between(wr_pos, re_pos),
+        )
+    }
+
+    /// Returns the error for an empty buffer, which is `RbError::Disconnected` if the producer
+    /// was dropped or the buffer was closed and there is nothing left to read.
+    fn empty_error(&self) -> RbError {
+        if self.rb.producer_dropped.load(Ordering::Acquire) && self.pending().1 == 0 {
+            RbError::Disconnected
+        } else {
+            RbError::Empty
+        }
+    }
+
+    /// Fills `data` with as many values as are pending,
+    /// the future waits until at least one value is pending.
+    ///
+    /// ```
+    /// # extern crate futures_executor;
+    /// # extern crate rb;
+    /// use rb::*;
+    ///
+    /// static RB: StaticSpscRb<u16, 64> = StaticSpscRb::new();
+    ///
+    /// # fn main() {
+    /// let (prod, mut cons) = RB.split().unwrap();
+    /// // e.g. written by an interrupt handler
+    /// prod.write(&[1, 2, 3]).unwrap();
+    /// let mut data = [0; 8];
+    /// let cnt = futures_executor::block_on(cons.read_async(&mut data)).unwrap();
+    /// assert_eq!(data[..cnt], [1, 2, 3]);
+    /// # }
+    /// ```
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Disconnected` the producer was dropped and nothing is left to read
+    #[cfg(feature = "static-async")]
+    pub fn read_async<'b>(&'b mut self, data: &'b mut [T]) -> StaticReadFuture<'a, 'b, T, N> {
+        StaticReadFuture {
+            consumer: self,
+            data,
+        }
+    }
+}
+
+/// Future returned by `StaticConsumer::read_async`.
+#[cfg(feature = "static-async")]
+pub struct StaticReadFuture<'a, 'b, T: 'a, const N: usize> {
+    consumer: &'b StaticConsumer<'a, T, N>,
+    data: &'b mut [T],
+}
+
+#[cfg(feature = "static-async")]
+impl<'a, 'b, T: Copy, const N: usize> Future for StaticReadFuture<'a, 'b, T, N> {
+    type Output = Result<usize>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        match this.consumer.read(this.data) {
+            Err(RbError::Empty) => {}
+            result => return Poll::Ready(result),
+        }
+        this.consumer.rb.data_available.register(cx.waker());
+        // The producer might have written before the waker was registered.
+        match this.consumer.read(this.data) {
+            Err(RbError::Empty) => Poll::Pending,
+            result => Poll::Ready(result),
+        }
+    }
+}
+
+impl<'a, T: Copy, const N: usize> RbConsumer<T> for StaticConsumer<'a, T, N> {
+    fn skip_pending(&self) -> Result<usize> {
+        self.skip(N)
+    }
+
+    fn skip(&self, cnt: usize) -> Result<usize> {
+        let (re_pos, available) = self.pending();
+        if available == 0 {
+            return Err(self.empty_error());
+        }
+        let count = cmp::min(cnt, available);
+        self.rb
+            .read_pos
+            .store((re_pos + count) % N, Ordering::Release);
+        self.rb.notify_read();
+        Ok(count)
+    }
+
+    fn get(&self, data: &mut [T]) -> Result<usize> {
+        self.peek(0, data)
+    }
+
+    fn peek(&self, offset: usize, data: &mut [T]) -> Result<usize> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        let (re_pos, available) = self.pending();
+        let cnt = cmp::min(data.len(), available.saturating_sub(offset));
+        if cnt == 0 {
+            return Err(self.empty_error());
+        }
+        self.rb
+            .copy_from_ring((re_pos + offset) % N, &mut data[..cnt]);
+        Ok(cnt)
+    }
+
+    fn read(&self, data: &mut [T]) -> Result<usize> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        let (re_pos, available) = self.pending();
+        let cnt = cmp::min(data.len(), available);
+        if cnt == 0 {
+            return Err(self.empty_error());
+        }
+        self.rb.copy_from_ring(re_pos, &mut data[..cnt]);
+        self.rb
+            .read_pos
+            .store((re_pos + cnt) % N, Ordering::Release);
+        self.rb.notify_read();
+        Ok(cnt)
+    }
+
+    #[cfg(feature = "std")]
+    fn read_blocking(&self, data: &mut [T]) -> Option<usize> {
+        match self.read_blocking_timeout(data, Duration::MAX) {
+            Ok(cnt) => cnt,
+            Err(RbError::Disconnected) => None,
+            Err(_) => panic!("Max duration shouldn't time out"),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn read_blocking_timeout(&self, data: &mut [T], timeout: Duration) -> Result<Option<usize>> {
+        if data.is_empty() {
+            return Ok(None);
+        }
+        let start = Instant::now();
+        loop {
+            match self.read(data) {
+                Ok(cnt) => return Ok(Some(cnt)),
+                Err(RbError::Empty) => {}
+                Err(err) => return Err(err),
+            }
+            if start.elapsed() >= timeout {
+                return Err(RbError::TimedOut);
+            }
+            thread::yield_now();
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn read_blocking_deadline(&self, data: &mut [T], deadline: Instant) -> Result<Option<usize>> {
+        self.read_blocking_timeout(data, deadline.saturating_duration_since(Instant::now()))
+    }
+
+    #[cfg(feature = "std")]
+    fn read_at_least(&self, data: &mut [T], min: usize) -> Option<usize> {
+        if data.is_empty() {
+            return None;
+        }
+        let min = cmp::min(cmp::max(min, 1), cmp::min(data.len(), N - 1));
+        while self.pending().1 < min && !self.rb.producer_dropped.load(Ordering::Acquire) {
+            thread::yield_now();
+        }
+        self.read(data).ok()
+    }
+}
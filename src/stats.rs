@@ -0,0 +1,151 @@
+use std::cmp;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+/// Snapshot of the statistics of a buffer, see `SpscRb::with_stats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Number of values that were written.
+    pub written: usize,
+    /// Number of values that were read or skipped.
+    pub read: usize,
+    /// Maximum number of pending values that was observed after a write.
+    pub max_count: usize,
+    /// Number of times a producer waited for free slots.
+    pub producer_waits: usize,
+    /// Number of times a consumer waited for pending values.
+    pub consumer_waits: usize,
+}
+
+/// Collects the statistics of a buffer once enabled, otherwise all updates are no-ops.
+#[derive(Default)]
+pub(crate) struct StatsCollector {
+    enabled: AtomicBool,
+    // Number of samples per range of counts, set by `enable_histogram`.
+    histogram: OnceLock<Histogram>,
+    written: AtomicUsize,
+    read: AtomicUsize,
+    max_count: AtomicUsize,
+    producer_waits: AtomicUsize,
+    consumer_waits: AtomicUsize,
+}
+
+impl StatsCollector {
+    pub(crate) fn enable(&self) {
+        self.enabled.store(true, Ordering::Relaxed);
+    }
+
+    /// Enables the histogram of the counts, `capacity` is the capacity of the buffer.
+    pub(crate) fn enable_histogram(&self, bins: usize, capacity: usize) {
+        assert!(bins > 0, "the histogram needs at least one bin");
+        let histogram = Histogram {
+            bins: (0..bins).map(|_| AtomicUsize::new(0)).collect(),
+            capacity,
+        };
+        assert!(
+            self.histogram.set(histogram).is_ok(),
+            "the histogram is already enabled"
+        );
+    }
+
+    #[inline(always)]
+    fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Records a write of `cnt` values, `count` is the number of pending values afterwards.
+    #[inline(always)]
+    pub(crate) fn on_write(&self, cnt: usize, count: usize) {
+        if self.is_enabled() {
+            self.written.fetch_add(cnt, Ordering::Relaxed);
+            self.max_count.fetch_max(count, Ordering::Relaxed);
+        }
+        self.sample(count);
+    }
+
+    /// Records a read of `cnt` values, `count` is the number of pending values afterwards.
+    #[inline(always)]
+    pub(crate) fn on_read(&self, cnt: usize, count: usize) {
+        if self.is_enabled() {
+            self.read.fetch_add(cnt, Ordering::Relaxed);
+        }
+        self.sample(count);
+    }
+
+    #[inline(always)]
+    fn sample(&self, count: usize) {
+        if let Some(histogram) = self.histogram.get() {
+            histogram.sample(count);
+        }
+    }
+
+    /// Returns the number of samples per bin, empty unless the histogram is enabled.
+    pub(crate) fn histogram(&self) -> Vec<usize> {
+        self.histogram
+            .get()
+            .map(|histogram| {
+                histogram
+                    .bins
+                    .iter()
+                    .map(|bin| bin.load(Ordering::Relaxed))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[inline(always)]
+    pub(crate) fn on_producer_wait(&self) {
+        if self.is_enabled() {
+            self.producer_waits.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn on_consumer_wait(&self) {
+        if self.is_enabled() {
+            self.consumer_waits.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> Stats {
+        Stats {
+            written: self.written.load(Ordering::Relaxed),
+            read: self.read.load(Ordering::Relaxed),
+            max_count: self.max_count.load(Ordering::Relaxed),
+            producer_waits: self.producer_waits.load(Ordering::Relaxed),
+            consumer_waits: self.consumer_waits.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Resets all statistics, the maximum to the current number of pending values `count`.
+    pub(crate) fn reset(&self, count: usize) {
+        self.written.store(0, Ordering::Relaxed);
+        self.read.store(0, Ordering::Relaxed);
+        self.max_count.store(count, Ordering::Relaxed);
+        self.producer_waits.store(0, Ordering::Relaxed);
+        self.consumer_waits.store(0, Ordering::Relaxed);
+        if let Some(histogram) = self.histogram.get() {
+            for bin in histogram.bins.iter() {
+                bin.store(0, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+/// Counts how often the number of pending values fell into each of the equally sized
+/// ranges of `0..=capacity`.
+struct Histogram {
+    bins: Vec<AtomicUsize>,
+    capacity: usize,
+}
+
+impl Histogram {
+    #[inline(always)]
+    fn sample(&self, count: usize) {
+        // Computed in `u128`, `count * bins` might overflow for huge buffers.
+        let bin = count as u128 * self.bins.len() as u128 / (self.capacity as u128 + 1);
+        // Counts beyond the capacity are possible after `SpscRb::resize`.
+        let bin = cmp::min(bin as usize, self.bins.len() - 1);
+        self.bins[bin].fetch_add(1, Ordering::Relaxed);
+    }
+}
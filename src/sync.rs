@@ -0,0 +1,227 @@
+// Mutex and condition variable used by the blocking buffers, taken from `std::sync` or,
+// with the `parking_lot` feature, from `parking_lot`. Without `std` the mutex is a spin lock
+// and there is no condition variable, the buffers have no blocking calls then. The `futex`
+// feature replaces the condition variable by one that blocks on a futex directly, and `SpscRb` blocks on the
+// futex word `Occupancy` instead of a condition variable.
+// All variants share the same interface and locks are never poisoned, a panic while
+// holding the lock leaves the positions of the buffer in a consistent state anyway.
+
+#[cfg(not(feature = "std"))]
+pub(crate) use self::spin::{Mutex, MutexGuard};
+#[cfg(feature = "parking_lot")]
+pub(crate) use parking_lot::MutexGuard;
+#[cfg(all(feature = "std", not(feature = "parking_lot")))]
+pub(crate) use std::sync::MutexGuard;
+
+#[cfg(feature = "parking_lot")]
+#[derive(Default)]
+pub(crate) struct Mutex<T>(parking_lot::Mutex<T>);
+#[cfg(all(feature = "std", not(feature = "parking_lot")))]
+#[derive(Default)]
+pub(crate) struct Mutex<T>(std::sync::Mutex<T>);
+
+#[cfg(feature = "std")]
+impl<T> Mutex<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Mutex(From::from(value))
+    }
+
+    #[cfg(feature = "parking_lot")]
+    pub(crate) fn lock(&self) -> MutexGuard<'_, T> {
+        self.0.lock()
+    }
+
+    #[cfg(all(feature = "std", not(feature = "parking_lot")))]
+    pub(crate) fn lock(&self) -> MutexGuard<'_, T> {
+        self.0
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    not(all(feature = "futex", any(target_os = "linux", windows)))
+))]
+pub(crate) use self::condvar::Condvar;
+#[cfg(all(feature = "futex", any(target_os = "linux", windows)))]
+pub(crate) use futex::{Condvar, Occupancy};
+
+/// Without a futex the blocked handles wait on a condition variable instead.
+#[cfg(all(
+    feature = "std",
+    not(all(feature = "futex", any(target_os = "linux", windows)))
+))]
+pub(crate) struct Occupancy;
+
+#[cfg(all(
+    feature = "std",
+    not(all(feature = "futex", any(target_os = "linux", windows)))
+))]
+impl Occupancy {
+    pub(crate) fn new() -> Self {
+        Occupancy
+    }
+}
+
+/// Without `std` the handles never block, thus there is nobody to wake up.
+#[cfg(not(feature = "std"))]
+pub(crate) struct Signal;
+
+#[cfg(not(feature = "std"))]
+impl Signal {
+    pub(crate) fn new() -> Self {
+        Signal
+    }
+
+    #[inline(always)]
+    pub(crate) fn notify_all(&self) {}
+}
+
+#[cfg(all(
+    feature = "std",
+    not(all(feature = "futex", any(target_os = "linux", windows)))
+))]
+mod condvar {
+    use std::time::Duration;
+
+    use super::{Mutex, MutexGuard};
+
+    #[cfg(feature = "parking_lot")]
+    #[derive(Default)]
+    pub(crate) struct Condvar(parking_lot::Condvar);
+    #[cfg(not(feature = "parking_lot"))]
+    #[derive(Default)]
+    pub(crate) struct Condvar(std::sync::Condvar);
+
+    impl Condvar {
+        pub(crate) fn new() -> Self {
+            Condvar::default()
+        }
+
+        pub(crate) fn notify_all(&self) {
+            self.0.notify_all();
+        }
+
+        /// Blocks until the condition variable is notified.
+        #[cfg(feature = "parking_lot")]
+        pub(crate) fn wait<'a, T>(
+            &self,
+            _mutex: &'a Mutex<T>,
+            mut guard: MutexGuard<'a, T>,
+        ) -> MutexGuard<'a, T> {
+            self.0.wait(&mut guard);
+            guard
+        }
+
+        /// Blocks until the condition variable is notified.
+        #[cfg(not(feature = "parking_lot"))]
+        pub(crate) fn wait<'a, T>(
+            &self,
+            _mutex: &'a Mutex<T>,
+            guard: MutexGuard<'a, T>,
+        ) -> MutexGuard<'a, T> {
+            self.0
+                .wait(guard)
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+        }
+
+        /// Blocks until the condition variable is notified or `timeout` has elapsed.
+        /// Returns the guard and whether the wait timed out.
+        #[cfg(feature = "parking_lot")]
+        pub(crate) fn wait_timeout<'a, T>(
+            &self,
+            _mutex: &'a Mutex<T>,
+            mut guard: MutexGuard<'a, T>,
+            timeout: Duration,
+        ) -> (MutexGuard<'a, T>, bool) {
+            let timed_out = self.0.wait_for(&mut guard, timeout).timed_out();
+            (guard, timed_out)
+        }
+
+        /// Blocks until the condition variable is notified or `timeout` has elapsed.
+        /// Returns the guard and whether the wait timed out.
+        #[cfg(not(feature = "parking_lot"))]
+        pub(crate) fn wait_timeout<'a, T>(
+            &self,
+            _mutex: &'a Mutex<T>,
+            guard: MutexGuard<'a, T>,
+            timeout: Duration,
+        ) -> (MutexGuard<'a, T>, bool) {
+            let (guard, result) = self
+                .0
+                .wait_timeout(guard, timeout)
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            (guard, result.timed_out())
+        }
+    }
+}
+
+#[cfg(not(feature = "std"))]
+mod spin {
+    use core::cell::UnsafeCell;
+    use core::hint;
+    use core::ops::{Deref, DerefMut};
+    use core::sync::atomic::{AtomicBool, Ordering};
+
+    /// Spin lock for targets without an operating system. The handles only hold it while
+    /// they copy values, thus the other side spins briefly. It must not be taken in an
+    /// interrupt handler that preempts its holder, which would spin forever.
+    #[derive(Default)]
+    pub(crate) struct Mutex<T> {
+        locked: AtomicBool,
+        value: UnsafeCell<T>,
+    }
+
+    // Like `std::sync::Mutex`, the value is only accessed by the holder of the lock.
+    unsafe impl<T: Send> Send for Mutex<T> {}
+    unsafe impl<T: Send> Sync for Mutex<T> {}
+
+    impl<T> Mutex<T> {
+        pub(crate) fn new(value: T) -> Self {
+            Mutex {
+                locked: AtomicBool::new(false),
+                value: UnsafeCell::new(value),
+            }
+        }
+
+        pub(crate) fn lock(&self) -> MutexGuard<'_, T> {
+            while self
+                .locked
+                .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_err()
+            {
+                while self.locked.load(Ordering::Relaxed) {
+                    hint::spin_loop();
+                }
+            }
+            MutexGuard { mutex: self }
+        }
+    }
+
+    pub(crate) struct MutexGuard<'a, T: 'a> {
+        mutex: &'a Mutex<T>,
+    }
+
+    impl<'a, T> Deref for MutexGuard<'a, T> {
+        type Target = T;
+
+        fn deref(&self) -> &T {
+            // Safety: the guard holds the lock.
+            unsafe { &*self.mutex.value.get() }
+        }
+    }
+
+    impl<'a, T> DerefMut for MutexGuard<'a, T> {
+        fn deref_mut(&mut self) -> &mut T {
+            // Safety: the guard holds the lock and is borrowed exclusively.
+            unsafe { &mut *self.mutex.value.get() }
+        }
+    }
+
+    impl<'a, T> Drop for MutexGuard<'a, T> {
+        fn drop(&mut self) {
+            self.mutex.locked.store(false, Ordering::Release);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use std::cmp;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use {OverflowPolicy, Producer, RbError, RbProducer, Result, Storage};
+
+/// Copies every written slice into several buffers, e.g. for a capture thread that feeds
+/// a file writer and a live monitor. Unlike `BroadcastRb` the buffers are independent, each
+/// one has its own capacity and overflow policy.
+///
+/// Every buffer handles the values that don't fit according to its `OverflowPolicy`, the
+/// ones it did not store are added to its `shortfall`. With the default policy a full buffer
+/// doesn't hold back the others, unlike `OverflowPolicy::Block`. A buffer whose consumers
+/// were dropped is skipped.
+///
+/// ```
+/// use rb::*;
+///
+/// let (file, monitor) = (SpscRb::new(8), SpscRb::new(2));
+/// let tee = Tee::new(vec![file.producer(), monitor.producer()]);
+/// let (file_cons, monitor_cons) = (file.consumer(), monitor.consumer());
+/// // written into all buffers
+/// assert_eq!(tee.write(&[1, 2, 3]).unwrap(), 2);
+/// assert_eq!(tee.shortfall(1), 1);
+/// assert_eq!(file_cons.read_vec(8), vec![1, 2, 3]);
+/// assert_eq!(monitor_cons.read_vec(8), vec![1, 2]);
+/// ```
+pub struct Tee<T, S = Vec<T>> {
+    producers: Vec<Producer<T, S>>,
+    // Number of values that each buffer could not store.
+    shortfalls: Vec<AtomicUsize>,
+}
+
+impl<T: Clone, S: Storage<T>> Tee<T, S> {
+    /// Creates a tee that writes into the buffers of `producers`.
+    pub fn new(producers: Vec<Producer<T, S>>) -> Self {
+        let shortfalls = producers.iter().map(|_| AtomicUsize::new(0)).collect();
+        Tee {
+            producers,
+            shortfalls,
+        }
+    }
+
+    /// Adds the buffer of `producer`, its index is the number of buffers before.
+    pub fn push(&mut self, producer: Producer<T, S>) {
+        self.producers.push(producer);
+        self.shortfalls.push(AtomicUsize::new(0));
+    }
+
+    /// Returns the number of buffers.
+    pub fn len(&self) -> usize {
+        self.producers.len()
+    }
+
+    /// Returns true if the tee writes into no buffer at all.
+    pub fn is_empty(&self) -> bool {
+        self.producers.is_empty()
+    }
+
+    /// Writes `data` into every buffer whose consumers are connected, see `RbProducer::write`.
+    ///
+    /// Returns the number of values that all of these buffers stored.
+    ///
+    /// Possible errors:
+    ///
+    /// - `RbError::Disconnected` the consumers of all buffers were dropped
+    pub fn write(&self, data: &[T]) -> Result<usize> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        let mut stored = None;
+        for (producer, shortfall) in self.producers.iter().zip(&self.shortfalls) {
+            if producer.is_disconnected() {
+                continue;
+            }
+            let cnt = match write_stored(producer, data) {
+                Ok(cnt) => cnt,
+                Err(RbError::Disconnected) => continue,
+                Err(_) => 0,
+            };
+            if cnt < data.len() {
+                shortfall.fetch_add(data.len() - cnt, Ordering::Relaxed);
+            }
+            stored = Some(cmp::min(cnt, stored.unwrap_or(cnt)));
+        }
+        stored.ok_or(RbError::Disconnected)
+    }
+
+    /// Returns the number of values that the buffer at `index` could not store.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn shortfall(&self, index: usize) -> usize {
+        self.shortfalls[index].load(Ordering::Relaxed)
+    }
+
+    /// Returns the producers, e.g. to drop one of the buffers.
+    pub fn into_inner(self) -> Vec<Producer<T, S>> {
+        self.producers
+    }
+}
+
+/// Writes `data` like `RbProducer::write` but returns the number of values that were stored,
+/// the ones that `OverflowPolicy::DropNewest` discarded are not counted as written.
+fn write_stored<T: Clone, S: Storage<T>>(producer: &Producer<T, S>, data: &[T]) -> Result<usize> {
+    match producer.overflow_policy {
+        OverflowPolicy::DropNewest => producer
+            .write_dropping(data)
+            .map(|dropped| data.len() - dropped),
+        _ => producer.write(data),
+    }
+}
@@ -210,3 +210,120 @@ fn read_equals_get_and_skip() {
     assert_eq!(rb.count(), 0);
     assert_eq!(rb.slots_free(), 2);
 }
+
+#[test]
+fn clear_resets_positions() {
+    let rb = SpscRb::new(4);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    assert_eq!(producer.write(&[1, 2, 3]).unwrap(), 3);
+    let mut b = [0];
+    assert_eq!(consumer.read(&mut b).unwrap(), 1);
+    rb.clear();
+    match consumer.read(&mut b) {
+        Err(RbError::Empty) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    assert_eq!(producer.write(&[4, 5, 6, 7]).unwrap(), 4);
+    let mut c = [0; 4];
+    assert_eq!(consumer.read(&mut c).unwrap(), 4);
+    assert_eq!(c, [4, 5, 6, 7]);
+}
+
+#[test]
+fn clear_resets_slots() {
+    use std::rc::Rc;
+
+    let rb = SpscRb::new(4);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    let value = Rc::new(1);
+    assert_eq!(producer.write(&[value.clone(), value.clone()]).unwrap(), 2);
+    assert_eq!(consumer.read(&mut [Rc::default()]).unwrap(), 1);
+    rb.clear();
+    // both the read and the pending value are dropped
+    assert_eq!(Rc::strong_count(&value), 1);
+
+    let rb = ConstSpscRb::<u8, 4>::default();
+    let producer = rb.producer();
+    assert_eq!(producer.write(&[1, 2, 3]).unwrap(), 3);
+    rb.clear();
+    // the slots of a fixed-size storage are set to the default value
+    assert_eq!(*producer.grant(3).unwrap(), [0, 0, 0]);
+}
+
+#[test]
+fn reset_keeps_slots() {
+    use std::rc::Rc;
+
+    let rb = SpscRb::new(4);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    let value = Rc::new(1);
+    assert_eq!(producer.write(&[value.clone(), value.clone()]).unwrap(), 2);
+    assert_eq!(consumer.read(&mut [Rc::default()]).unwrap(), 1);
+    rb.reset();
+    assert_eq!(rb.count(), 0);
+    assert_eq!(rb.slots_free(), 4);
+    match consumer.read(&mut [Rc::default()]) {
+        Err(RbError::Empty) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    // the values are dropped once their slots are overwritten
+    assert_eq!(Rc::strong_count(&value), 3);
+    let data = [2, 3, 4, 5].map(Rc::new);
+    assert_eq!(producer.write(&data).unwrap(), 4);
+    // the second slot is the free one now
+    assert_eq!(Rc::strong_count(&value), 2);
+    let mut out = vec![Rc::default(); 4];
+    assert_eq!(consumer.read(&mut out).unwrap(), 4);
+    assert_eq!(out, data);
+}
+
+#[test]
+fn const_sized_wraps_around() {
+    let rb = ConstSpscRb::<u8, 4>::default();
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    assert_eq!(rb.capacity(), 3);
+    let mut b = [0; 3];
+    for i in 0..5 {
+        assert_eq!(producer.write(&[i, i + 1, i + 2, i + 3]).unwrap(), 3);
+        assert!(rb.is_full());
+        assert_eq!(consumer.read(&mut b).unwrap(), 3);
+        assert_eq!(b, [i, i + 1, i + 2]);
+    }
+    assert!(rb.is_empty());
+}
+
+#[test]
+fn clone_only_elements_are_dropped() {
+    use std::rc::Rc;
+
+    let rb = SpscRb::new(2);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    let value = Rc::new("frame".to_string());
+    assert_eq!(producer.write(&[value.clone(), value.clone()]).unwrap(), 2);
+    assert_eq!(Rc::strong_count(&value), 3);
+    let mut out = vec![Rc::default(), Rc::default()];
+    assert_eq!(consumer.read(&mut out).unwrap(), 2);
+    assert_eq!(*out[0], "frame");
+    assert_eq!(Rc::strong_count(&value), 5);
+    drop(out);
+    // the first slot of the buffer is overwritten by the second value
+    assert_eq!(producer.write(&[Rc::default(), Rc::default()]).unwrap(), 2);
+    assert_eq!(Rc::strong_count(&value), 2);
+    rb.clear();
+    assert_eq!(Rc::strong_count(&value), 1);
+}
+
+#[test]
+fn elements_without_default() {
+    #[derive(Clone, Debug, PartialEq)]
+    struct Frame(u8);
+
+    let rb = SpscRb::new(3);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    let mut out = vec![Frame(0); 2];
+    for i in 0..4 {
+        assert_eq!(producer.write(&[Frame(i), Frame(i + 1)]).unwrap(), 2);
+        assert_eq!(consumer.read(&mut out).unwrap(), 2);
+        assert_eq!(out, [Frame(i), Frame(i + 1)]);
+    }
+}
@@ -0,0 +1,396 @@
+use std::cell::UnsafeCell;
+use std::cmp;
+use std::hint;
+use std::sync::atomic::{self, AtomicU8, AtomicUsize, Ordering};
+#[cfg(any(feature = "async", all(feature = "readiness", unix)))]
+use std::sync::Arc;
+
+#[cfg(feature = "async")]
+use atomic_waker::AtomicWaker;
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+
+#[cfg(all(feature = "readiness", unix))]
+use readiness::Notifier;
+#[cfg(not(all(feature = "futex", any(target_os = "linux", windows))))]
+use sync::Condvar;
+use sync::{Mutex, MutexGuard, Occupancy};
+
+/// Determines how blocking calls wait for the other side of the buffer.
+///
+/// Real-time threads can avoid the syscalls of blocking on a condition variable by spinning,
+/// at the cost of burning CPU time while they wait.
+///
+/// ```
+/// use rb::*;
+///
+/// let rb = SpscRb::<f32>::new(1024).with_wait_strategy(WaitStrategy::SpinYield { spins: 100 });
+/// let (prod, cons) = (rb.producer(), rb.consumer());
+/// prod.write_blocking(&[0.5; 64]).unwrap();
+/// assert_eq!(cons.read_blocking(&mut [0.0; 64]), Some(64));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WaitStrategy {
+    /// Blocks on a condition variable until the other side makes progress.
+    ///
+    /// With the `futex` feature it blocks on the number of pending values directly instead,
+    /// without taking the buffer's lock until it can make progress.
+    #[default]
+    Block,
+    /// Busy-spins without ever giving up the CPU, for the lowest latency.
+    Spin,
+    /// Busy-spins `spins` times, then yields the thread between the checks.
+    SpinYield { spins: u32 },
+    /// Busy-spins `spins` times, then blocks like `Block`.
+    SpinBlock { spins: u32 },
+    /// Parks the thread until the other side unparks it.
+    ///
+    /// The waiting thread registers its `Thread` handle in one of the fixed slots of its side
+    /// and releases the buffer's lock while parked, the other side wakes it up without taking
+    /// a lock or allocating. Once all slots are taken, further waiting threads poll every
+    /// millisecond instead.
+    Park,
+}
+
+/// Number of threads per side that can be parked at once.
+const PARK_SLOTS: usize = 8;
+
+/// Interval at which a thread checks its condition if no slot was free to park it.
+const PARK_POLL: Duration = Duration::from_millis(1);
+
+const SLOT_EMPTY: u8 = 0;
+// Taken by a thread that registers, removes or unparks the thread of the slot.
+const SLOT_BUSY: u8 = 1;
+const SLOT_PARKED: u8 = 2;
+
+/// Slot for the `Thread` handle of a parked thread.
+#[derive(Default)]
+struct ParkSlot {
+    state: AtomicU8,
+    thread: UnsafeCell<Option<Thread>>,
+}
+
+// The handle is only accessed by the thread that moved the state to `SLOT_BUSY`.
+unsafe impl Sync for ParkSlot {}
+
+impl ParkSlot {
+    /// Moves the state from `from` to `SLOT_BUSY`, waiting for others that hold the slot.
+    /// Returns false if the slot is in another state.
+    fn acquire(&self, from: u8) -> bool {
+        loop {
+            match self.state.compare_exchange_weak(
+                from,
+                SLOT_BUSY,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return true,
+                // Held only for a few instructions by the other thread.
+                Err(SLOT_BUSY) => hint::spin_loop(),
+                Err(state) if state != from => return false,
+                Err(_) => {}
+            }
+        }
+    }
+
+    /// Moves the state from `SLOT_BUSY` to `to`, see `acquire`.
+    fn release(&self, to: u8) {
+        self.state.store(to, Ordering::Release);
+    }
+}
+
+/// Wakes up the threads waiting for one side of the buffer,
+/// whether they block on the condition variable or are parked,
+/// the tasks waiting for the futures of the async handles and the readiness handles.
+///
+/// There is no way to wake up a single thread: handles might be shared between threads
+/// and wait for different conditions, e.g. `read_at_least` with different minimums.
+/// A single wake-up could pick a thread that can't make progress and leave the others blocked.
+#[derive(Default)]
+pub(crate) struct Signal {
+    // The futex feature blocks on the `Occupancy` of the buffer instead.
+    #[cfg(not(all(feature = "futex", any(target_os = "linux", windows))))]
+    condvar: Condvar,
+    // Threads that wait with `WaitStrategy::Park`.
+    parked: [ParkSlot; PARK_SLOTS],
+    // Number of taken slots in `parked`, checked to skip them if no thread is parked.
+    parked_cnt: AtomicUsize,
+    // Waker slots of the async handles, one per handle.
+    #[cfg(feature = "async")]
+    wakers: Mutex<Vec<Arc<AtomicWaker>>>,
+    // Length of `wakers`, checked to skip its lock if there are no async handles.
+    #[cfg(feature = "async")]
+    wakers_cnt: AtomicUsize,
+    // File descriptors of the readiness handles.
+    #[cfg(all(feature = "readiness", unix))]
+    notifiers: Mutex<Vec<Arc<Notifier>>>,
+    // Length of `notifiers`, checked to skip its lock if there are no readiness handles.
+    #[cfg(all(feature = "readiness", unix))]
+    notifiers_cnt: AtomicUsize,
+}
+
+impl Signal {
+    pub(crate) fn new() -> Self {
+        Signal::default()
+    }
+
+    pub(crate) fn notify_all(&self) {
+        #[cfg(not(all(feature = "futex", any(target_os = "linux", windows))))]
+        self.condvar.notify_all();
+        self.unpark();
+        #[cfg(feature = "async")]
+        self.wake();
+        #[cfg(all(feature = "readiness", unix))]
+        self.notify_fds();
+    }
+
+    /// Adds the waker slot of an async handle, it's woken by every notification.
+    #[cfg(feature = "async")]
+    pub(crate) fn add_waker(&self, waker: Arc<AtomicWaker>) {
+        let mut wakers = self.wakers.lock();
+        wakers.push(waker);
+        self.wakers_cnt.store(wakers.len(), Ordering::Relaxed);
+        // Pairs with the fence in `wake`, like for parked threads.
+        atomic::fence(Ordering::SeqCst);
+    }
+
+    /// Removes the waker slot of an async handle.
+    #[cfg(feature = "async")]
+    pub(crate) fn remove_waker(&self, waker: &Arc<AtomicWaker>) {
+        let mut wakers = self.wakers.lock();
+        wakers.retain(|w| !Arc::ptr_eq(w, waker));
+        self.wakers_cnt.store(wakers.len(), Ordering::Relaxed);
+    }
+
+    /// Wakes the tasks that wait for the futures of the async handles.
+    #[cfg(feature = "async")]
+    fn wake(&self) {
+        atomic::fence(Ordering::SeqCst);
+        if self.wakers_cnt.load(Ordering::Relaxed) > 0 {
+            self.wakers.lock().iter().for_each(|w| w.wake());
+        }
+    }
+
+    /// Adds the file descriptor of a readiness handle, it's signaled by every notification.
+    #[cfg(all(feature = "readiness", unix))]
+    pub(crate) fn add_notifier(&self, notifier: Arc<Notifier>) {
+        let mut notifiers = self.notifiers.lock();
+        notifiers.push(notifier);
+        self.notifiers_cnt.store(notifiers.len(), Ordering::Relaxed);
+        atomic::fence(Ordering::SeqCst);
+    }
+
+    /// Removes the file descriptor of a readiness handle.
+    #[cfg(all(feature = "readiness", unix))]
+    pub(crate) fn remove_notifier(&self, notifier: &Arc<Notifier>) {
+        let mut notifiers = self.notifiers.lock();
+        notifiers.retain(|n| !Arc::ptr_eq(n, notifier));
+        self.notifiers_cnt.store(notifiers.len(), Ordering::Relaxed);
+    }
+
+    /// Signals the file descriptors of the readiness handles.
+    #[cfg(all(feature = "readiness", unix))]
+    fn notify_fds(&self) {
+        atomic::fence(Ordering::SeqCst);
+        if self.notifiers_cnt.load(Ordering::Relaxed) > 0 {
+            self.notifiers.lock().iter().for_each(|n| n.notify());
+        }
+    }
+
+    /// Unparks all parked threads, they check their condition again anyway.
+    fn unpark(&self) {
+        // Pairs with the fence in `park`, either the parked thread sees the new state
+        // or it's seen here.
+        atomic::fence(Ordering::SeqCst);
+        if self.parked_cnt.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        for slot in &self.parked {
+            if slot.acquire(SLOT_PARKED) {
+                // Safety: the slot is held and `SLOT_PARKED` means that it has a thread.
+                if let Some(thread) = unsafe { &*slot.thread.get() } {
+                    thread.unpark();
+                }
+                slot.release(SLOT_PARKED);
+            }
+        }
+    }
+
+    /// Parks the current thread until `ready` returns true or `timeout` has elapsed.
+    ///
+    /// Returns whether the wait timed out.
+    fn park<F>(&self, timeout: Duration, ready: F) -> bool
+    where
+        F: Fn() -> bool,
+    {
+        let start = Instant::now();
+        let slot = self.add_parked(thread::current());
+        let timed_out = loop {
+            if ready() {
+                break false;
+            }
+            let elapsed = start.elapsed();
+            if elapsed >= timeout {
+                break true;
+            }
+            park_timeout(slot.is_some(), timeout - elapsed);
+        };
+        if let Some(slot) = slot {
+            self.remove_parked(slot);
+        }
+        timed_out
+    }
+
+    /// Registers `thread` to be unparked by every notification, it has to check its condition
+    /// after the registration since earlier notifications are missed.
+    ///
+    /// Returns the index of its slot, `None` if all slots are taken and the thread has to poll.
+    pub(crate) fn add_parked(&self, thread: Thread) -> Option<usize> {
+        let idx = self.parked.iter().position(|slot| {
+            slot.state
+                .compare_exchange(SLOT_EMPTY, SLOT_BUSY, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+        })?;
+        let slot = &self.parked[idx];
+        // Safety: the slot is held.
+        unsafe { *slot.thread.get() = Some(thread) };
+        slot.release(SLOT_PARKED);
+        self.parked_cnt.fetch_add(1, Ordering::Relaxed);
+        // Pairs with the fence in `unpark`.
+        atomic::fence(Ordering::SeqCst);
+        Some(idx)
+    }
+
+    /// Removes the registration in the slot `idx` that `add_parked` returned.
+    pub(crate) fn remove_parked(&self, idx: usize) {
+        let slot = &self.parked[idx];
+        if slot.acquire(SLOT_PARKED) {
+            // Safety: the slot is held.
+            drop(unsafe { (*slot.thread.get()).take() });
+            slot.release(SLOT_EMPTY);
+            self.parked_cnt.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Parks the current thread for at most `timeout`, but only for `PARK_POLL` if it
+/// isn't registered in a slot since nobody unparks it then.
+pub(crate) fn park_timeout(registered: bool, timeout: Duration) {
+    if !registered {
+        thread::park_timeout(cmp::min(timeout, PARK_POLL));
+    } else if timeout == Duration::MAX {
+        thread::park();
+    } else {
+        thread::park_timeout(timeout);
+    }
+}
+
+impl WaitStrategy {
+    /// Waits until `ready` returns true or `timeout` has elapsed.
+    ///
+    /// `guard` is the lock of `buf`, it's released while waiting and reacquired before returning.
+    /// A blocking wait returns after the first wake-up of `signal`, the caller checks again.
+    /// With the `futex` feature it blocks on `occupancy` instead.
+    ///
+    /// Returns the guard and whether the wait timed out.
+    pub(crate) fn wait<'a, S, F>(
+        self,
+        buf: &'a Mutex<S>,
+        guard: MutexGuard<'a, S>,
+        signal: &Signal,
+        occupancy: &Occupancy,
+        timeout: Duration,
+        ready: F,
+    ) -> (MutexGuard<'a, S>, bool)
+    where
+        F: Fn() -> bool,
+    {
+        let max_spins = match self {
+            WaitStrategy::Block => {
+                return block(buf, Some(guard), signal, occupancy, timeout, ready)
+            }
+            WaitStrategy::Park => {
+                drop(guard);
+                let timed_out = signal.park(timeout, ready);
+                return (buf.lock(), timed_out);
+            }
+            WaitStrategy::Spin => u32::MAX,
+            WaitStrategy::SpinYield { spins } | WaitStrategy::SpinBlock { spins } => spins,
+        };
+        drop(guard);
+        let start = Instant::now();
+        let mut spins = 0u32;
+        loop {
+            if ready() {
+                return (buf.lock(), false);
+            }
+            let elapsed = start.elapsed();
+            if elapsed >= timeout {
+                return (buf.lock(), true);
+            }
+            if spins < max_spins {
+                spins += 1;
+                hint::spin_loop();
+            } else if let WaitStrategy::SpinBlock { .. } = self {
+                return block(buf, None, signal, occupancy, timeout - elapsed, ready);
+            } else {
+                thread::yield_now();
+            }
+        }
+    }
+}
+
+/// Waits for a single wake-up of the condition variable of `signal`.
+///
+/// `guard` is `None` if the caller doesn't hold the lock of `buf`.
+#[cfg(not(all(feature = "futex", any(target_os = "linux", windows))))]
+fn block<'a, S, F>(
+    buf: &'a Mutex<S>,
+    guard: Option<MutexGuard<'a, S>>,
+    signal: &Signal,
+    _occupancy: &Occupancy,
+    timeout: Duration,
+    ready: F,
+) -> (MutexGuard<'a, S>, bool)
+where
+    F: Fn() -> bool,
+{
+    let guard = match guard {
+        Some(guard) => guard,
+        None => {
+            let guard = buf.lock();
+            // The other side might have made progress before the lock was taken.
+            if ready() {
+                return (guard, false);
+            }
+            guard
+        }
+    };
+    if timeout == Duration::MAX {
+        // No need to call wait_timeout if the duration is max
+        (signal.condvar.wait(buf, guard), false)
+    } else {
+        signal.condvar.wait_timeout(buf, guard, timeout)
+    }
+}
+
+/// Blocks on `occupancy` until `ready` returns true, the lock of `buf` is only taken
+/// again once it does or `timeout` has elapsed.
+///
+/// `guard` is `None` if the caller doesn't hold the lock of `buf`.
+#[cfg(all(feature = "futex", any(target_os = "linux", windows)))]
+fn block<'a, S, F>(
+    buf: &'a Mutex<S>,
+    guard: Option<MutexGuard<'a, S>>,
+    _signal: &Signal,
+    occupancy: &Occupancy,
+    timeout: Duration,
+    ready: F,
+) -> (MutexGuard<'a, S>, bool)
+where
+    F: Fn() -> bool,
+{
+    drop(guard);
+    let timed_out = occupancy.wait(timeout, ready);
+    (buf.lock(), timed_out)
+}
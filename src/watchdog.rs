@@ -0,0 +1,182 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use spsc::Inspector;
+
+/// Number of xruns that a watchdog remembers by default, see `XrunWatchdog::with_history`.
+const DEFAULT_HISTORY: usize = 64;
+
+/// Kind of an xrun, see `SpscRb::overruns` and `SpscRb::underruns`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XrunKind {
+    /// Writes could not store all values, the consumer is too slow or the buffer too small.
+    Overrun,
+    /// Reads found the buffer empty, the producer is too slow or the buffer too small.
+    Underrun,
+}
+
+/// Xruns that a watchdog noticed by one poll.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Xrun {
+    /// Whether values were lost or missing.
+    pub kind: XrunKind,
+    /// Number of new xruns, for overruns the number of values that could not be stored.
+    pub count: usize,
+    /// Time of the poll that noticed them, they happened at most one poll interval before.
+    pub at: Instant,
+}
+
+/// Watches the overrun and underrun counters of a buffer and records when they increase,
+/// e.g. to tell the user that the buffer is too small, see `SpscRb::watchdog`.
+///
+/// The handles only increment the counters, the watchdog compares them in `poll`, thus
+/// all of its bookkeeping and the callback of `spawn` stay off the real-time thread.
+///
+/// ```
+/// use rb::*;
+///
+/// let rb = SpscRb::new(2);
+/// let mut watchdog = rb.watchdog();
+/// let (prod, cons) = (rb.producer(), rb.consumer());
+/// prod.write(&[1, 2, 3]).unwrap();
+/// let xruns = watchdog.poll();
+/// assert_eq!((xruns[0].kind, xruns[0].count), (XrunKind::Overrun, 1));
+/// assert!(watchdog.poll().is_empty());
+/// ```
+pub struct XrunWatchdog {
+    inspector: Arc<Inspector>,
+    // Counters of the buffer at the last poll.
+    overruns: usize,
+    underruns: usize,
+    total_overruns: usize,
+    total_underruns: usize,
+    history: VecDeque<Xrun>,
+    max_history: usize,
+}
+
+impl XrunWatchdog {
+    /// Creates a watchdog that reports the xruns after this point.
+    pub(crate) fn new(inspector: Arc<Inspector>) -> Self {
+        let (overruns, underruns) = inspector.xruns();
+        XrunWatchdog {
+            inspector,
+            overruns,
+            underruns,
+            total_overruns: 0,
+            total_underruns: 0,
+            history: VecDeque::new(),
+            max_history: DEFAULT_HISTORY,
+        }
+    }
+
+    /// Remembers the last `max_history` xruns instead of the last 64, see `history`.
+    pub fn with_history(mut self, max_history: usize) -> Self {
+        self.max_history = max_history;
+        self.trim_history();
+        self
+    }
+
+    /// Checks the counters of the buffer and returns the xruns since the last poll,
+    /// at most one of each kind.
+    ///
+    /// The counters are assumed to be reset if they decreased, see `SpscRb::reset_xruns`.
+    pub fn poll(&mut self) -> Vec<Xrun> {
+        let at = Instant::now();
+        let (overruns, underruns) = self.inspector.xruns();
+        let new = |now, last| if now >= last { now - last } else { now };
+        let xruns: Vec<Xrun> = [
+            (XrunKind::Overrun, new(overruns, self.overruns)),
+            (XrunKind::Underrun, new(underruns, self.underruns)),
+        ]
+        .iter()
+        .filter(|&&(_, count)| count > 0)
+        .map(|&(kind, count)| Xrun { kind, count, at })
+        .collect();
+        for xrun in &xruns {
+            match xrun.kind {
+                XrunKind::Overrun => self.total_overruns += xrun.count,
+                XrunKind::Underrun => self.total_underruns += xrun.count,
+            }
+        }
+        self.overruns = overruns;
+        self.underruns = underruns;
+        self.history.extend(&xruns);
+        self.trim_history();
+        xruns
+    }
+
+    /// Returns the xruns that were noticed so far, the oldest first.
+    pub fn history(&self) -> impl Iterator<Item = &Xrun> {
+        self.history.iter()
+    }
+
+    /// Returns the total number of noticed xruns of `kind`, including the ones that were
+    /// dropped from the history.
+    pub fn total(&self, kind: XrunKind) -> usize {
+        match kind {
+            XrunKind::Overrun => self.total_overruns,
+            XrunKind::Underrun => self.total_underruns,
+        }
+    }
+
+    /// Polls the buffer every `interval` on a new thread and calls `callback` for every
+    /// noticed xrun. The thread runs until the returned handle is stopped or dropped.
+    pub fn spawn<F>(mut self, interval: Duration, mut callback: F) -> WatchdogThread
+    where
+        F: FnMut(&Xrun) + Send + 'static,
+    {
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+        let handle = thread::spawn(move || {
+            while thread_running.load(Ordering::Relaxed) {
+                self.poll().iter().for_each(&mut callback);
+                thread::park_timeout(interval);
+            }
+            // The xruns up to the stop are reported as well.
+            self.poll().iter().for_each(&mut callback);
+            self
+        });
+        WatchdogThread {
+            running,
+            handle: Some(handle),
+        }
+    }
+
+    fn trim_history(&mut self) {
+        let excess = self.history.len().saturating_sub(self.max_history);
+        self.history.drain(..excess);
+    }
+}
+
+/// Handle of the thread of `XrunWatchdog::spawn`.
+pub struct WatchdogThread {
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<XrunWatchdog>>,
+}
+
+impl WatchdogThread {
+    /// Stops the thread and returns the watchdog, e.g. to inspect its history.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the callback panicked.
+    pub fn stop(mut self) -> XrunWatchdog {
+        self.join().expect("the watchdog thread panicked")
+    }
+
+    fn join(&mut self) -> Option<XrunWatchdog> {
+        self.running.store(false, Ordering::Relaxed);
+        let handle = self.handle.take()?;
+        handle.thread().unpark();
+        handle.join().ok()
+    }
+}
+
+impl Drop for WatchdogThread {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
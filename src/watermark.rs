@@ -0,0 +1,74 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use sync::Mutex;
+
+/// Watermark that the number of pending values crossed, see `SpscRb::on_watermark`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Watermark {
+    /// The number of pending values rose to the high watermark or above.
+    High,
+    /// The number of pending values fell to the low watermark or below.
+    Low,
+}
+
+struct Watcher {
+    low: usize,
+    high: usize,
+    // Last watermark that was crossed, the other one is reported next.
+    level: Watermark,
+    callback: Box<dyn FnMut(Watermark) + Send>,
+}
+
+/// Registry of the watermark callbacks of a buffer.
+#[derive(Default)]
+pub(crate) struct Watermarks {
+    watchers: Mutex<Vec<Watcher>>,
+    // Number of watchers, checked to skip the lock of buffers without any.
+    cnt: AtomicUsize,
+}
+
+impl Watermarks {
+    /// Registers `callback` for the given watermarks, `count` is the current number of
+    /// pending values.
+    pub(crate) fn register<F>(&self, low: usize, high: usize, count: usize, callback: F)
+    where
+        F: FnMut(Watermark) + Send + 'static,
+    {
+        assert!(
+            low < high,
+            "the low watermark must be below the high watermark"
+        );
+        let mut watchers = self.watchers.lock();
+        watchers.push(Watcher {
+            low,
+            high,
+            level: if count >= high {
+                Watermark::High
+            } else {
+                Watermark::Low
+            },
+            callback: Box::new(callback),
+        });
+        self.cnt.store(watchers.len(), Ordering::Relaxed);
+    }
+
+    /// Calls the callbacks whose watermark was crossed,
+    /// `count` is the number of pending values after a write or read.
+    #[inline(always)]
+    pub(crate) fn update(&self, count: usize) {
+        if self.cnt.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        for watcher in self.watchers.lock().iter_mut() {
+            let crossed = match watcher.level {
+                Watermark::Low if count >= watcher.high => Watermark::High,
+                Watermark::High if count <= watcher.low => Watermark::Low,
+                _ => continue,
+            };
+            watcher.level = crossed;
+            (watcher.callback)(crossed);
+        }
+    }
+}
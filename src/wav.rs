@@ -0,0 +1,155 @@
+use std::fs::File;
+use std::io::{BufWriter, Seek, Write};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use hound::{Sample, WavSpec, WavWriter};
+
+use {Consumer, RbConsumer, RbError};
+
+// Number of samples moved from the ring buffer into the writer at once.
+const BLOCK_SIZE: usize = 1024;
+// Upper bound for the time the drain thread needs to react on `flush` and `stop`.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+enum Command {
+    Flush(Sender<hound::Result<()>>),
+    Stop,
+}
+
+/// Records everything that flows through a consumer into a WAV file.
+///
+/// A background thread reads from the consumer and writes the samples
+/// into a `hound::WavWriter` until `stop` is called, the drain is dropped or
+/// all producers of the buffer were dropped.
+/// All samples that are pending at that point are still written before the
+/// WAV file is finalized.
+///
+/// ```no_run
+/// extern crate hound;
+/// extern crate rb;
+///
+/// use rb::{SpscRb, WavDrain, RB};
+///
+/// let rb = SpscRb::<f32>::new(4096);
+/// let spec = hound::WavSpec {
+///     channels: 1,
+///     sample_rate: 44100,
+///     bits_per_sample: 32,
+///     sample_format: hound::SampleFormat::Float,
+/// };
+/// let drain = WavDrain::create("out.wav", spec, rb.consumer()).unwrap();
+/// // ... write samples using `rb.producer()` ...
+/// drain.stop().unwrap();
+/// ```
+pub struct WavDrain {
+    commands: Sender<Command>,
+    handle: Option<JoinHandle<hound::Result<()>>>,
+}
+
+impl WavDrain {
+    /// Creates the WAV file at `path` and starts draining `consumer` into it.
+    pub fn create<T, P>(path: P, spec: WavSpec, consumer: Consumer<T>) -> hound::Result<Self>
+    where
+        T: Sample + Copy + Default + Send + 'static,
+        P: AsRef<Path>,
+    {
+        let writer = WavWriter::<BufWriter<File>>::create(path, spec)?;
+        Ok(WavDrain::new(writer, consumer))
+    }
+
+    /// Starts draining `consumer` into the given `writer`.
+    pub fn new<T, W>(writer: WavWriter<W>, consumer: Consumer<T>) -> Self
+    where
+        T: Sample + Copy + Default + Send + 'static,
+        W: Write + Seek + Send + 'static,
+    {
+        let (commands, rx) = mpsc::channel();
+        let handle = thread::spawn(move || drain(writer, consumer, rx));
+        WavDrain {
+            commands,
+            handle: Some(handle),
+        }
+    }
+
+    /// Blocks until all samples that were read so far are flushed to the underlying writer.
+    ///
+    /// Possible errors:
+    ///
+    /// - any error returned by `WavWriter::flush`
+    /// - `hound::Error::IoError` if the drain thread already stopped due to an error
+    pub fn flush(&self) -> hound::Result<()> {
+        let (reply, response) = mpsc::channel();
+        self.commands
+            .send(Command::Flush(reply))
+            .map_err(|_| stopped())?;
+        response.recv().map_err(|_| stopped())?
+    }
+
+    /// Writes all pending samples, finalizes the WAV file and joins the drain thread.
+    pub fn stop(mut self) -> hound::Result<()> {
+        self.join()
+    }
+
+    fn join(&mut self) -> hound::Result<()> {
+        match self.handle.take() {
+            Some(handle) => {
+                // The thread might already be gone if it failed, its error is returned below.
+                let _ = self.commands.send(Command::Stop);
+                handle.join().unwrap_or_else(|_| Err(stopped()))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for WavDrain {
+    fn drop(&mut self) {
+        let _ = self.join();
+    }
+}
+
+fn stopped() -> hound::Error {
+    hound::Error::IoError(::std::io::Error::new(
+        ::std::io::ErrorKind::BrokenPipe,
+        "WAV drain thread has stopped",
+    ))
+}
+
+fn drain<T, W>(
+    mut writer: WavWriter<W>,
+    consumer: Consumer<T>,
+    commands: Receiver<Command>,
+) -> hound::Result<()>
+where
+    T: Sample + Copy + Default,
+    W: Write + Seek,
+{
+    let mut buf = vec![T::default(); BLOCK_SIZE];
+    loop {
+        match consumer.read_blocking_timeout(&mut buf, POLL_INTERVAL) {
+            Ok(Some(cnt)) => {
+                for sample in &buf[..cnt] {
+                    writer.write_sample(*sample)?;
+                }
+            }
+            Err(RbError::Disconnected) => break,
+            _ => {}
+        }
+        match commands.try_recv() {
+            Ok(Command::Flush(reply)) => {
+                let _ = reply.send(writer.flush());
+            }
+            Ok(Command::Stop) | Err(TryRecvError::Disconnected) => break,
+            Err(TryRecvError::Empty) => {}
+        }
+    }
+    while let Ok(cnt) = consumer.read(&mut buf) {
+        for sample in &buf[..cnt] {
+            writer.write_sample(*sample)?;
+        }
+    }
+    writer.finalize()
+}
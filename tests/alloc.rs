@@ -0,0 +1,59 @@
+#![cfg(feature = "alloc")]
+
+extern crate rb;
+
+use rb::{ConstSpscRb, OverflowPolicy, RbConsumer, RbError, RbInspector, RbProducer, SpscRb, RB};
+
+#[test]
+fn test_alloc_spsc_rb_non_blocking() {
+    let rb = SpscRb::new(4);
+    let (prod, cons) = (rb.producer(), rb.consumer());
+    assert_eq!(prod.write(&[1, 2, 3, 4, 5]).unwrap(), 4);
+    assert!(rb.is_full());
+    assert!(matches!(prod.write_exact(&[6]), Err(RbError::Full)));
+
+    let mut data = [0; 3];
+    assert_eq!(cons.read(&mut data).unwrap(), 3);
+    assert_eq!(data, [1, 2, 3]);
+    assert_eq!(prod.write_overwriting(&[6, 7, 8]).unwrap(), 0);
+    assert_eq!(prod.write_overwriting(&[9]).unwrap(), 1);
+    assert_eq!(cons.read_vec(8), vec![6, 7, 8, 9]);
+    assert!(matches!(cons.read(&mut data), Err(RbError::Empty)));
+
+    drop((rb, prod));
+    assert!(matches!(cons.read(&mut data), Err(RbError::Disconnected)));
+}
+
+#[test]
+fn test_alloc_spsc_rb_overflow_policy_and_resize() {
+    let rb = SpscRb::new(2).with_overflow_policy(OverflowPolicy::Grow { max: 8 });
+    let (prod, cons) = (rb.producer(), rb.consumer());
+    assert_eq!(prod.write(&[1, 2, 3, 4, 5]).unwrap(), 5);
+    assert_eq!(rb.capacity(), 8);
+    rb.resize(5).unwrap();
+    assert_eq!(cons.read_vec(8), vec![1, 2, 3, 4, 5]);
+    rb.clear();
+    assert!(rb.is_empty());
+}
+
+#[test]
+fn test_alloc_const_spsc_rb_grants() {
+    let rb = ConstSpscRb::<u8, 5>::default();
+    let (prod, cons) = (rb.producer(), rb.consumer());
+    {
+        let mut grant = prod.grant(3).unwrap();
+        grant.copy_from_slice(&[1, 2, 3]);
+        grant.commit(2);
+    }
+    let grant = cons.read_grant().unwrap();
+    assert_eq!(*grant, [1, 2]);
+    grant.release(1);
+    assert_eq!(cons.read_vec(4), vec![2]);
+}
+
+#[test]
+#[cfg(not(feature = "std"))]
+#[should_panic(expected = "OverflowPolicy::Block requires the std feature")]
+fn test_alloc_spsc_rb_rejects_block() {
+    let _ = SpscRb::<u8>::new(4).with_overflow_policy(OverflowPolicy::Block);
+}
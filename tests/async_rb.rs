@@ -0,0 +1,102 @@
+#![cfg(feature = "async")]
+
+extern crate futures_executor;
+extern crate rb;
+#[cfg(feature = "tokio")]
+extern crate tokio;
+
+use std::thread;
+use std::time::Duration;
+
+use futures_executor::block_on;
+use rb::{RbError, RbProducer, SpscRb, RB};
+
+#[test]
+fn test_async_read_write() {
+    const SIZE: usize = 4;
+    let rb = SpscRb::new(SIZE);
+    let (mut consumer, mut producer) = (rb.consumer().into_async(), rb.producer().into_async());
+    let in_data = (0..64).collect::<Vec<_>>();
+    let expected = in_data.clone();
+    let handle = thread::spawn(move || {
+        let mut written = 0;
+        while written < in_data.len() {
+            written += block_on(producer.write(&in_data[written..])).unwrap();
+        }
+    });
+    let mut out_data = Vec::new();
+    let mut buf = [0; SIZE];
+    while let Ok(cnt) = block_on(consumer.read(&mut buf)) {
+        out_data.extend_from_slice(&buf[..cnt]);
+    }
+    handle.join().unwrap();
+    assert_eq!(out_data, expected);
+}
+
+#[test]
+fn test_async_read_from_blocking_producer() {
+    let rb = SpscRb::new(4);
+    let (mut consumer, producer) = (rb.consumer().into_async(), rb.producer());
+    let handle = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(10));
+        producer.write(&[1, 2]).unwrap();
+    });
+    let mut buf = [0; 4];
+    assert_eq!(block_on(consumer.read(&mut buf)).unwrap(), 2);
+    assert_eq!(buf[..2], [1, 2]);
+    handle.join().unwrap();
+    match block_on(consumer.read(&mut buf)) {
+        Err(RbError::Disconnected) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+}
+
+#[test]
+fn test_async_write_wakes_up_on_close() {
+    let rb = SpscRb::new(1);
+    let (_consumer, mut producer) = (rb.consumer(), rb.producer().into_async());
+    block_on(producer.write(&[1])).unwrap();
+    let handle = thread::spawn(move || block_on(producer.write(&[2])));
+    thread::sleep(Duration::from_millis(10));
+    rb.close();
+    match handle.join().unwrap() {
+        Err(RbError::Disconnected) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+}
+
+#[test]
+fn test_async_consumer_recreated() {
+    let rb = SpscRb::new(4);
+    let producer = rb.producer();
+    drop(rb.consumer().into_async());
+    let mut consumer = rb.consumer().into_async();
+    let reader = thread::spawn(move || block_on(consumer.read(&mut [0; 2])));
+    thread::sleep(Duration::from_millis(10));
+    // the waker slot of the dropped consumer is gone, the new one has its own
+    producer.write(&[1, 2]).unwrap();
+    assert_eq!(reader.join().unwrap().unwrap(), 2);
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn test_async_pipe() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let rb = SpscRb::new(16);
+    let (mut consumer, mut producer) = (rb.consumer().into_async(), rb.producer().into_async());
+    let in_data = (0..=255).collect::<Vec<u8>>();
+    let expected = in_data.clone();
+    let handle = thread::spawn(move || {
+        block_on(producer.write_all(&in_data)).unwrap();
+        block_on(producer.shutdown()).unwrap();
+        producer
+    });
+    let mut out_data = Vec::new();
+    assert_eq!(block_on(consumer.read_to_end(&mut out_data)).unwrap(), 256);
+    assert_eq!(out_data, expected);
+
+    let mut producer = handle.join().unwrap();
+    let err = block_on(AsyncWriteExt::write(&mut producer, &[1])).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::BrokenPipe);
+}
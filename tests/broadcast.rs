@@ -0,0 +1,111 @@
+#![cfg(feature = "std")]
+
+extern crate rb;
+
+use std::thread;
+use std::time::Duration;
+
+use rb::{BroadcastRb, RbConsumer, RbError, RbInspector, RbProducer};
+
+#[test]
+fn test_every_consumer_sees_every_element() {
+    const SIZE: usize = 8;
+    let rb = BroadcastRb::new(SIZE);
+    let (a, b) = (rb.consumer(), rb.consumer());
+    let producer = rb.producer();
+    let in_data = (0..SIZE).collect::<Vec<_>>();
+    assert_eq!(producer.write(&in_data).unwrap(), SIZE);
+    assert!(rb.is_full());
+
+    let mut out_a = vec![0; SIZE];
+    assert_eq!(a.read(&mut out_a).unwrap(), SIZE);
+    assert_eq!(out_a, in_data);
+    // `b` did not read yet, thus the producer must not overwrite any slot
+    assert!(rb.is_full());
+    assert_eq!(a.count(), 0);
+    assert_eq!(b.count(), SIZE);
+    match producer.write(&in_data) {
+        Err(RbError::Full) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+
+    let mut out_b = vec![0; SIZE];
+    assert_eq!(b.read(&mut out_b).unwrap(), SIZE);
+    assert_eq!(out_b, in_data);
+    assert!(rb.is_empty());
+}
+
+#[test]
+fn test_consumer_starts_at_write_position() {
+    let rb = BroadcastRb::new(4);
+    let producer = rb.producer();
+    let early = rb.consumer();
+    producer.write(&[1, 2]).unwrap();
+    let late = rb.consumer();
+    assert_eq!(late.count(), 0);
+    let cloned = early.clone();
+    assert_eq!(cloned.count(), 2);
+    producer.write(&[3]).unwrap();
+    let mut buf = [0; 4];
+    assert_eq!(late.read(&mut buf).unwrap(), 1);
+    assert_eq!(buf[0], 3);
+    assert_eq!(cloned.read(&mut buf).unwrap(), 3);
+    assert_eq!(&buf[..3], &[1, 2, 3]);
+}
+
+#[test]
+fn test_dropped_consumer_does_not_block_producer() {
+    let rb = BroadcastRb::new(2);
+    let producer = rb.producer();
+    let (a, b) = (rb.consumer(), rb.consumer());
+    producer.write(&[1, 2]).unwrap();
+    let mut buf = [0; 2];
+    a.read(&mut buf).unwrap();
+    assert!(rb.is_full());
+    drop(b);
+    assert!(rb.is_empty());
+    assert_eq!(producer.write(&[3, 4]).unwrap(), 2);
+}
+
+#[test]
+fn test_read_blocking_timeout_times_out() {
+    let rb = BroadcastRb::<u8>::new(2);
+    let consumer = rb.consumer();
+    let mut buf = [0; 2];
+    match consumer.read_blocking_timeout(&mut buf, Duration::from_millis(10)) {
+        Err(RbError::TimedOut) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+}
+
+#[test]
+fn test_threads_broadcast() {
+    const SIZE: usize = 64;
+    const TOTAL: usize = 10_000;
+    let rb = BroadcastRb::new(SIZE);
+    let producer = rb.producer();
+    let handles = (0..3)
+        .map(|_| {
+            let consumer = rb.consumer();
+            thread::spawn(move || {
+                let mut out = Vec::with_capacity(TOTAL);
+                let mut buf = [0; 16];
+                while out.len() < TOTAL {
+                    let cnt = consumer.read_blocking(&mut buf).unwrap();
+                    out.extend_from_slice(&buf[..cnt]);
+                }
+                out
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let in_data = (0..TOTAL).collect::<Vec<_>>();
+    let mut written = 0;
+    while written < TOTAL {
+        written += producer.write_blocking(&in_data[written..]).unwrap();
+    }
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), in_data);
+    }
+    assert!(rb.is_empty());
+}
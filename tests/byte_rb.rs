@@ -0,0 +1,83 @@
+#![cfg(feature = "std")]
+
+extern crate rb;
+
+use std::thread;
+
+use rb::{ByteRb, CapacityError};
+
+#[test]
+fn test_byte_rb_vectors() {
+    let (mut writer, mut reader) = ByteRb::new(4).split();
+    assert_eq!(writer.write_space(), 4);
+    assert_eq!(writer.write(b"abc"), 3);
+    let mut data = [0; 2];
+    assert_eq!(reader.read(&mut data), 2);
+    assert_eq!(&data, b"ab");
+
+    // the free slots wrap around the end of the buffer
+    {
+        let [head, tail] = writer.write_vector();
+        assert_eq!((head.len(), tail.len()), (2, 1));
+        head.copy_from_slice(b"de");
+        tail.copy_from_slice(b"f");
+    }
+    writer.write_advance(8);
+    assert_eq!((writer.write_space(), reader.read_space()), (0, 4));
+    assert_eq!(writer.write(b"g"), 0);
+
+    assert_eq!(reader.peek(&mut data), 2);
+    assert_eq!(&data, b"cd");
+    {
+        let [head, tail] = reader.read_vector();
+        assert_eq!((head, tail), (&b"cde"[..], &b"f"[..]));
+    }
+    reader.read_advance(3);
+    let mut data = [0; 4];
+    assert_eq!(reader.read(&mut data), 1);
+    assert_eq!(&data[..1], b"f");
+    assert_eq!(reader.read(&mut data), 0);
+}
+
+#[test]
+fn test_byte_rb_threads() {
+    let (mut writer, mut reader) = ByteRb::new(7).split();
+    let data: Vec<u8> = (0..10_000).map(|i| i as u8).collect();
+    let expected = data.clone();
+    let thread = thread::spawn(move || {
+        let mut written = 0;
+        while written < data.len() {
+            written += writer.write(&data[written..]);
+            thread::yield_now();
+        }
+    });
+    let mut received = Vec::new();
+    while received.len() < expected.len() {
+        let cnt = {
+            let [head, tail] = reader.read_vector();
+            received.extend_from_slice(head);
+            received.extend_from_slice(tail);
+            head.len() + tail.len()
+        };
+        reader.read_advance(cnt);
+        thread::yield_now();
+    }
+    thread.join().unwrap();
+    assert_eq!(received, expected);
+}
+
+#[test]
+fn test_byte_rb_try_new() {
+    assert_eq!(ByteRb::try_new(0).err(), Some(CapacityError::Zero));
+    // the additional slot doesn't fit
+    assert_eq!(
+        ByteRb::try_new(usize::MAX).err(),
+        Some(CapacityError::TooLarge)
+    );
+    assert_eq!(
+        ByteRb::try_new(isize::MAX as usize).err(),
+        Some(CapacityError::TooLarge)
+    );
+    let (writer, _) = ByteRb::try_new(3).unwrap().split();
+    assert_eq!(writer.write_space(), 3);
+}
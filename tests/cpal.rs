@@ -0,0 +1,53 @@
+#![cfg(feature = "cpal")]
+
+extern crate cpal;
+extern crate rb;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use cpal::{
+    InputCallbackInfo, InputStreamTimestamp, OutputCallbackInfo, OutputStreamTimestamp,
+    StreamInstant,
+};
+use rb::{input_callback, output_callback, RbConsumer, RbProducer, SpscRb};
+
+#[test]
+fn test_cpal_output_callback() {
+    let (producer, consumer) = SpscRb::new(8).into_split();
+    let xruns = Arc::new(AtomicUsize::new(0));
+    let mut callback = output_callback(consumer, xruns.clone());
+    let info = OutputCallbackInfo::new(OutputStreamTimestamp {
+        callback: StreamInstant::new(0, 0),
+        playback: StreamInstant::new(0, 0),
+    });
+    producer.write(&[1.0, 2.0, 3.0, 4.0]).unwrap();
+    let mut data = [9.0; 2];
+    callback(&mut data, &info);
+    assert_eq!(data, [1.0, 2.0]);
+    assert_eq!(xruns.load(Ordering::Relaxed), 0);
+    // underrun, the rest is silence
+    let mut data = [9.0; 4];
+    callback(&mut data, &info);
+    assert_eq!(data, [3.0, 4.0, 0.0, 0.0]);
+    assert_eq!(xruns.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn test_cpal_input_callback() {
+    let (producer, consumer) = SpscRb::new(4).into_split();
+    let xruns = Arc::new(AtomicUsize::new(0));
+    let mut callback = input_callback(producer, xruns.clone());
+    let info = InputCallbackInfo::new(InputStreamTimestamp {
+        callback: StreamInstant::new(0, 0),
+        capture: StreamInstant::new(0, 0),
+    });
+    callback(&[1.0, 2.0], &info);
+    assert_eq!(xruns.load(Ordering::Relaxed), 0);
+    // overrun, the samples that did not fit are dropped
+    callback(&[3.0, 4.0, 5.0], &info);
+    assert_eq!(xruns.load(Ordering::Relaxed), 1);
+    let mut data = [0.0; 8];
+    assert_eq!(consumer.read(&mut data).unwrap(), 4);
+    assert_eq!(data[..4], [1.0, 2.0, 3.0, 4.0]);
+}
@@ -0,0 +1,81 @@
+#![cfg(feature = "ffi")]
+
+extern crate rb;
+
+use std::ptr;
+
+use rb::ffi::*;
+
+#[test]
+fn test_ffi_u8() {
+    assert!(rb_u8_new(0).is_null());
+    let rb = rb_u8_new(4);
+    unsafe {
+        assert_eq!(rb_u8_slots_free(rb), 4);
+        assert_eq!(rb_u8_write(rb, b"hello".as_ptr(), 5), 4);
+        assert_eq!(rb_u8_count(rb), 4);
+        assert_eq!(rb_u8_write(rb, b"!".as_ptr(), 1), 0);
+        let mut data = [0; 8];
+        assert_eq!(rb_u8_read(rb, data.as_mut_ptr(), data.len()), 4);
+        assert_eq!(&data[..4], b"hell");
+        assert_eq!(rb_u8_read(rb, data.as_mut_ptr(), data.len()), 0);
+        assert_eq!(rb_u8_write(rb, ptr::null(), 0), 0);
+        rb_u8_free(rb);
+        rb_u8_free(ptr::null_mut());
+    }
+}
+
+#[test]
+fn test_ffi_f32() {
+    let rb = rb_f32_new(8);
+    unsafe {
+        assert_eq!(rb_f32_write(rb, [0.5; 3].as_ptr(), 3), 3);
+        assert_eq!(rb_f32_count(rb), 3);
+        assert_eq!(rb_f32_slots_free(rb), 5);
+        let mut data = [0.0; 2];
+        assert_eq!(rb_f32_read(rb, data.as_mut_ptr(), data.len()), 2);
+        assert_eq!(data, [0.5; 2]);
+        rb_f32_free(rb);
+    }
+}
+
+#[test]
+fn test_ffi_ringbuffer() {
+    assert!(rb_ringbuffer_create(0).is_null());
+    assert!(rb_ringbuffer_create(usize::MAX).is_null());
+    assert!(rb_ringbuffer_create(isize::MAX as usize).is_null());
+    let rb = rb_ringbuffer_create(4);
+    unsafe {
+        assert_eq!(rb_ringbuffer_write(rb, b"abc".as_ptr(), 3), 3);
+        let mut data = [0; 2];
+        assert_eq!(rb_ringbuffer_read(rb, data.as_mut_ptr(), 2), 2);
+        assert_eq!(rb_ringbuffer_write_space(rb), 3);
+
+        let mut vec = [
+            RbRingbufferData {
+                buf: ptr::null_mut(),
+                len: 0,
+            },
+            RbRingbufferData {
+                buf: ptr::null_mut(),
+                len: 0,
+            },
+        ];
+        rb_ringbuffer_get_write_vector(rb, vec.as_mut_ptr());
+        assert_eq!((vec[0].len, vec[1].len), (2, 1));
+        *vec[0].buf = b'd';
+        *vec[1].buf = b'e';
+        rb_ringbuffer_write_advance(rb, 3);
+        assert_eq!(rb_ringbuffer_read_space(rb), 4);
+
+        rb_ringbuffer_get_read_vector(rb, vec.as_mut_ptr());
+        assert_eq!((vec[0].len, vec[1].len), (3, 1));
+        assert_eq!(*vec[1].buf, b'e');
+        assert_eq!(rb_ringbuffer_peek(rb, data.as_mut_ptr(), 2), 2);
+        assert_eq!(&data, b"cd");
+        rb_ringbuffer_read_advance(rb, 4);
+        rb_ringbuffer_get_read_vector(rb, vec.as_mut_ptr());
+        assert!(vec[0].buf.is_null() && vec[1].buf.is_null());
+        rb_ringbuffer_free(rb);
+    }
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "std")]
+
+extern crate rb;
+
+use std::thread;
+
+use rb::{FrameRb, RbError};
+
+#[test]
+fn test_frame_rb_whole_frames() {
+    let (producer, consumer) = FrameRb::new(2, 3).split();
+    assert_eq!((producer.frame_len(), consumer.frame_len()), (2, 2));
+    let mut buf = [0; 5];
+    match consumer.read(&mut buf) {
+        Err(RbError::Empty) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    // too short for a single frame
+    assert_eq!(producer.write(&[1]).unwrap(), 0);
+    assert_eq!(producer.write(&[1, -1, 2]).unwrap(), 1);
+    assert_eq!(producer.frames_free(), 2);
+    // rounded down to the free frames
+    assert_eq!(producer.write(&[2, -2, 3, -3, 4, -4]).unwrap(), 2);
+    match producer.write(&[5, -5]) {
+        Err(RbError::Full) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+
+    assert_eq!(consumer.frames(), 3);
+    assert_eq!(consumer.read(&mut buf[..1]).unwrap(), 0);
+    assert_eq!(consumer.read(&mut buf).unwrap(), 2);
+    assert_eq!(buf, [1, -1, 2, -2, 0]);
+    assert_eq!(producer.write(&[5, -5, 6, -6]).unwrap(), 2);
+    assert_eq!(consumer.read(&mut buf).unwrap(), 2);
+    assert_eq!(buf, [3, -3, 5, -5, 0]);
+
+    drop(producer);
+    assert_eq!(consumer.read(&mut buf).unwrap(), 1);
+    match consumer.read(&mut buf) {
+        Err(RbError::Disconnected) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+}
+
+#[test]
+fn test_frame_rb_blocking() {
+    let (producer, consumer) = FrameRb::new(3, 2).split();
+    let handle = thread::spawn(move || {
+        let data: Vec<usize> = (0..3 * 50).map(|i| i % 3).collect();
+        let mut written = 0;
+        while written < data.len() {
+            written += 3 * producer.write_blocking(&data[written..]).unwrap();
+        }
+    });
+    // never a multiple of the frame length, the channels must not slip anyway
+    let mut buf = [9; 7];
+    let mut frames = 0;
+    while let Some(cnt) = consumer.read_blocking(&mut buf) {
+        for frame in buf[..3 * cnt].chunks(3) {
+            assert_eq!(frame, [0, 1, 2]);
+        }
+        frames += cnt;
+    }
+    assert_eq!(frames, 50);
+    handle.join().unwrap();
+}
@@ -0,0 +1,168 @@
+#![cfg(feature = "gstreamer")]
+
+extern crate gstreamer_app;
+extern crate rb;
+
+use std::time::Duration;
+
+use gstreamer_app::gst;
+use gstreamer_app::prelude::*;
+use gstreamer_app::{AppSink, AppSrc};
+use rb::{
+    connect_appsink, write_sample, AppSrcBridge, GstSample, RbConsumer, RbInspector, RbProducer,
+    SpscRb,
+};
+
+/// Returns the caps of a mono `f32` stream with a sample rate.
+fn f32_caps() -> gst::Caps {
+    let mut caps = f32::caps().unwrap();
+    caps.make_mut().set("rate", 48_000i32);
+    caps.make_mut().set("channels", 1i32);
+    caps
+}
+
+fn f32_sample(samples: &[f32], caps: &gst::Caps) -> gst::Sample {
+    let mut bytes = Vec::new();
+    f32::extend_bytes(samples, &mut bytes);
+    gst::Sample::builder()
+        .buffer(&gst::Buffer::from_mut_slice(bytes))
+        .caps(caps)
+        .build()
+}
+
+#[test]
+fn test_gstreamer_sample_bytes() {
+    let mut bytes = vec![0xff];
+    f32::extend_bytes(&[1.0, -0.5], &mut bytes);
+    assert_eq!(bytes.len(), 9);
+    // the samples are appended, a trailing partial sample is ignored
+    let mut samples = vec![2.0];
+    f32::extend_samples(&bytes[1..], &mut samples);
+    f32::extend_samples(&bytes[..3], &mut samples);
+    assert_eq!(samples, [2.0, 1.0, -0.5]);
+
+    let mut bytes = Vec::new();
+    u8::extend_bytes(b"ab", &mut bytes);
+    let mut samples = Vec::new();
+    u8::extend_samples(&bytes, &mut samples);
+    assert_eq!(samples, b"ab");
+}
+
+#[test]
+fn test_gstreamer_accepts_caps() {
+    gst::init().unwrap();
+    let s16 = gst::Caps::builder("audio/x-raw")
+        .field("format", "S16LE")
+        .build();
+    assert!(f32::accepts_caps(None));
+    assert!(f32::accepts_caps(Some(&f32_caps())));
+    assert!(!f32::accepts_caps(Some(&s16)));
+    // any stream can be handled as bytes
+    assert!(u8::caps().is_none());
+    assert!(u8::accepts_caps(Some(&s16)));
+}
+
+#[test]
+fn test_gstreamer_write_sample() {
+    gst::init().unwrap();
+    let (producer, consumer) = SpscRb::new(4).into_split();
+    let mut samples = Vec::new();
+    let sample = f32_sample(&[1.0, 2.0, 3.0], &f32_caps());
+    assert_eq!(
+        write_sample(&producer, &sample, &mut samples),
+        Ok(gst::FlowSuccess::Ok)
+    );
+    assert_eq!(consumer.read_vec(4), [1.0, 2.0, 3.0]);
+
+    let s16 = gst::Caps::builder("audio/x-raw")
+        .field("format", "S16LE")
+        .build();
+    let sample = f32_sample(&[4.0], &s16);
+    assert_eq!(
+        write_sample(&producer, &sample, &mut samples),
+        Err(gst::FlowError::NotNegotiated)
+    );
+    let sample = gst::Sample::builder().caps(&f32_caps()).build();
+    assert_eq!(
+        write_sample(&producer, &sample, &mut samples),
+        Err(gst::FlowError::Error)
+    );
+    assert!(consumer.is_empty());
+
+    // the samples that don't fit are written once the consumer has read
+    let sample = f32_sample(&[4.0, 5.0, 6.0, 7.0, 8.0, 9.0], &f32_caps());
+    let reader = std::thread::spawn(move || {
+        let mut data = Vec::new();
+        let mut buf = [0.0; 4];
+        while let Some(cnt) = consumer.read_blocking(&mut buf) {
+            data.extend_from_slice(&buf[..cnt]);
+        }
+        data
+    });
+    assert_eq!(
+        write_sample(&producer, &sample, &mut samples),
+        Ok(gst::FlowSuccess::Ok)
+    );
+    drop(producer);
+    assert_eq!(reader.join().unwrap(), [4.0, 5.0, 6.0, 7.0, 8.0, 9.0]);
+}
+
+#[test]
+fn test_gstreamer_write_sample_disconnected() {
+    gst::init().unwrap();
+    let (producer, consumer) = SpscRb::<f32>::new(2).into_split();
+    drop(consumer);
+    let sample = f32_sample(&[1.0, 2.0, 3.0], &f32_caps());
+    assert_eq!(
+        write_sample(&producer, &sample, &mut Vec::new()),
+        Err(gst::FlowError::Eos)
+    );
+}
+
+#[test]
+fn test_gstreamer_appsrc_appsink_round_trip() {
+    gst::init().unwrap();
+    let pipeline = gst::parse::launch("appsrc name=src ! appsink name=sink sync=false")
+        .unwrap()
+        .downcast::<gst::Pipeline>()
+        .unwrap();
+    let appsrc = pipeline
+        .by_name("src")
+        .unwrap()
+        .downcast::<AppSrc>()
+        .unwrap();
+    let appsink = pipeline
+        .by_name("sink")
+        .unwrap()
+        .downcast::<AppSink>()
+        .unwrap();
+    appsrc.set_caps(Some(&f32_caps()));
+
+    let (src_prod, src_cons) = SpscRb::new(64).into_split();
+    let (sink_prod, sink_cons) = SpscRb::new(64).into_split();
+    let bridge = AppSrcBridge::spawn(appsrc, src_cons, 16).unwrap();
+    connect_appsink(&appsink, sink_prod);
+    pipeline.set_state(gst::State::Playing).unwrap();
+
+    let in_data = (0..48).map(|i| i as f32).collect::<Vec<_>>();
+    assert_eq!(src_prod.write(&in_data).unwrap(), 48);
+    // the bridge signals end-of-stream once the producer is gone
+    drop(src_prod);
+    let bus = pipeline.bus().unwrap();
+    match bus.timed_pop_filtered(
+        gst::ClockTime::from_seconds(5),
+        &[gst::MessageType::Eos, gst::MessageType::Error],
+    ) {
+        Some(msg) => assert_eq!(msg.type_(), gst::MessageType::Eos),
+        None => panic!("the pipeline didn't reach end-of-stream"),
+    }
+    let mut out_data = vec![0.0; 64];
+    let cnt = sink_cons
+        .read_blocking_timeout(&mut out_data, Duration::from_secs(1))
+        .unwrap()
+        .unwrap();
+    assert_eq!(out_data[..cnt], in_data[..]);
+
+    bridge.stop();
+    pipeline.set_state(gst::State::Null).unwrap();
+}
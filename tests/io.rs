@@ -0,0 +1,180 @@
+#![cfg(feature = "std")]
+
+extern crate rb;
+
+use std::io::{self, BufRead, Read, Write};
+use std::thread;
+
+use rb::{pump_from, pump_into, RbConsumer, RbError, RbProducer, SpscRb, RB};
+
+#[test]
+fn test_io_copy_into_producer() {
+    const SIZE: usize = 16;
+    let rb = SpscRb::new(SIZE);
+    let (consumer, mut producer) = (rb.consumer(), rb.producer());
+    let in_data = (0..255).collect::<Vec<u8>>();
+    let expected = in_data.clone();
+    let handle = thread::spawn(move || {
+        assert_eq!(io::copy(&mut &in_data[..], &mut producer).unwrap(), 255);
+        Write::flush(&mut producer).unwrap();
+    });
+    let mut out_data = Vec::new();
+    let mut buf = [0; SIZE];
+    while out_data.len() < expected.len() {
+        let cnt = consumer.read_blocking(&mut buf).unwrap();
+        out_data.extend_from_slice(&buf[..cnt]);
+    }
+    handle.join().unwrap();
+    assert_eq!(out_data, expected);
+}
+
+#[test]
+fn test_io_read_from_consumer() {
+    const SIZE: usize = 16;
+    let rb = SpscRb::new(SIZE);
+    let (mut consumer, producer) = (rb.consumer(), rb.producer());
+    let in_data = (0..255).collect::<Vec<u8>>();
+    let expected = in_data.clone();
+    let handle = thread::spawn(move || {
+        for chunk in in_data.chunks(SIZE) {
+            producer.write_blocking(chunk).unwrap();
+        }
+    });
+    let mut out_data = Vec::new();
+    assert_eq!(
+        consumer
+            .by_ref()
+            .take(255)
+            .read_to_end(&mut out_data)
+            .unwrap(),
+        255
+    );
+    handle.join().unwrap();
+    assert_eq!(out_data, expected);
+
+    consumer.set_nonblocking(true);
+    let producer = rb.producer();
+    let mut buf = [0; 1];
+    let err = Read::read(&mut consumer, &mut buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+    // end of file once all producers and the buffer were dropped
+    drop((rb, producer));
+    assert_eq!(Read::read(&mut consumer, &mut buf).unwrap(), 0);
+}
+
+#[test]
+fn test_io_read_until() {
+    const SIZE: usize = 16;
+    let rb = SpscRb::new(SIZE);
+    let (mut consumer, producer) = (rb.consumer(), rb.producer());
+    let handle = thread::spawn(move || {
+        for line in &["a frame\n", "longer than the buffer\n", "last\n"] {
+            let mut data = line.as_bytes();
+            while !data.is_empty() {
+                let cnt = producer.write_blocking(data).unwrap();
+                data = &data[cnt..];
+            }
+        }
+    });
+    let mut lines = Vec::new();
+    for _ in 0..3 {
+        let mut line = String::new();
+        consumer.read_line(&mut line).unwrap();
+        lines.push(line);
+    }
+    handle.join().unwrap();
+    assert_eq!(lines, ["a frame\n", "longer than the buffer\n", "last\n"]);
+}
+
+#[test]
+fn test_frames() {
+    let rb = SpscRb::new(16);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    let mut frame = Vec::new();
+    match consumer.read_frame(&mut frame) {
+        Err(RbError::Empty) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    // frames wrap around the end of the buffer, which is shifted by every round
+    for i in 0..64u8 {
+        let data = (0..i % 11).map(|x| x.wrapping_mul(i)).collect::<Vec<_>>();
+        producer.write_frame(&data).unwrap();
+        assert_eq!(consumer.read_frame(&mut frame).unwrap(), data.len());
+        assert_eq!(frame, data);
+    }
+    producer.write_frame(&[1; 8]).unwrap();
+    // either the whole frame is written or nothing
+    match producer.write_frame(&[2; 8]) {
+        Err(RbError::Full) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    producer.write_frame(&[]).unwrap();
+    assert_eq!(consumer.read_frame(&mut frame).unwrap(), 8);
+    assert_eq!(consumer.read_frame(&mut frame).unwrap(), 0);
+    assert!(frame.is_empty());
+
+    drop((rb, producer));
+    match consumer.read_frame(&mut frame) {
+        Err(RbError::Disconnected) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+}
+
+#[test]
+fn test_pump() {
+    let (producer, consumer) = SpscRb::new(7).into_split();
+    let in_data = (0..=255).cycle().take(100_000).collect::<Vec<u8>>();
+    let expected = in_data.clone();
+    let handle = thread::spawn(move || pump_from(&in_data[..], &producer).unwrap());
+    let mut out_data = Vec::new();
+    // stops once the producer is dropped and the buffer is drained
+    assert_eq!(pump_into(&consumer, &mut out_data).unwrap(), 100_000);
+    assert_eq!(handle.join().unwrap(), 100_000);
+    assert_eq!(out_data, expected);
+}
+
+#[test]
+fn test_pump_stops_on_close() {
+    let rb = SpscRb::new(16);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    // an endless reader only stops once the buffer is closed
+    let handle = thread::spawn(move || pump_from(io::repeat(1), &producer).unwrap());
+    let mut data = [0; 8];
+    consumer.read_blocking(&mut data).unwrap();
+    consumer.close();
+    assert!(handle.join().unwrap() <= 16 + 8);
+
+    let rb = SpscRb::new(16);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    producer.write(b"abc").unwrap();
+    let handle = thread::spawn(move || {
+        let mut data = Vec::new();
+        let cnt = pump_into(&consumer, &mut data).unwrap();
+        (cnt, data)
+    });
+    producer.write_blocking(b"def").unwrap();
+    producer.close();
+    let (cnt, data) = handle.join().unwrap();
+    assert_eq!((cnt, &data[..]), (6, &b"abcdef"[..]));
+}
+
+#[test]
+fn test_pump_errors() {
+    struct Broken;
+
+    impl Write for Broken {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::Other.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let rb = SpscRb::new(16);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    producer.write(b"abc").unwrap();
+    let err = pump_into(&consumer, Broken).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Other);
+}
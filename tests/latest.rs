@@ -0,0 +1,43 @@
+#![cfg(feature = "std")]
+
+extern crate rb;
+
+use std::thread;
+
+use rb::LatestRb;
+
+#[test]
+fn test_latest_read_skips_stale_values() {
+    let (mut producer, mut consumer) = LatestRb::new(0).split();
+    assert!(!consumer.has_update());
+    assert_eq!(*consumer.read(), 0);
+    for i in 1..=5 {
+        producer.write(i);
+    }
+    assert!(consumer.has_update());
+    assert_eq!(*consumer.read(), 5);
+    assert_eq!(*consumer.read(), 5);
+    producer.write(6);
+    producer.write(7);
+    assert_eq!(*consumer.read(), 7);
+}
+
+#[test]
+fn test_latest_threads() {
+    const BLOCK: usize = 64;
+    let (mut producer, mut consumer) = LatestRb::new(vec![0usize; BLOCK]).split();
+    let handle = thread::spawn(move || {
+        for i in 1..=10_000 {
+            producer.write_with(|block| block.iter_mut().for_each(|x| *x = i));
+        }
+    });
+    let mut last = 0;
+    while last < 10_000 {
+        let block = consumer.read();
+        // a block is never torn and values never go back in time
+        assert!(block.iter().all(|&x| x == block[0]));
+        assert!(block[0] >= last);
+        last = block[0];
+    }
+    handle.join().unwrap();
+}
@@ -0,0 +1,54 @@
+#![cfg(feature = "symphonia")]
+
+extern crate rb;
+extern crate symphonia_core;
+
+use std::io::{Read, Seek, SeekFrom};
+use std::thread;
+
+use rb::{ConsumerSource, RbProducer, SpscRb, RB};
+use symphonia_core::io::{MediaSource, MediaSourceStream, MediaSourceStreamOptions, ReadBytes};
+
+#[test]
+fn test_source_is_unseekable() {
+    let rb = SpscRb::<u8>::new(16);
+    let mut source = ConsumerSource::new(rb.consumer());
+    assert!(!source.is_seekable());
+    assert_eq!(source.byte_len(), None);
+    assert!(source.seek(SeekFrom::Start(0)).is_err());
+}
+
+#[test]
+fn test_source_reads_blocking() {
+    let rb = SpscRb::<u8>::new(16);
+    let producer = rb.producer();
+    let mut source = ConsumerSource::new(rb.consumer());
+    thread::spawn(move || {
+        producer.write_blocking(&[1, 2, 3, 4]).unwrap();
+    });
+    let mut buf = [0u8; 4];
+    source.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [1, 2, 3, 4]);
+}
+
+#[test]
+fn test_media_source_stream() {
+    const SIZE: usize = 32;
+    let rb = SpscRb::<u8>::new(SIZE);
+    let producer = rb.producer();
+    let in_data = (0..4 * SIZE).map(|i| i as u8).collect::<Vec<_>>();
+    let in_data_copy = in_data.clone();
+    thread::spawn(move || {
+        for chunk in in_data_copy.chunks(SIZE / 2) {
+            let mut written = 0;
+            while written < chunk.len() {
+                written += producer.write_blocking(&chunk[written..]).unwrap();
+            }
+        }
+    });
+    let source = ConsumerSource::new(rb.consumer());
+    let mut mss = MediaSourceStream::new(Box::new(source), MediaSourceStreamOptions::default());
+    let mut out_data = vec![0u8; in_data.len()];
+    ReadBytes::read_buf_exact(&mut mss, &mut out_data).unwrap();
+    assert_eq!(in_data, out_data);
+}
@@ -0,0 +1,75 @@
+#![cfg(feature = "std")]
+
+extern crate rb;
+
+use std::thread;
+
+use rb::{MessageRb, RbError};
+
+#[test]
+fn test_message_boundaries() {
+    let (producer, consumer) = MessageRb::new(8).split();
+    match consumer.read_vec() {
+        Err(RbError::Empty) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    producer.write(&[1, 2, 3]).unwrap();
+    producer.write(&[]).unwrap();
+    producer.write(&[4, 5, 6, 7]).unwrap();
+    // either the whole message is written or nothing
+    match producer.write(&[8, 9]) {
+        Err(RbError::Full) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    match producer.write_blocking(&[0; 9]) {
+        Err(RbError::Full) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+
+    assert_eq!(consumer.next_len(), Some(3));
+    let mut buf = [0; 8];
+    assert_eq!(consumer.read(&mut buf).unwrap(), 3);
+    assert_eq!(buf[..3], [1, 2, 3]);
+    assert_eq!(consumer.read_vec().unwrap(), Vec::<i32>::new());
+    assert_eq!(consumer.read_vec().unwrap(), vec![4, 5, 6, 7]);
+    assert_eq!(consumer.next_len(), None);
+
+    drop(producer);
+    match consumer.read(&mut buf) {
+        Err(RbError::Disconnected) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+}
+
+#[test]
+fn test_message_read_into_short_buffer() {
+    let (producer, consumer) = MessageRb::new(8).split();
+    producer.write(&[1, 2, 3]).unwrap();
+    match consumer.read(&mut [0; 2]) {
+        Err(RbError::TooShort) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    // the message is still pending
+    assert_eq!(consumer.next_len(), Some(3));
+    assert_eq!(consumer.read_vec().unwrap(), vec![1, 2, 3]);
+}
+
+#[test]
+fn test_message_threads() {
+    let (producer, consumer) = MessageRb::new(16).split();
+    let handle = thread::spawn(move || {
+        for len in 0..100 {
+            let msg = vec![len; len % 16];
+            producer.write_blocking(&msg).unwrap();
+        }
+    });
+    let mut buf = [0; 16];
+    let mut received = 0;
+    while let Some(len) = consumer.read_blocking(&mut buf) {
+        assert_eq!(len, received % 16);
+        assert!(buf[..len].iter().all(|&x| x == received));
+        received += 1;
+    }
+    handle.join().unwrap();
+    assert_eq!(received, 100);
+}
@@ -0,0 +1,94 @@
+#![cfg(all(feature = "mmap", unix))]
+
+extern crate rb;
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process;
+
+use rb::{MmapRb, RbError, RbInspector};
+
+fn temp_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("rb-test-{}-{}", name, process::id()))
+}
+
+#[test]
+fn test_mmap_write_read() {
+    let path = temp_path("write-read");
+    let mut rb = MmapRb::create(&path, 4).unwrap();
+    assert_eq!(rb.capacity(), 4);
+    assert!(rb.is_empty());
+    assert_eq!(rb.write(&[1, 2, 3, 4, 5]).unwrap(), 4);
+    assert!(rb.is_full());
+    match rb.write(&[6]) {
+        Err(RbError::Full) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    let mut data = [0; 3];
+    assert_eq!(rb.read(&mut data).unwrap(), 3);
+    assert_eq!(data, [1, 2, 3]);
+    // wraps around the end of the slots
+    assert_eq!(rb.write(&[5, 6, 7]).unwrap(), 3);
+    assert_eq!(rb.tail(2), [6, 7]);
+    assert_eq!(rb.tail(8), [4, 5, 6, 7]);
+    let mut data = [0; 8];
+    assert_eq!(rb.read(&mut data).unwrap(), 4);
+    assert_eq!(data[..4], [4, 5, 6, 7]);
+    match rb.read(&mut data) {
+        Err(RbError::Empty) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_mmap_reopen() {
+    let path = temp_path("reopen");
+    let mut rb = MmapRb::create(&path, 4).unwrap();
+    rb.write(&[1, 2, 3]).unwrap();
+    rb.read(&mut [0]).unwrap();
+    assert_eq!(rb.write_overwriting(&[4, 5, 6, 7, 8]), 3);
+    rb.flush().unwrap();
+    drop(rb);
+
+    let mut rb = MmapRb::open(&path).unwrap();
+    assert_eq!(rb.capacity(), 4);
+    let mut data = [0; 4];
+    assert_eq!(rb.read(&mut data).unwrap(), 4);
+    assert_eq!(data, [5, 6, 7, 8]);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_mmap_locked() {
+    let path = temp_path("locked");
+    let mut rb = MmapRb::create(&path, 4).unwrap();
+    rb.write(&[1, 2]).unwrap();
+    for other in [MmapRb::open(&path), MmapRb::create(&path, 4)] {
+        match other {
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {}
+            Err(err) => panic!("incorrect error: {:?}", err),
+            Ok(_) => panic!("no error"),
+        }
+    }
+    // the failed create didn't truncate the file
+    assert_eq!(rb.tail(4), [1, 2]);
+    drop(rb);
+    assert_eq!(MmapRb::open(&path).unwrap().tail(4), [1, 2]);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_mmap_invalid_file() {
+    let path = temp_path("invalid");
+    fs::write(&path, [0; 64]).unwrap();
+    match MmapRb::open(&path) {
+        Err(ref err) if err.kind() == io::ErrorKind::InvalidData => {}
+        Err(err) => panic!("incorrect error: {:?}", err),
+        Ok(_) => panic!("no error"),
+    }
+    fs::remove_file(&path).unwrap();
+    assert!(MmapRb::create(&path, 0).is_err());
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "std")]
+
+extern crate rb;
+
+use std::thread;
+use std::time::Duration;
+
+use rb::{PlanarRb, RbError, RbInspector};
+
+#[test]
+fn test_planar_lanes_stay_aligned() {
+    let rb = PlanarRb::new(2, 4);
+    let (producer, consumer) = (rb.producer(), rb.consumer());
+    assert_eq!(rb.channels(), 2);
+    assert_eq!(rb.capacity(), 4);
+    // the shortest channel determines the number of frames
+    assert_eq!(producer.write(&[&[1, 2, 3], &[-1, -2]]).unwrap(), 2);
+    assert_eq!(producer.write(&[&[3, 4, 5], &[-3, -4, -5]]).unwrap(), 2);
+    assert!(rb.is_full());
+    match producer.write(&[&[6], &[-6]]) {
+        Err(RbError::Full) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+
+    let (mut left, mut right) = ([0; 3], [0; 3]);
+    assert_eq!(consumer.read(&mut [&mut left, &mut right]).unwrap(), 3);
+    assert_eq!((left, right), ([1, 2, 3], [-1, -2, -3]));
+    // the lanes wrap around their end
+    assert_eq!(producer.write(&[&[5, 6], &[-5, -6]]).unwrap(), 2);
+    assert_eq!(rb.count(), 3);
+    assert_eq!(consumer.read(&mut [&mut left, &mut right]).unwrap(), 3);
+    assert_eq!((left, right), ([4, 5, 6], [-4, -5, -6]));
+    match consumer.read(&mut [&mut left, &mut right]) {
+        Err(RbError::Empty) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_planar_channel_mismatch() {
+    let rb = PlanarRb::new(2, 4);
+    let _ = rb.producer().write(&[&[1, 2]]);
+}
+
+#[test]
+fn test_planar_blocking() {
+    let rb = PlanarRb::new(2, 2);
+    let (producer, consumer) = (rb.producer(), rb.consumer());
+    let handle = thread::spawn(move || {
+        let (left, right) = ([1.0f32; 64], [-1.0f32; 64]);
+        let mut written = 0;
+        while written < left.len() {
+            written += producer
+                .write_blocking(&[&left[written..], &right[written..]])
+                .unwrap();
+        }
+    });
+    let (mut left, mut right) = ([0.0; 3], [0.0; 3]);
+    let mut read = 0;
+    while let Some(cnt) = consumer.read_blocking(&mut [&mut left, &mut right]) {
+        assert_eq!(
+            (left[..cnt].to_vec(), right[..cnt].to_vec()),
+            (vec![1.0; cnt], vec![-1.0; cnt])
+        );
+        read += cnt;
+    }
+    assert_eq!(read, 64);
+    handle.join().unwrap();
+    match consumer.read_blocking_timeout(&mut [&mut left, &mut right], Duration::from_millis(1)) {
+        Err(RbError::Disconnected) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+}
@@ -0,0 +1,44 @@
+#![cfg(feature = "pulseaudio")]
+
+extern crate rb;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use rb::{capture_callback, playback_callback, RbConsumer, RbProducer, SpscRb};
+
+fn to_bytes(samples: &[f32]) -> Vec<u8> {
+    samples.iter().flat_map(|s| s.to_ne_bytes()).collect()
+}
+
+#[test]
+fn test_pulseaudio_playback_callback() {
+    let (producer, consumer) = SpscRb::new(8).into_split();
+    let xruns = Arc::new(AtomicUsize::new(0));
+    let mut callback = playback_callback(consumer, xruns.clone());
+    producer.write(&[1.0, 2.0, 3.0, 4.0]).unwrap();
+    let mut bytes = [9; 8];
+    callback(&mut bytes);
+    assert_eq!(bytes[..], to_bytes(&[1.0, 2.0])[..]);
+    assert_eq!(xruns.load(Ordering::Relaxed), 0);
+    // underrun, the rest is silence
+    let mut bytes = [9; 16];
+    callback(&mut bytes);
+    assert_eq!(bytes[..], to_bytes(&[3.0, 4.0, 0.0, 0.0])[..]);
+    assert_eq!(xruns.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn test_pulseaudio_capture_callback() {
+    let (producer, consumer) = SpscRb::new(4).into_split();
+    let xruns = Arc::new(AtomicUsize::new(0));
+    let mut callback = capture_callback(producer, xruns.clone());
+    callback(&to_bytes(&[1.0, 2.0]));
+    assert_eq!(xruns.load(Ordering::Relaxed), 0);
+    // overrun, the samples that did not fit are dropped
+    callback(&to_bytes(&[3.0, 4.0, 5.0]));
+    assert_eq!(xruns.load(Ordering::Relaxed), 1);
+    let mut data = [0.0; 8];
+    assert_eq!(consumer.read(&mut data).unwrap(), 4);
+    assert_eq!(data[..4], [1.0, 2.0, 3.0, 4.0]);
+}
@@ -0,0 +1,92 @@
+#![cfg(all(feature = "mio", unix))]
+
+extern crate mio;
+extern crate rb;
+
+use std::thread;
+use std::time::Duration;
+
+use mio::{Events, Interest, Poll, Token};
+use rb::{RbConsumer, RbError, RbProducer, SpscRb, RB};
+
+const CONSUMER: Token = Token(0);
+const PRODUCER: Token = Token(1);
+
+#[test]
+fn test_readiness_initial_state() {
+    let rb = SpscRb::new(2);
+    let (producer, consumer) = (rb.producer(), rb.consumer());
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(2);
+    let mut cons_ready = consumer.readiness().unwrap();
+    let mut prod_ready = producer.readiness().unwrap();
+    poll.registry()
+        .register(&mut cons_ready, CONSUMER, Interest::READABLE)
+        .unwrap();
+    poll.registry()
+        .register(&mut prod_ready, PRODUCER, Interest::READABLE)
+        .unwrap();
+
+    // An empty buffer has free slots but nothing to read.
+    poll.poll(&mut events, Some(Duration::from_millis(100)))
+        .unwrap();
+    let tokens = events.iter().map(|e| e.token()).collect::<Vec<_>>();
+    assert_eq!(tokens, vec![PRODUCER]);
+
+    prod_ready.clear();
+    producer.write(&[1, 2]).unwrap();
+    poll.poll(&mut events, Some(Duration::from_millis(100)))
+        .unwrap();
+    let tokens = events.iter().map(|e| e.token()).collect::<Vec<_>>();
+    assert_eq!(tokens, vec![CONSUMER]);
+}
+
+#[test]
+fn test_readiness_threads() {
+    let rb = SpscRb::new(4);
+    let (producer, consumer) = (rb.producer(), rb.consumer());
+    let in_data = (0..256).collect::<Vec<_>>();
+    let expected = in_data.clone();
+    let handle = thread::spawn(move || {
+        let mut poll = Poll::new().unwrap();
+        let mut events = Events::with_capacity(1);
+        let mut readiness = producer.readiness().unwrap();
+        poll.registry()
+            .register(&mut readiness, PRODUCER, Interest::READABLE)
+            .unwrap();
+        let mut written = 0;
+        while written < in_data.len() {
+            poll.poll(&mut events, None).unwrap();
+            readiness.clear();
+            while written < in_data.len() {
+                match producer.write(&in_data[written..]) {
+                    Ok(cnt) => written += cnt,
+                    Err(RbError::Full) => break,
+                    v => panic!("No error or incorrect error: {:?}", v),
+                }
+            }
+        }
+    });
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(1);
+    let mut readiness = consumer.readiness().unwrap();
+    poll.registry()
+        .register(&mut readiness, CONSUMER, Interest::READABLE)
+        .unwrap();
+    let mut out_data = Vec::new();
+    let mut buf = [0; 4];
+    'outer: loop {
+        poll.poll(&mut events, None).unwrap();
+        readiness.clear();
+        loop {
+            match consumer.read(&mut buf) {
+                Ok(cnt) => out_data.extend_from_slice(&buf[..cnt]),
+                Err(RbError::Empty) => break,
+                Err(RbError::Disconnected) => break 'outer,
+                v => panic!("No error or incorrect error: {:?}", v),
+            }
+        }
+    }
+    handle.join().unwrap();
+    assert_eq!(out_data, expected);
+}
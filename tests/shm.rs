@@ -0,0 +1,123 @@
+#![cfg(all(feature = "shm", unix))]
+
+extern crate rb;
+
+use std::io;
+use std::process;
+use std::thread;
+
+use rb::{RbConsumer, RbError, RbInspector, RbProducer, ShmSpscRb};
+
+fn shm_name(name: &str) -> String {
+    format!("/rb-test-{}-{}", name, process::id())
+}
+
+#[test]
+fn test_shm_write_read() {
+    let name = shm_name("write-read");
+    let rb = ShmSpscRb::<u32>::create(&name, 4).unwrap();
+    let other = ShmSpscRb::<u32>::open(&name).unwrap();
+    let producer = rb.producer().unwrap();
+    assert!(other.producer().is_none());
+    let consumer = other.consumer().unwrap();
+    assert!(rb.consumer().is_none());
+    assert_eq!(other.capacity(), 4);
+
+    assert_eq!(producer.write(&[1, 2, 3, 4, 5]).unwrap(), 4);
+    assert!(other.is_full());
+    match producer.write_exact(&[6]) {
+        Err(RbError::Full) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    let mut data = [0; 3];
+    assert_eq!(consumer.read(&mut data).unwrap(), 3);
+    assert_eq!(data, [1, 2, 3]);
+    // wraps around the end of the slots
+    producer.write_exact(&[5, 6, 7]).unwrap();
+    assert_eq!(consumer.peek(1, &mut data).unwrap(), 3);
+    assert_eq!(data, [5, 6, 7]);
+    assert_eq!(consumer.skip_pending().unwrap(), 4);
+
+    drop(producer);
+    match consumer.read(&mut data) {
+        Err(RbError::Disconnected) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+}
+
+#[test]
+fn test_shm_threads() {
+    let name = shm_name("threads");
+    let rb = ShmSpscRb::<[f32; 2]>::create(&name, 16).unwrap();
+    let writer = thread::spawn({
+        let name = name.clone();
+        move || {
+            let rb = ShmSpscRb::<[f32; 2]>::open(&name).unwrap();
+            let producer = rb.producer().unwrap();
+            for i in 0..1000 {
+                producer.write_blocking(&[[i as f32, -i as f32]]).unwrap();
+            }
+        }
+    });
+    let consumer = rb.consumer().unwrap();
+    let mut data = [[0.0; 2]; 8];
+    let mut expected = 0;
+    while let Some(cnt) = consumer.read_blocking(&mut data) {
+        for frame in &data[..cnt] {
+            assert_eq!(*frame, [expected as f32, -expected as f32]);
+            expected += 1;
+        }
+    }
+    assert_eq!(expected, 1000);
+    writer.join().unwrap();
+}
+
+#[test]
+fn test_shm_invalid() {
+    let name = shm_name("invalid");
+    let _rb = ShmSpscRb::<u8>::create(&name, 64).unwrap();
+    match ShmSpscRb::<u8>::create(&name, 64) {
+        Err(ref err) if err.kind() == io::ErrorKind::AlreadyExists => {}
+        Err(err) => panic!("incorrect error: {:?}", err),
+        Ok(_) => panic!("no error"),
+    }
+    match ShmSpscRb::<u16>::open(&name) {
+        Err(ref err) if err.kind() == io::ErrorKind::InvalidData => {}
+        Err(err) => panic!("incorrect error: {:?}", err),
+        Ok(_) => panic!("no error"),
+    }
+    drop(_rb);
+    assert!(ShmSpscRb::<u8>::open(&name).is_err());
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_shm_corrupted_position() {
+    use std::fs::OpenOptions;
+    use std::io::{Seek, SeekFrom, Write};
+
+    let name = shm_name("corrupted");
+    let rb = ShmSpscRb::<u8>::create(&name, 4).unwrap();
+    let producer = rb.producer().unwrap();
+    let consumer = rb.consumer().unwrap();
+    producer.write(&[1, 2]).unwrap();
+    // The write position follows the magic, the size, the value size and the read position.
+    let mut file = OpenOptions::new()
+        .write(true)
+        .open(format!("/dev/shm{}", name))
+        .unwrap();
+    file.seek(SeekFrom::Start(32)).unwrap();
+    file.write_all(&u64::MAX.to_ne_bytes()).unwrap();
+
+    assert_eq!(rb.count(), 0);
+    let mut data = [0; 4];
+    match consumer.read(&mut data) {
+        Err(RbError::Disconnected) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    match producer.write(&[3]) {
+        Err(RbError::Disconnected) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    assert_eq!(consumer.read_blocking(&mut data), None);
+}
@@ -0,0 +1,86 @@
+#[cfg(all(feature = "std", feature = "static-async"))]
+extern crate futures_executor;
+extern crate rb;
+
+#[cfg(feature = "std")]
+use std::thread;
+
+use rb::{RbConsumer, RbError, RbInspector, RbProducer, StaticSpscRb};
+
+#[test]
+fn test_static_wraps_around() {
+    let rb = StaticSpscRb::<u32, 5>::new();
+    let (producer, consumer) = rb.split().unwrap();
+    assert_eq!(rb.capacity(), 4);
+    let mut out = [0; 3];
+    for i in 0..10 {
+        assert_eq!(producer.write(&[i, i + 1, i + 2]).unwrap(), 3);
+        assert_eq!(consumer.get(&mut out).unwrap(), 3);
+        assert_eq!(consumer.read(&mut out).unwrap(), 3);
+        assert_eq!(out, [i, i + 1, i + 2]);
+    }
+    assert!(rb.is_empty());
+    match consumer.read(&mut out) {
+        Err(RbError::Empty) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    assert_eq!(producer.write(&[1, 2, 3, 4, 5]).unwrap(), 4);
+    match producer.write(&[6]) {
+        Err(RbError::Full) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    assert_eq!(consumer.skip(1).unwrap(), 1);
+    assert_eq!(consumer.skip_pending().unwrap(), 3);
+    assert!(rb.is_empty());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_static_threads() {
+    static RB: StaticSpscRb<u64, 64> = StaticSpscRb::new();
+    const COUNT: u64 = 100_000;
+    let (producer, consumer) = RB.split().unwrap();
+    assert!(RB.split().is_none());
+    let t = thread::spawn(move || {
+        let data = (0..COUNT).collect::<Vec<_>>();
+        let mut written = 0;
+        while written < data.len() {
+            written += producer.write_blocking(&data[written..]).unwrap();
+        }
+    });
+    let mut out = [0; 48];
+    let mut expected = 0;
+    while expected < COUNT {
+        let cnt = consumer.read_blocking(&mut out).unwrap();
+        for v in &out[..cnt] {
+            assert_eq!(*v, expected);
+            expected += 1;
+        }
+    }
+    t.join().unwrap();
+}
+
+#[cfg(all(feature = "std", feature = "static-async"))]
+#[test]
+fn test_static_async() {
+    use futures_executor::block_on;
+
+    static RB: StaticSpscRb<u32, 4> = StaticSpscRb::new();
+    let (mut producer, mut consumer) = RB.split().unwrap();
+    let handle = thread::spawn(move || {
+        for i in 0..32 {
+            assert_eq!(block_on(producer.write_async(&[i])).unwrap(), 1);
+        }
+    });
+    let mut out_data = Vec::new();
+    let mut buf = [0; 4];
+    loop {
+        match block_on(consumer.read_async(&mut buf)) {
+            Ok(cnt) => out_data.extend_from_slice(&buf[..cnt]),
+            Err(RbError::Disconnected) => break,
+            Err(err) => panic!("Unexpected error: {:?}", err),
+        }
+    }
+    handle.join().unwrap();
+    assert_eq!(out_data, (0..32).collect::<Vec<_>>());
+}
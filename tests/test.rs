@@ -1,8 +1,15 @@
+#![cfg(feature = "std")]
+
 extern crate rb;
 
-use std::time::Duration;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 
-use rb::{RbConsumer, RbInspector, RbProducer, SpscRb, RB};
+use rb::{
+    duplex, transfer, CapacityError, Latency, MpscRb, OverflowPolicy, RbConsumer, RbError, RbHooks,
+    RbInspector, RbPool, RbProducer, Side, SpscRb, Stats, Tee, WaitStrategy, Watermark, RB,
+};
 
 #[test]
 fn test_write() {
@@ -115,6 +122,533 @@ fn test_get() {
     assert!(rb.is_empty());
 }
 
+#[test]
+fn test_peek() {
+    const SIZE: usize = 8;
+    let rb = SpscRb::new(SIZE);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    let mut out_data = vec![0; 4];
+    assert_eq!(producer.write(&[0, 1, 2, 3, 4, 5]).unwrap(), 6);
+    assert_eq!(consumer.read(&mut out_data).unwrap(), 4);
+    // the pending values wrap around the end of the buffer
+    assert_eq!(producer.write(&[6, 7, 8, 9, 10]).unwrap(), 5);
+    assert_eq!(consumer.peek(3, &mut out_data).unwrap(), 4);
+    assert_eq!(out_data, [7, 8, 9, 10]);
+    assert_eq!(consumer.peek(5, &mut out_data).unwrap(), 2);
+    assert_eq!(out_data[..2], [9, 10]);
+    assert!(consumer.peek(7, &mut out_data).is_err());
+    assert_eq!(rb.count(), 7);
+    assert_eq!(consumer.peek(0, &mut out_data).unwrap(), 4);
+    assert_eq!(out_data, [4, 5, 6, 7]);
+}
+
+#[test]
+fn test_interleave() {
+    let rb = SpscRb::new(8);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    let (left, right) = ([1, 2, 3, 4], [-1, -2, -3, -4]);
+    assert_eq!(producer.write_interleaved(&[]).unwrap(), 0);
+    assert_eq!(producer.write(&[0; 3]).unwrap(), 3);
+    // only complete frames that fit are written
+    assert_eq!(producer.write_interleaved(&[&left, &right]).unwrap(), 2);
+    match producer.write_interleaved(&[&left, &right]) {
+        Err(RbError::Full) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    assert_eq!(consumer.skip(3).unwrap(), 3);
+    // the frames wrap around the end of the buffer
+    assert_eq!(
+        producer
+            .write_interleaved(&[&left[2..], &right[2..]])
+            .unwrap(),
+        2
+    );
+    let (mut l, mut r) = ([0; 8], [0; 3]);
+    assert_eq!(
+        consumer.read_deinterleaved(&mut [&mut l, &mut r]).unwrap(),
+        3
+    );
+    assert_eq!((&l[..3], r), (&[1, 2, 3][..], [-1, -2, -3]));
+    assert_eq!(
+        consumer.read_deinterleaved(&mut [&mut l, &mut r]).unwrap(),
+        1
+    );
+    assert_eq!((l[0], r[0]), (4, -4));
+    match consumer.read_deinterleaved(&mut [&mut l, &mut r]) {
+        Err(RbError::Empty) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+}
+
+#[test]
+fn test_interleave_wrapping_frames() {
+    // frames of one to three channels that wrap around the end at every offset
+    for n in 1..4 {
+        for offset in 0..10 {
+            let rb = SpscRb::new(10);
+            let (consumer, producer) = (rb.consumer(), rb.producer());
+            producer.write(&vec![0; offset]).unwrap();
+            consumer.skip(offset).ok();
+            let channels: Vec<Vec<i32>> = (0..n)
+                .map(|c| (0..5).map(|i| (c * 100 + i) as i32).collect())
+                .collect();
+            let refs: Vec<&[i32]> = channels.iter().map(|c| &c[..]).collect();
+            let frames = 10 / n;
+            assert_eq!(producer.write_interleaved(&refs).unwrap(), frames.min(5));
+            let mut out: Vec<Vec<i32>> = vec![vec![-1; 5]; n];
+            let mut refs: Vec<&mut [i32]> = out.iter_mut().map(|c| &mut c[..]).collect();
+            assert_eq!(
+                consumer.read_deinterleaved(&mut refs).unwrap(),
+                frames.min(5)
+            );
+            for (c, channel) in out.iter().enumerate() {
+                assert_eq!(channel[..frames.min(5)], channels[c][..frames.min(5)]);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_read_map() {
+    let rb = SpscRb::new(4);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    let mut data = [0u32; 4];
+    match consumer.read_map(&mut data, |&x: &u32| x.swap_bytes()) {
+        Err(RbError::Empty) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    assert_eq!(producer.write(&[1, 2, 3]).unwrap(), 3);
+    assert_eq!(consumer.read(&mut [0; 2]).unwrap(), 2);
+    // the pending values wrap around the end of the buffer
+    assert_eq!(producer.write(&[4, 5, 6]).unwrap(), 3);
+    assert_eq!(consumer.read_map(&mut data, |x| x.to_be()).unwrap(), 4);
+    assert_eq!(data, [3, 4, 5, 6].map(u32::to_be));
+    assert!(rb.is_empty());
+}
+
+#[test]
+fn test_read_or_default() {
+    let rb = SpscRb::new(4);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    let mut data = [9; 3];
+    assert_eq!(consumer.read_or_default(&mut data), 0);
+    assert_eq!(data, [0, 0, 0]);
+    assert_eq!(rb.underruns(), 1);
+    assert_eq!(producer.write(&[1, 2, 3, 4]).unwrap(), 4);
+    assert_eq!(consumer.read_or_default(&mut data), 3);
+    assert_eq!(data, [1, 2, 3]);
+    assert_eq!(consumer.read_or_default(&mut data), 1);
+    assert_eq!(data, [4, 0, 0]);
+    assert_eq!(rb.underruns(), 1);
+    drop(producer);
+    assert_eq!(consumer.read_or_default(&mut data), 0);
+    assert_eq!(data, [0, 0, 0]);
+}
+
+#[test]
+fn test_read_while() {
+    let rb = SpscRb::new(4);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    assert!(consumer.read_while(|_| true).is_empty());
+    assert_eq!(producer.write(&[1, 2, 3]).unwrap(), 3);
+    assert_eq!(consumer.read_while(|&x| x < 2), [1]);
+    assert!(consumer.read_while(|&x| x < 2).is_empty());
+    assert_eq!(rb.count(), 2);
+    // the pending values wrap around the end of the buffer
+    assert_eq!(producer.write(&[4, 5]).unwrap(), 2);
+    assert_eq!(consumer.read_while(|&x| x != 5), [2, 3, 4]);
+    assert_eq!(consumer.read_while(|_| true), [5]);
+    assert!(rb.is_empty());
+}
+
+#[test]
+fn test_read_overlapped() {
+    let rb = SpscRb::new(4);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    let mut window = [0; 3];
+    assert_eq!(producer.write(&[1, 2]).unwrap(), 2);
+    // either the whole window or nothing
+    match consumer.read_overlapped(&mut window, 1) {
+        Err(RbError::Empty) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    assert_eq!(rb.count(), 2);
+    assert_eq!(producer.write(&[3, 4]).unwrap(), 2);
+    consumer.read_overlapped(&mut window, 0).unwrap();
+    assert_eq!(window, [1, 2, 3]);
+    consumer.read_overlapped(&mut window, 2).unwrap();
+    assert_eq!(window, [1, 2, 3]);
+    // the window wraps around the end of the buffer
+    assert_eq!(producer.write(&[5, 6]).unwrap(), 2);
+    consumer.read_overlapped(&mut window, 3).unwrap();
+    assert_eq!(window, [3, 4, 5]);
+    drop((rb, producer));
+    match consumer.read_overlapped(&mut window, 1) {
+        Err(RbError::Disconnected) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    assert_eq!(consumer.read_vec(4), [6]);
+}
+
+#[test]
+#[should_panic]
+fn test_read_overlapped_hop_too_large() {
+    let rb = SpscRb::<i32>::new(4);
+    let _ = rb.consumer().read_overlapped(&mut [0; 2], 3);
+}
+
+#[test]
+#[should_panic(expected = "the window is larger than the capacity of the buffer")]
+fn test_read_overlapped_window_too_large() {
+    let rb = SpscRb::<i32>::new(4);
+    let _ = rb.consumer().read_overlapped(&mut [0; 5], 1);
+}
+
+#[test]
+fn test_transfer() {
+    let (src, dst) = (SpscRb::new(4), SpscRb::new(3));
+    let (src_prod, src_cons) = (src.producer(), src.consumer());
+    let (dst_prod, dst_cons) = (dst.producer(), dst.consumer());
+    match transfer(&src_cons, &dst_prod, 4) {
+        Err(RbError::Empty) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    assert_eq!(transfer(&src_cons, &dst_prod, 0).unwrap(), 0);
+    // the pending values of both buffers wrap around their ends
+    assert_eq!(src_prod.write(&[1, 2, 3]).unwrap(), 3);
+    assert_eq!(src_cons.read_vec(3), [1, 2, 3]);
+    assert_eq!(dst_prod.write(&[0, 0]).unwrap(), 2);
+    assert_eq!(dst_cons.read_vec(2), [0, 0]);
+    assert_eq!(src_prod.write(&[4, 5, 6]).unwrap(), 3);
+    assert_eq!(transfer(&src_cons, &dst_prod, 2).unwrap(), 2);
+    assert_eq!(transfer(&src_cons, &dst_prod, 4).unwrap(), 1);
+    assert!(src.is_empty());
+    assert_eq!(src_prod.write(&[7]).unwrap(), 1);
+    match transfer(&src_cons, &dst_prod, 4) {
+        Err(RbError::Full) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    assert_eq!(dst_cons.read_vec(4), [4, 5, 6]);
+    dst.close();
+    match transfer(&src_cons, &dst_prod, 4) {
+        Err(RbError::Disconnected) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    assert_eq!(src.count(), 1);
+}
+
+#[test]
+#[should_panic]
+fn test_transfer_same_buffer() {
+    let rb = SpscRb::new(4);
+    assert_eq!(rb.producer().write(&[1]).unwrap(), 1);
+    let _ = transfer(&rb.consumer(), &rb.producer(), 1);
+}
+
+#[test]
+fn test_fold_pending() {
+    let rb = SpscRb::new(4);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    assert_eq!(consumer.fold_pending(0, |sum, x| sum + x), 0);
+    assert_eq!(producer.write(&[1, 2, 3]).unwrap(), 3);
+    assert_eq!(consumer.read(&mut [0; 2]).unwrap(), 2);
+    // the pending values wrap around the end of the buffer
+    assert_eq!(producer.write(&[4, 5, 6]).unwrap(), 3);
+    let order = consumer.fold_pending(Vec::new(), |mut order, &x| {
+        order.push(x);
+        order
+    });
+    assert_eq!(order, [3, 4, 5, 6]);
+    assert_eq!(consumer.fold_pending(0, |sum, x| sum + x), 18);
+    assert_eq!(rb.count(), 4);
+}
+
+#[test]
+fn test_snapshot() {
+    let rb = SpscRb::new(4);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    assert!(consumer.snapshot().is_empty());
+    assert_eq!(producer.write(&[1, 2, 3]).unwrap(), 3);
+    assert_eq!(consumer.read(&mut [0; 2]).unwrap(), 2);
+    // the pending values wrap around the end of the buffer
+    assert_eq!(producer.write(&[4, 5, 6]).unwrap(), 3);
+    assert_eq!(consumer.snapshot(), [3, 4, 5, 6]);
+    assert_eq!(consumer.snapshot(), [3, 4, 5, 6]);
+    assert_eq!(rb.count(), 4);
+    assert_eq!(consumer.read_all_vec(), [3, 4, 5, 6]);
+}
+
+#[test]
+fn test_discard_oldest() {
+    let rb = SpscRb::new(4);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    assert_eq!(producer.discard_oldest(2).unwrap(), 0);
+    assert_eq!(producer.write(&[1, 2, 3]).unwrap(), 3);
+    assert_eq!(consumer.read(&mut [0; 2]).unwrap(), 2);
+    // the pending values wrap around the end of the buffer
+    assert_eq!(producer.write(&[4, 5, 6]).unwrap(), 3);
+    assert_eq!(producer.discard_oldest(2).unwrap(), 2);
+    assert_eq!(rb.count(), 2);
+    assert_eq!(producer.write(&[7, 8]).unwrap(), 2);
+    assert_eq!(producer.discard_oldest(8).unwrap(), 4);
+    assert_eq!(producer.overwritten(), 6);
+    match consumer.read(&mut [0]) {
+        Err(RbError::Empty) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    assert_eq!(producer.write(&[9]).unwrap(), 1);
+    assert_eq!(consumer.read_all_vec(), [9]);
+    assert_eq!(consumer.skipped(), 6);
+    rb.close();
+    match producer.discard_oldest(1) {
+        Err(RbError::Disconnected) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+}
+
+#[test]
+fn test_rollback() {
+    let rb = SpscRb::new(4);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    assert_eq!(producer.rollback(2), 0);
+    assert_eq!(producer.write(&[1, 2, 3]).unwrap(), 3);
+    assert_eq!(consumer.read(&mut [0; 2]).unwrap(), 2);
+    // the pending values wrap around the end of the buffer
+    assert_eq!(producer.write(&[4, 5, 6]).unwrap(), 3);
+    assert_eq!(producer.rollback(2), 2);
+    assert_eq!(rb.count(), 2);
+    assert_eq!(producer.write(&[7, 8]).unwrap(), 2);
+    assert_eq!(consumer.read_all_vec(), [3, 4, 7, 8]);
+    // the values that were already read stay read
+    assert_eq!(producer.write(&[9]).unwrap(), 1);
+    assert_eq!(producer.rollback(4), 1);
+    assert!(rb.is_empty());
+    assert_eq!(producer.write(&[10]).unwrap(), 1);
+    assert_eq!(consumer.read_all_vec(), [10]);
+}
+
+#[test]
+fn test_rollback_unwritten_slots() {
+    #[derive(Clone, Debug, PartialEq)]
+    struct Frame(u8);
+
+    // the slots are allocated lazily and the write position is behind the allocated ones
+    let rb = SpscRb::new(8);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    assert_eq!(producer.write(&[Frame(1), Frame(2), Frame(3)]).unwrap(), 3);
+    assert_eq!(producer.rollback(2), 2);
+    assert_eq!(producer.write(&[Frame(4)]).unwrap(), 1);
+    let mut out = vec![Frame(0); 4];
+    assert_eq!(consumer.read(&mut out).unwrap(), 2);
+    assert_eq!(out[..2], [Frame(1), Frame(4)]);
+}
+
+#[test]
+fn test_unread() {
+    let rb = SpscRb::new(4);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    assert_eq!(consumer.unread(1), 0);
+    assert_eq!(producer.write(&[1, 2, 3]).unwrap(), 3);
+    assert_eq!(consumer.read_vec(3), [1, 2, 3]);
+    assert_eq!(consumer.unread(2), 2);
+    assert_eq!(consumer.read_vec(1), [2]);
+    // the slot of the first value is overwritten
+    assert_eq!(producer.write(&[4, 5]).unwrap(), 2);
+    assert_eq!(consumer.unread(4), 1);
+    assert!(rb.is_full());
+    match producer.write(&[6]) {
+        Err(RbError::Full) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    assert_eq!(consumer.read_all_vec(), [2, 3, 4, 5]);
+    // the values wrap around the end of the buffer
+    assert_eq!(producer.write(&[6]).unwrap(), 1);
+    assert_eq!(consumer.read_all_vec(), [6]);
+    assert_eq!(consumer.unread(8), 4);
+    assert_eq!(consumer.read_all_vec(), [3, 4, 5, 6]);
+    // discarded values were never read
+    assert_eq!(producer.write(&[7, 8]).unwrap(), 2);
+    rb.reset();
+    assert_eq!(consumer.unread(8), 0);
+    assert_eq!(producer.write(&[9]).unwrap(), 1);
+    assert_eq!(consumer.read_all_vec(), [9]);
+    assert_eq!(consumer.unread(8), 1);
+    assert_eq!(consumer.read_all_vec(), [9]);
+}
+
+#[test]
+fn test_producers_see_each_others_writes() {
+    let rb = MpscRb::new(4);
+    let (consumer, first) = (rb.consumer(), rb.producer());
+    let second = first.clone();
+    assert_eq!(second.write(&[0]).unwrap(), 1);
+    assert_eq!(consumer.read_vec(1), [0]);
+    // the first producer wraps around the read position that the second one saw last
+    for _ in 0..3 {
+        assert_eq!(first.write(&[1, 2, 3, 4]).unwrap(), 4);
+        assert_eq!(consumer.read_vec(4), [1, 2, 3, 4]);
+    }
+    assert_eq!(first.write(&[5, 6, 7, 8]).unwrap(), 4);
+    match second.write(&[9]) {
+        Err(RbError::Full) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    drop(first);
+    assert_eq!(consumer.read_vec(4), [5, 6, 7, 8]);
+    assert_eq!(second.write(&[9]).unwrap(), 1);
+    assert_eq!(consumer.read_vec(4), [9]);
+}
+
+#[test]
+fn test_read_write_access() {
+    const SIZE: usize = 4;
+    let rb = SpscRb::new(SIZE);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    let written = producer.write_access(|a, b| {
+        assert_eq!((a.len(), b.len()), (SIZE, 0));
+        a[..3].copy_from_slice(&[1, 2, 3]);
+        3
+    });
+    assert_eq!(written.unwrap(), 3);
+    let consumed = consumer.read_access(|a, b| {
+        assert_eq!((a, b), (&[1, 2, 3][..], &[][..]));
+        2
+    });
+    assert_eq!(consumed.unwrap(), 2);
+    // the free slots wrap around the end of the buffer
+    let written = producer.write_access(|a, b| {
+        assert_eq!((a.len(), b.len()), (2, 1));
+        a.copy_from_slice(&[4, 5]);
+        b[0] = 6;
+        // more than there are slots
+        8
+    });
+    assert_eq!(written.unwrap(), 3);
+    assert!(rb.is_full());
+    assert!(producer.write_access(|_, _| 1).is_err());
+    let consumed = consumer.read_access(|a, b| {
+        assert_eq!((a, b), (&[3, 4, 5][..], &[6][..]));
+        a.len() + b.len()
+    });
+    assert_eq!(consumed.unwrap(), 4);
+    assert!(consumer.read_access(|_, _| 1).is_err());
+}
+
+#[test]
+fn test_write_grant() {
+    const SIZE: usize = 4;
+    let rb = SpscRb::new(SIZE);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    {
+        let mut grant = producer.grant(3).unwrap();
+        assert_eq!(grant.len(), 3);
+        grant.copy_from_slice(&[1, 2, 3]);
+    }
+    assert_eq!(rb.count(), 3);
+    let mut out_data = vec![0; 3];
+    assert_eq!(consumer.read(&mut out_data).unwrap(), 3);
+    // only the slots up to the end of the buffer are granted
+    let mut grant = producer.grant(SIZE).unwrap();
+    assert_eq!(grant.len(), 2);
+    grant[0] = 4;
+    grant.commit(1);
+    assert_eq!(rb.count(), 1);
+    let mut grant = producer.grant(SIZE).unwrap();
+    assert_eq!(grant.len(), 1);
+    grant[0] = 5;
+    drop(grant);
+    assert_eq!(consumer.read(&mut out_data).unwrap(), 2);
+    assert_eq!(out_data[..2], [4, 5]);
+}
+
+#[test]
+fn test_read_grant() {
+    const SIZE: usize = 4;
+    let rb = SpscRb::new(SIZE);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    assert!(consumer.read_grant().is_err());
+    assert_eq!(producer.write(&[1, 2, 3]).unwrap(), 3);
+    {
+        let grant = consumer.read_grant().unwrap();
+        assert_eq!(*grant, [1, 2, 3]);
+    }
+    assert!(rb.is_empty());
+    // the pending values wrap around the end of the buffer
+    assert_eq!(producer.write(&[4, 5, 6, 7]).unwrap(), 4);
+    let grant = consumer.read_grant().unwrap();
+    assert_eq!(*grant, [4, 5]);
+    grant.release(1);
+    assert_eq!(rb.count(), 3);
+    assert_eq!(*consumer.read_grant().unwrap(), [5]);
+    assert_eq!(*consumer.read_grant().unwrap(), [6, 7]);
+    assert!(rb.is_empty());
+}
+
+#[test]
+fn test_iter_pending() {
+    const SIZE: usize = 4;
+    let rb = SpscRb::new(SIZE);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    assert_eq!(consumer.iter_pending().count(), 0);
+    assert_eq!(producer.write(&[1, 2, 3]).unwrap(), 3);
+    assert_eq!(consumer.skip(2).unwrap(), 2);
+    // the pending values wrap around the end of the buffer
+    assert_eq!(producer.write(&[4, 5, 6]).unwrap(), 3);
+    let iter = consumer.iter_pending();
+    assert_eq!(iter.len(), 4);
+    assert_eq!(iter.collect::<Vec<_>>(), [3, 4, 5, 6]);
+    assert_eq!(rb.count(), 4);
+}
+
+#[test]
+fn test_write_from_iter() {
+    const SIZE: usize = 4;
+    let rb = SpscRb::new(SIZE);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    let mut values = 0..6;
+    assert_eq!(producer.write_from_iter(values.by_ref()).unwrap(), SIZE);
+    assert!(producer.write_from_iter(values.by_ref()).is_err());
+    // values that didn't fit are left in the iterator
+    assert_eq!(values.next(), Some(4));
+    let mut out_data = vec![0; SIZE];
+    assert_eq!(consumer.read(&mut out_data).unwrap(), SIZE);
+    assert_eq!(out_data, [0, 1, 2, 3]);
+}
+
+#[test]
+fn test_read_vec() {
+    const SIZE: usize = 4;
+    let rb = SpscRb::new(SIZE);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    assert!(consumer.read_all_vec().is_empty());
+    assert_eq!(producer.write(&[1, 2, 3]).unwrap(), 3);
+    assert_eq!(consumer.read_vec(2), [1, 2]);
+    // the pending values wrap around the end of the buffer
+    assert_eq!(producer.write(&[4, 5, 6]).unwrap(), 3);
+    assert_eq!(consumer.read_all_vec(), [3, 4, 5, 6]);
+    assert!(rb.is_empty());
+}
+
+#[test]
+fn test_write_exact() {
+    const SIZE: usize = 4;
+    let rb = SpscRb::new(SIZE);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    producer.write_exact(&[1, 2, 3]).unwrap();
+    match producer.write_exact(&[4, 5]) {
+        Err(RbError::Full) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    assert_eq!(rb.count(), 3);
+    assert_eq!(consumer.skip(2).unwrap(), 2);
+    // the record wraps around the end of the buffer
+    producer.write_exact(&[4, 5, 6]).unwrap();
+    assert!(rb.is_full());
+    let mut out_data = vec![0; SIZE];
+    assert_eq!(consumer.read(&mut out_data).unwrap(), SIZE);
+    assert_eq!(out_data, [3, 4, 5, 6]);
+}
+
 #[test]
 fn test_read_write_wrap() {
     const SIZE: usize = 2;
@@ -194,3 +728,732 @@ fn test_read_write_timeout_wrap_blocking() {
     assert_eq!(rb.count(), 0);
     assert_eq!(rb.slots_free(), 2);
 }
+
+#[test]
+fn test_read_write_deadline() {
+    const SIZE: usize = 2;
+    let rb = SpscRb::new(SIZE);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    let deadline = Instant::now() + Duration::from_millis(10);
+    let mut out_data = vec![0; SIZE];
+    match consumer.read_blocking_deadline(&mut out_data, deadline) {
+        Err(RbError::TimedOut) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    assert!(Instant::now() >= deadline);
+    assert_eq!(
+        producer.write_blocking_deadline(&[1, 2], deadline).unwrap(),
+        Some(2)
+    );
+    // the deadline has passed, but there is no need to wait
+    assert_eq!(
+        consumer
+            .read_blocking_deadline(&mut out_data, deadline)
+            .unwrap(),
+        Some(2)
+    );
+    assert_eq!(out_data, [1, 2]);
+}
+
+#[test]
+fn test_disconnected() {
+    const SIZE: usize = 4;
+    let rb = SpscRb::new(SIZE);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    producer.write(&[1, 2]).unwrap();
+    drop(producer);
+    // the buffer can still create a producer
+    let mut out_data = vec![0; SIZE];
+    assert_eq!(consumer.read(&mut out_data).unwrap(), 2);
+    match consumer.read(&mut out_data) {
+        Err(RbError::Empty) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+
+    let producer = rb.producer();
+    assert_eq!(producer.write(&[3]).unwrap(), 1);
+    drop((rb, consumer));
+    // the non-blocking writes keep storing values
+    assert_eq!(producer.write(&[4]).unwrap(), 1);
+    assert_eq!(producer.write_blocking(&[5]), None);
+
+    let (producer, consumer) = SpscRb::new(SIZE).into_split();
+    producer.write(&[1, 2]).unwrap();
+    drop(producer);
+    // pending values are still delivered
+    assert_eq!(consumer.read(&mut out_data).unwrap(), 2);
+    match consumer.read(&mut out_data) {
+        Err(RbError::Disconnected) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    assert_eq!(consumer.read_blocking(&mut out_data), None);
+}
+
+#[test]
+fn test_watermarks() {
+    let rb = SpscRb::new(8);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    let (tx, rx) = mpsc::channel();
+    rb.on_watermark(1, 4, move |watermark| tx.send(watermark).unwrap());
+
+    producer.write(&[0; 3]).unwrap();
+    consumer.skip(3).unwrap();
+    assert_eq!(rx.try_recv().ok(), None);
+
+    producer.write(&[0; 4]).unwrap();
+    // every watermark is only reported once until the other one is crossed
+    producer.write(&[0; 2]).unwrap();
+    consumer.skip(3).unwrap();
+    consumer.skip(2).unwrap();
+    producer.write(&[0; 3]).unwrap();
+    rb.clear();
+    assert_eq!(
+        rx.try_iter().collect::<Vec<_>>(),
+        [
+            Watermark::High,
+            Watermark::Low,
+            Watermark::High,
+            Watermark::Low
+        ]
+    );
+}
+
+#[test]
+fn test_write_overwriting() {
+    let rb = SpscRb::new(4);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    assert_eq!(producer.write_overwriting(&[1, 2]).unwrap(), 0);
+    // the consumer caches the write position
+    assert_eq!(consumer.read_vec(1), vec![1]);
+    assert_eq!(producer.write_overwriting(&[3, 4, 5, 6]).unwrap(), 1);
+    assert_eq!(rb.count(), 4);
+    assert_eq!(consumer.read_vec(4), vec![3, 4, 5, 6]);
+    // only the last values of data longer than the capacity are written
+    producer.write(&[7]).unwrap();
+    assert_eq!(
+        producer.write_overwriting(&[8, 9, 10, 11, 12, 13]).unwrap(),
+        3
+    );
+    assert_eq!(consumer.read_vec(8), vec![10, 11, 12, 13]);
+    assert_eq!(consumer.overwritten(), 4);
+    assert_eq!(producer.overwritten(), 4);
+
+    rb.close();
+    match producer.write_overwriting(&[1]) {
+        Err(RbError::Disconnected) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+}
+
+#[test]
+fn test_skipped() {
+    let rb = SpscRb::new(4);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    producer.write_overwriting(&[1, 2, 3]).unwrap();
+    assert_eq!(consumer.read_vec(2), vec![1, 2]);
+    assert_eq!(consumer.skipped(), 0);
+    producer.write_overwriting(&[4, 5, 6, 7]).unwrap();
+    producer.write_overwriting(&[8]).unwrap();
+    assert_eq!(consumer.read_vec(1), vec![5]);
+    assert_eq!(consumer.skipped(), 2);
+    assert_eq!(consumer.read_vec(1), vec![6]);
+    assert_eq!(consumer.skipped(), 0);
+
+    // a new consumer only sees the values that were overwritten after its creation
+    producer.write_overwriting(&[9, 10, 11]).unwrap();
+    drop(consumer);
+    let consumer = rb.consumer();
+    assert_eq!(consumer.read_vec(1), vec![8]);
+    assert_eq!(consumer.skipped(), 0);
+    producer.write_overwriting(&[12, 13]).unwrap();
+    assert_eq!(consumer.read_vec(1), vec![10]);
+    assert_eq!(consumer.skipped(), 1);
+}
+#[test]
+fn test_write_dropping() {
+    let rb = SpscRb::new(4);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    assert_eq!(producer.write_dropping(&[1, 2, 3]).unwrap(), 0);
+    assert_eq!(producer.write_dropping(&[4, 5]).unwrap(), 1);
+    assert_eq!(producer.write_dropping(&[6]).unwrap(), 1);
+    assert_eq!(consumer.read_vec(4), vec![1, 2, 3, 4]);
+    assert_eq!(producer.dropped(), 2);
+    assert_eq!(consumer.dropped(), 2);
+
+    rb.close();
+    match producer.write_dropping(&[1]) {
+        Err(RbError::Disconnected) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+}
+
+#[test]
+fn test_overflow_policy() {
+    let write = |policy, data: &[u8]| {
+        let rb = SpscRb::new(4).with_overflow_policy(policy);
+        let (consumer, producer) = (rb.consumer(), rb.producer());
+        producer.write(&[1, 2]).unwrap();
+        let result = producer.write(data);
+        (result, consumer.read_vec(4))
+    };
+    match write(OverflowPolicy::Error, &[3, 4, 5]) {
+        (Err(RbError::Full), pending) => assert_eq!(pending, vec![1, 2]),
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    match write(OverflowPolicy::Partial, &[3, 4, 5]) {
+        (Ok(2), pending) => assert_eq!(pending, vec![1, 2, 3, 4]),
+        v => panic!("Unexpected result: {:?}", v),
+    }
+    match write(OverflowPolicy::DropNewest, &[3, 4, 5]) {
+        (Ok(3), pending) => assert_eq!(pending, vec![1, 2, 3, 4]),
+        v => panic!("Unexpected result: {:?}", v),
+    }
+    match write(OverflowPolicy::OverwriteOldest, &[3, 4, 5]) {
+        (Ok(3), pending) => assert_eq!(pending, vec![2, 3, 4, 5]),
+        v => panic!("Unexpected result: {:?}", v),
+    }
+    match write(OverflowPolicy::Block, &[3, 4, 5]) {
+        (Ok(2), pending) => assert_eq!(pending, vec![1, 2, 3, 4]),
+        v => panic!("Unexpected result: {:?}", v),
+    }
+
+    // a full buffer blocks until the consumer reads
+    let rb = SpscRb::new(1).with_overflow_policy(OverflowPolicy::Block);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    producer.write(&[1]).unwrap();
+    let reader = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(10));
+        (consumer.read_vec(1), consumer)
+    });
+    assert_eq!(producer.write(&[2]).unwrap(), 1);
+    assert_eq!(reader.join().unwrap().0, vec![1]);
+}
+
+#[test]
+fn test_xruns() {
+    let rb = SpscRb::new(4);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    assert_eq!(producer.write(&[1, 2, 3]).unwrap(), 3);
+    assert_eq!(producer.write(&[4, 5]).unwrap(), 1);
+    assert!(producer.write_exact(&[6, 7]).is_err());
+    assert_eq!(rb.overruns(), 3);
+    assert_eq!(producer.write_dropping(&[8]).unwrap(), 1);
+    assert_eq!(producer.write_overwriting(&[9, 10]).unwrap(), 2);
+    assert_eq!(rb.overruns(), 6);
+    assert_eq!(rb.underruns(), 0);
+
+    assert_eq!(consumer.read_vec(5), vec![3, 4, 9, 10]);
+    assert!(consumer.read(&mut [0]).is_err());
+    assert!(consumer.read_grant().is_err());
+    assert!(consumer.read_access(|_, _| 0).is_err());
+    // Peeking doesn't consume, thus it's not an underrun.
+    assert!(consumer.get(&mut [0]).is_err());
+    assert_eq!(rb.underruns(), 3);
+
+    rb.reset_xruns();
+    assert_eq!((rb.overruns(), rb.underruns()), (0, 0));
+    rb.close();
+    match consumer.read(&mut [0]) {
+        Err(RbError::Disconnected) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    assert_eq!(rb.underruns(), 0);
+}
+
+#[test]
+fn test_stats() {
+    let rb = SpscRb::new(4);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    producer.write(&[1, 2]).unwrap();
+    assert_eq!(rb.stats(), Stats::default());
+
+    let rb = rb.with_stats();
+    producer.write(&[3, 4]).unwrap();
+    assert_eq!(consumer.read_vec(3), vec![1, 2, 3]);
+    consumer.skip(1).unwrap();
+    producer.write_exact(&[5, 6]).unwrap();
+    match producer.write_blocking_timeout(&[7, 8, 9], Duration::from_millis(1)) {
+        Ok(Some(2)) => {}
+        v => panic!("Incorrect result: {:?}", v),
+    }
+    match producer.write_blocking_timeout(&[9], Duration::from_millis(1)) {
+        Err(RbError::TimedOut) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    assert_eq!(consumer.read_vec(4), vec![5, 6, 7, 8]);
+    match consumer.read_blocking_timeout(&mut [0], Duration::from_millis(1)) {
+        Err(RbError::TimedOut) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    let stats = rb.stats();
+    assert_eq!(stats.written, 6);
+    assert_eq!(stats.read, 8);
+    assert_eq!(stats.max_count, 4);
+    assert!(stats.producer_waits >= 1);
+    assert!(stats.consumer_waits >= 1);
+
+    producer.write(&[1]).unwrap();
+    rb.reset_stats();
+    assert_eq!(
+        rb.stats(),
+        Stats {
+            max_count: 1,
+            ..Stats::default()
+        }
+    );
+}
+
+#[test]
+fn test_histogram() {
+    let rb = SpscRb::new(8);
+    assert!(rb.histogram().is_empty());
+    let rb = rb.with_histogram(2);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    producer.write(&[0; 3]).unwrap();
+    producer.write(&[0; 2]).unwrap();
+    producer.write(&[0; 3]).unwrap();
+    assert_eq!(consumer.read_vec(8).len(), 8);
+    // counts 0..=4 fall into the first bin and 5..=8 into the second one
+    assert_eq!(rb.histogram(), [2, 2]);
+
+    rb.reset_stats();
+    assert_eq!(rb.histogram(), [0, 0]);
+}
+
+#[test]
+fn test_duplex() {
+    let (left, right) = duplex::<u8, &str>(2);
+    left.producer().write(&[1, 2]).unwrap();
+    right.producer().write(&["one"]).unwrap();
+    assert_eq!(right.consumer().read_vec(4), [1, 2]);
+    assert_eq!(left.consumer().read_vec(4), ["one"]);
+    // the buffers are independent
+    assert!(left.producer().write(&[3, 4]).is_ok());
+    assert!(left.consumer().is_empty());
+
+    let (producer, consumer) = right.into_split();
+    left.close();
+    match producer.write(&["two"]) {
+        Err(RbError::Disconnected) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    assert_eq!(consumer.read_vec(4), [3, 4]);
+    match consumer.read(&mut [0]) {
+        Err(RbError::Disconnected) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+}
+
+#[test]
+fn test_latency() {
+    let rb = SpscRb::new(8);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    producer.write(&[1]).unwrap();
+    assert_eq!(rb.latency(), Latency::default());
+
+    let rb = rb.with_latency();
+    consumer.skip(1).unwrap();
+    producer.write(&[1, 2]).unwrap();
+    thread::sleep(Duration::from_millis(50));
+    producer.write(&[3]).unwrap();
+    assert_eq!(consumer.read_vec(1), [1]);
+    // the oldest value of the read determines its age
+    assert_eq!(consumer.read_vec(2), [2, 3]);
+    producer.write(&[4, 5]).unwrap();
+    thread::sleep(Duration::from_millis(50));
+    producer.write(&[6]).unwrap();
+    // neither discarded nor retracted values are measured
+    assert_eq!(producer.discard_oldest(2).unwrap(), 2);
+    producer.write(&[7]).unwrap();
+    assert_eq!(producer.rollback(1), 1);
+    assert_eq!(consumer.read_vec(2), [6]);
+    let latency = rb.latency();
+    assert_eq!(latency.samples, 4);
+    assert!(latency.max >= Duration::from_millis(50));
+    assert!(latency.min < Duration::from_millis(50));
+    assert!(latency.min <= latency.avg && latency.avg <= latency.max);
+
+    rb.reset_latency();
+    assert_eq!(rb.latency(), Latency::default());
+}
+
+#[test]
+fn test_hooks() {
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Write(usize),
+        Read(usize),
+        BlockStart(Side),
+        BlockEnd(Side),
+    }
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<Event>>);
+
+    impl RbHooks for Recorder {
+        fn on_write(&self, n: usize) {
+            self.0.lock().unwrap().push(Event::Write(n));
+        }
+        fn on_read(&self, n: usize) {
+            self.0.lock().unwrap().push(Event::Read(n));
+        }
+        fn on_block_start(&self, side: Side) {
+            self.0.lock().unwrap().push(Event::BlockStart(side));
+        }
+        fn on_block_end(&self, side: Side) {
+            self.0.lock().unwrap().push(Event::BlockEnd(side));
+        }
+    }
+
+    let recorder = Arc::new(Recorder::default());
+    let rb = SpscRb::new(2).with_hooks(recorder.clone());
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    producer.write(&[1, 2]).unwrap();
+    assert!(producer
+        .write_blocking_timeout(&[3], Duration::from_millis(1))
+        .is_err());
+    consumer.skip(2).unwrap();
+    assert!(consumer
+        .read_blocking_timeout(&mut [0], Duration::from_millis(1))
+        .is_err());
+    // nothing is reported for empty grants
+    producer.grant(1).unwrap().commit(0);
+
+    let events = recorder.0.lock().unwrap();
+    assert_eq!(events[0], Event::Write(2));
+    // the blocking calls might wait several times
+    let mut rest = events[1..]
+        .iter()
+        .filter(|event| !matches!(event, Event::BlockStart(_) | Event::BlockEnd(_)));
+    assert_eq!(rest.next(), Some(&Event::Read(2)));
+    assert_eq!(rest.next(), None);
+    assert!(events.contains(&Event::BlockStart(Side::Producer)));
+    assert!(events.contains(&Event::BlockEnd(Side::Producer)));
+    assert!(events.contains(&Event::BlockStart(Side::Consumer)));
+    assert_eq!(events.last(), Some(&Event::BlockEnd(Side::Consumer)));
+}
+
+#[test]
+fn test_handle_inspector() {
+    let rb = SpscRb::new(4);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    drop(rb);
+    producer.write(&[1, 2, 3]).unwrap();
+    for inspector in [&producer as &dyn RbInspector, &consumer] {
+        assert!(!inspector.is_empty());
+        assert!(!inspector.is_full());
+        assert_eq!(inspector.capacity(), 4);
+        assert_eq!(inspector.count(), 3);
+        assert_eq!(inspector.slots_free(), 1);
+    }
+    consumer.skip_pending().unwrap();
+    assert!(producer.is_empty());
+}
+
+#[test]
+fn test_try_handles() {
+    let rb = SpscRb::<u8>::new(4);
+    let producer = rb.try_producer().unwrap();
+    let consumer = rb.try_consumer().unwrap();
+    assert!(rb.try_producer().is_none());
+    assert!(rb.try_consumer().is_none());
+    // handles of `producer` and `consumer` count as well
+    drop(producer);
+    let _producer = rb.producer();
+    assert!(rb.try_producer().is_none());
+    drop(consumer);
+    assert!(rb.try_consumer().is_some());
+}
+
+#[test]
+#[should_panic(expected = "the buffer already has a producer")]
+fn test_second_producer() {
+    let rb = SpscRb::<u8>::new(4);
+    let _producer = rb.producer();
+    let _ = rb.producer();
+}
+
+#[test]
+#[should_panic(expected = "the buffer already has a consumer")]
+fn test_second_consumer() {
+    let rb = SpscRb::<u8>::new(4);
+    let _consumer = rb.consumer();
+    let _ = rb.consumer();
+}
+
+#[test]
+fn test_into_split() {
+    let (producer, consumer) = SpscRb::new(4).into_split();
+    assert_eq!(producer.write(&[1, 2, 3, 4, 5]).unwrap(), 4);
+    assert_eq!(consumer.read_vec(8), vec![1, 2, 3, 4]);
+    drop(producer);
+    match consumer.read(&mut [0]) {
+        Err(RbError::Disconnected) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+}
+
+#[test]
+fn test_builder() {
+    let recorder = Arc::new(Mutex::new(0));
+    struct Writes(Arc<Mutex<usize>>);
+    impl RbHooks for Writes {
+        fn on_write(&self, n: usize) {
+            *self.0.lock().unwrap() += n;
+        }
+    }
+
+    let rb = SpscRb::builder()
+        .capacity(4)
+        .fill(7u8)
+        .overwrite_oldest()
+        .wait_strategy(WaitStrategy::Spin)
+        .with_stats()
+        .with_histogram(5)
+        .with_hooks(Writes(recorder.clone()))
+        .build();
+    assert_eq!(rb.capacity(), 4);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    // the dropped grant publishes the filled slots
+    assert_eq!(*producer.grant(3).unwrap(), [7, 7, 7]);
+    assert_eq!(producer.write(&[1, 2, 3, 4, 5, 6]).unwrap(), 6);
+    assert_eq!(consumer.read_vec(8), vec![3, 4, 5, 6]);
+    assert_eq!(rb.stats().written, 7);
+    assert_eq!(rb.histogram().iter().sum::<usize>(), 3);
+    assert_eq!(*recorder.lock().unwrap(), 7);
+}
+
+#[test]
+#[should_panic(expected = "the capacity of the buffer must be set")]
+fn test_builder_without_capacity() {
+    SpscRb::<u8>::builder().build();
+}
+
+#[test]
+fn test_new_with() {
+    let rb = SpscRb::new_with(4, -1i8);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    producer.write(&[1, 2]).unwrap();
+    let mut grant = producer.grant(4).unwrap();
+    assert_eq!(*grant, [-1, -1]);
+    grant[0] = 3;
+    grant.commit(1);
+    assert_eq!(consumer.read_vec(4), vec![1, 2, 3]);
+    // the slots after the read ones keep the value
+    assert_eq!(*producer.grant(4).unwrap(), [-1, -1]);
+}
+
+#[test]
+fn test_from_vec() {
+    let rb = SpscRb::from_vec(vec![1, 2, 3], 4);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    assert_eq!(rb.count(), 3);
+    assert_eq!(producer.write(&[4, 5]).unwrap(), 1);
+    assert_eq!(consumer.read_vec(8), vec![1, 2, 3, 4]);
+    assert_eq!(producer.write(&[6, 7]).unwrap(), 2);
+    assert_eq!(consumer.read_vec(8), vec![6, 7]);
+
+    let rb = SpscRb::from_vec(vec![1, 2], 2);
+    assert!(rb.is_full());
+    assert_eq!(rb.consumer().read_vec(4), vec![1, 2]);
+}
+
+#[test]
+#[should_panic(expected = "the data exceeds the capacity of the buffer")]
+fn test_from_vec_exceeding_capacity() {
+    SpscRb::from_vec(vec![1, 2, 3], 2);
+}
+
+#[test]
+fn test_try_new() {
+    match SpscRb::<u8>::try_new(0) {
+        Err(CapacityError::Zero) => {}
+        _ => panic!("No error or incorrect error"),
+    }
+    match SpscRb::<u8>::try_new(usize::MAX) {
+        Err(CapacityError::TooLarge) => {}
+        _ => panic!("No error or incorrect error"),
+    }
+    match SpscRb::<[u8; 16]>::try_new(usize::MAX / 16) {
+        Err(CapacityError::TooLarge) => {}
+        _ => panic!("No error or incorrect error"),
+    }
+    assert_eq!(
+        SpscRb::<()>::try_new(usize::MAX - 1).unwrap().capacity(),
+        usize::MAX - 1
+    );
+    assert_eq!(SpscRb::<u8>::try_new(3).unwrap().capacity(), 3);
+}
+
+#[test]
+#[should_panic(expected = "The capacity of the buffer is zero")]
+fn test_new_zero() {
+    SpscRb::<u8>::new(0);
+}
+
+#[test]
+fn test_resize() {
+    let rb = SpscRb::new(4);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    // the pending values wrap around the end of the ring
+    producer.write(&[0, 0, 0]).unwrap();
+    consumer.skip(3).unwrap();
+    producer.write(&[1, 2, 3, 4]).unwrap();
+
+    match rb.resize(3) {
+        Err(RbError::Full) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    rb.resize(6).unwrap();
+    assert_eq!(rb.capacity(), 6);
+    assert_eq!(rb.count(), 4);
+    assert_eq!(producer.write(&[5, 6, 7]).unwrap(), 2);
+    assert_eq!(consumer.read_vec(3), vec![1, 2, 3]);
+
+    rb.resize(3).unwrap();
+    assert_eq!(consumer.capacity(), 3);
+    assert!(producer.is_full());
+    assert_eq!(consumer.read_vec(4), vec![4, 5, 6]);
+    assert_eq!(producer.write(&[7, 8, 9, 10]).unwrap(), 3);
+    assert_eq!(consumer.read_vec(4), vec![7, 8, 9]);
+}
+
+#[test]
+fn test_grow() {
+    let rb = SpscRb::new(2).with_overflow_policy(OverflowPolicy::Grow { max: 10 });
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    producer.write(&[0]).unwrap();
+    consumer.skip(1).unwrap();
+    assert_eq!(producer.write(&[1, 2]).unwrap(), 2);
+    assert_eq!(rb.capacity(), 2);
+
+    // doubled twice, the pending values wrapped around the end of the ring
+    assert_eq!(producer.write(&[3, 4, 5]).unwrap(), 3);
+    assert_eq!(rb.capacity(), 8);
+    assert_eq!(consumer.read_vec(2), vec![1, 2]);
+
+    // capped at the maximum
+    assert_eq!(producer.write(&[6; 10]).unwrap(), 7);
+    assert_eq!(rb.capacity(), 10);
+    match producer.write(&[7]) {
+        Err(RbError::Full) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    assert_eq!(consumer.read_vec(10), vec![3, 4, 5, 6, 6, 6, 6, 6, 6, 6]);
+}
+
+#[test]
+fn test_pool() {
+    let pool = RbPool::new(4, 1);
+    assert_eq!((pool.len(), pool.idle(), pool.capacity()), (1, 1, 4));
+    let (producer, consumer) = pool.get();
+    producer.write(&[1, 2, 3]).unwrap();
+    consumer.close();
+    assert_eq!(pool.idle(), 0);
+
+    // all buffers are in use, thus the pool grows
+    let (producer2, consumer2) = pool.get();
+    assert_eq!(pool.len(), 2);
+    drop(producer2);
+    // only reclaimed once both handles are dropped
+    assert_eq!(pool.idle(), 0);
+    drop(consumer2);
+    assert_eq!(pool.idle(), 1);
+
+    drop((producer, consumer));
+    let (producer, consumer) = pool.get();
+    assert!(consumer.is_empty());
+    assert_eq!(producer.write(&[4]).unwrap(), 1);
+    assert_eq!(consumer.read_vec(4), vec![4]);
+    assert_eq!(pool.idle(), 1);
+
+    pool.shrink();
+    assert_eq!((pool.len(), pool.idle()), (1, 0));
+}
+
+#[test]
+fn test_from_storage() {
+    let rb = SpscRb::from_storage(vec![0; 4].into_boxed_slice());
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    assert_eq!(rb.capacity(), 3);
+    assert_eq!(producer.write(&[1, 2]).unwrap(), 2);
+    assert_eq!(consumer.read_vec(1), vec![1]);
+    // wraps around the end of the slice
+    assert_eq!(producer.write(&[3, 4, 5]).unwrap(), 2);
+    assert!(producer.is_full());
+    assert_eq!(consumer.read_vec(4), vec![2, 3, 4]);
+    rb.clear();
+    assert!(rb.is_empty());
+}
+
+#[test]
+fn test_from_raw_parts() {
+    let slots: &'static mut [u8] = Box::leak(vec![0; 3].into_boxed_slice());
+    let (ptr, len) = (slots.as_mut_ptr(), slots.len());
+    let rb = unsafe { SpscRb::from_raw_parts(ptr, len) };
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    assert_eq!(rb.capacity(), 2);
+    assert_eq!(producer.write(&[1, 2, 3]).unwrap(), 2);
+    assert_eq!(consumer.read_vec(4), vec![1, 2]);
+    assert_eq!(producer.write(&[4]).unwrap(), 1);
+    assert_eq!(consumer.read_vec(4), vec![4]);
+}
+
+#[test]
+fn test_new_aligned() {
+    let rb = SpscRb::new_aligned(3, 4096);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    assert_eq!(rb.capacity(), 3);
+    producer
+        .write(&[String::from("a"), String::from("b")])
+        .unwrap();
+    consumer.skip(2).unwrap();
+    // wraps around the end of the slots
+    let data = [String::from("c"), String::from("d"), String::from("e")];
+    assert_eq!(producer.write(&data).unwrap(), 3);
+    assert_eq!(consumer.read_vec(4), data);
+
+    let rb = SpscRb::<(), _>::new_aligned(2, 64);
+    assert_eq!(rb.producer().write(&[(), (), ()]).unwrap(), 2);
+}
+
+#[test]
+fn test_tee() {
+    let (first, second) = (SpscRb::new(4), SpscRb::new(2));
+    let mut tee = Tee::new(vec![first.producer()]);
+    tee.push(second.producer());
+    assert_eq!(tee.len(), 2);
+    let (first_consumer, second_consumer) = (first.consumer(), second.consumer());
+    assert_eq!(tee.write(&[]).unwrap(), 0);
+    assert_eq!(tee.write(&[1, 2, 3]).unwrap(), 2);
+    // a full buffer doesn't hold back the others
+    assert_eq!(tee.write(&[4]).unwrap(), 0);
+    assert_eq!((tee.shortfall(0), tee.shortfall(1)), (0, 2));
+    assert_eq!(first_consumer.read_vec(4), [1, 2, 3, 4]);
+    assert_eq!(second_consumer.read_vec(4), [1, 2]);
+
+    // disconnected buffers are skipped
+    drop((second, second_consumer));
+    assert_eq!(tee.write(&[5, 6]).unwrap(), 2);
+    assert_eq!(tee.shortfall(1), 2);
+    drop((first, first_consumer));
+    match tee.write(&[7]) {
+        Err(RbError::Disconnected) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    assert_eq!(tee.into_inner().len(), 2);
+}
+
+#[test]
+fn test_tee_drop_newest() {
+    let first = SpscRb::new(4);
+    let second = SpscRb::new(2).with_overflow_policy(OverflowPolicy::DropNewest);
+    let tee = Tee::new(vec![first.producer(), second.producer()]);
+    // the values that were discarded are not stored
+    assert_eq!(tee.write(&[1, 2, 3]).unwrap(), 2);
+    assert_eq!(tee.write(&[4]).unwrap(), 0);
+    assert_eq!((tee.shortfall(0), tee.shortfall(1)), (0, 2));
+    assert_eq!(first.consumer().read_vec(4), [1, 2, 3, 4]);
+    assert_eq!(second.consumer().read_vec(4), [1, 2]);
+    assert_eq!(second.consumer().dropped(), 2);
+}
@@ -1,6 +1,9 @@
+#![cfg(feature = "std")]
+
 extern crate rb;
 
-use rb::{RbConsumer, RbInspector, RbProducer, SpscRb, RB};
+use rb::{MpmcRb, MpscRb, RbConsumer, RbError, RbInspector, RbProducer, SpscRb, WaitStrategy, RB};
+use std::sync::Arc;
 use std::{thread, time::Duration};
 
 #[test]
@@ -26,8 +29,9 @@ fn test_threads() {
     const READ_BUF_SIZE: usize = 8;
     for _ in 0..(size / READ_BUF_SIZE) {
         let mut buf = [0; READ_BUF_SIZE];
-        // TODO: Remove the busy wait by providing blocking receive and write functions
-        while rb.count() < READ_BUF_SIZE {}
+        consumer
+            .wait_for_count(READ_BUF_SIZE, Duration::MAX)
+            .unwrap();
         let cnt = consumer.read(&mut buf).unwrap();
         assert_eq!(cnt, READ_BUF_SIZE);
         out_data.extend(buf.iter().cloned());
@@ -132,3 +136,596 @@ fn test_threads_count_underflow() {
         }
     }
 }
+
+#[test]
+fn test_threads_non_blocking_sequence() {
+    // Producer and consumer only synchronize through the positions, a consumer that observes
+    // an advanced write position must also observe the data written before.
+    const SIZE: usize = 7;
+    const ITERATIONS: usize = 100_000;
+    let rb = SpscRb::new(SIZE);
+    let producer = rb.producer();
+    let consumer = rb.consumer();
+
+    let handle = thread::spawn(move || {
+        let mut next = 0;
+        while next < ITERATIONS {
+            let data = [next, next + 1, next + 2];
+            match producer.write(&data) {
+                Ok(cnt) => next += cnt,
+                Err(_) => thread::yield_now(),
+            }
+        }
+    });
+
+    let mut expected = 0;
+    let mut buf = [0; 5];
+    while expected < ITERATIONS {
+        match consumer.read(&mut buf) {
+            Ok(cnt) => {
+                for v in &buf[..cnt] {
+                    assert_eq!(*v, expected);
+                    expected += 1;
+                }
+            }
+            Err(_) => thread::yield_now(),
+        }
+    }
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_threads_skip_pending_count() {
+    const SIZE: usize = 64;
+    const ITERATIONS: usize = 100_000;
+    let rb = SpscRb::new(SIZE);
+    let producer = rb.producer();
+    let consumer = rb.consumer();
+
+    let handle = thread::spawn(move || {
+        let data = [1u8; 3];
+        let mut written = 0;
+        while written < ITERATIONS {
+            match producer.write(&data) {
+                Ok(cnt) => written += cnt,
+                Err(_) => thread::yield_now(),
+            }
+        }
+        written
+    });
+
+    // `skip_pending` must report exactly the number of elements it skipped,
+    // even if the producer writes concurrently.
+    let mut skipped = 0;
+    while skipped < ITERATIONS {
+        match consumer.skip_pending() {
+            Ok(cnt) => skipped += cnt,
+            Err(_) => thread::yield_now(),
+        }
+    }
+    let written = handle.join().unwrap();
+    assert_eq!(skipped + consumer.skip_pending().unwrap_or(0), written);
+}
+
+#[test]
+fn test_threads_mpsc() {
+    const PRODUCERS: usize = 4;
+    const SIZE: usize = 64;
+    const PER_PRODUCER: usize = 10_000;
+    let rb = MpscRb::new(SIZE);
+    let consumer = rb.consumer();
+    let producer = rb.producer();
+
+    let handles = (0..PRODUCERS)
+        .map(|id| {
+            let producer = producer.clone();
+            thread::spawn(move || {
+                let data = (0..PER_PRODUCER)
+                    .map(|i| id * PER_PRODUCER + i)
+                    .collect::<Vec<_>>();
+                let mut written = 0;
+                while written < data.len() {
+                    written += producer.write_blocking(&data[written..]).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    drop(producer);
+
+    // values of each producer must arrive complete and in order
+    let mut next = (0..PRODUCERS)
+        .map(|id| id * PER_PRODUCER)
+        .collect::<Vec<_>>();
+    let mut buf = [0; 16];
+    let mut received = 0;
+    while received < PRODUCERS * PER_PRODUCER {
+        let cnt = consumer
+            .read_blocking_timeout(&mut buf, Duration::from_millis(100))
+            .unwrap_or(Some(0))
+            .unwrap();
+        for v in &buf[..cnt] {
+            let id = v / PER_PRODUCER;
+            assert_eq!(*v, next[id]);
+            next[id] += 1;
+        }
+        received += cnt;
+    }
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert!(rb.is_empty());
+}
+
+#[test]
+fn test_threads_mpmc() {
+    const WORKERS: usize = 3;
+    const SIZE: usize = 32;
+    const PER_PRODUCER: usize = 5_000;
+    let rb = MpmcRb::new(SIZE);
+
+    let producers = (0..WORKERS)
+        .map(|id| {
+            let producer = rb.producer();
+            thread::spawn(move || {
+                let data = (0..PER_PRODUCER)
+                    .map(|i| id * PER_PRODUCER + i)
+                    .collect::<Vec<_>>();
+                let mut written = 0;
+                while written < data.len() {
+                    written += producer.write_blocking(&data[written..]).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    let consumers = (0..WORKERS)
+        .map(|_| {
+            let consumer = rb.consumer();
+            thread::spawn(move || {
+                let mut out = Vec::new();
+                let mut buf = [0; 8];
+                while let Ok(Some(cnt)) =
+                    consumer.read_blocking_timeout(&mut buf, Duration::from_millis(200))
+                {
+                    out.extend_from_slice(&buf[..cnt]);
+                }
+                out
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    // every element must be delivered to exactly one consumer
+    let mut out_data = consumers
+        .into_iter()
+        .flat_map(|consumer| consumer.join().unwrap())
+        .collect::<Vec<_>>();
+    out_data.sort();
+    assert_eq!(out_data, (0..WORKERS * PER_PRODUCER).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_threads_extend() {
+    const SIZE: usize = 4;
+    let rb = SpscRb::new(SIZE);
+    let (consumer, mut producer) = (rb.consumer(), rb.producer());
+    let handle = thread::spawn(move || producer.extend(0..100));
+    let mut out_data = Vec::new();
+    let mut buf = [0; SIZE];
+    while out_data.len() < 100 {
+        let cnt = consumer.read_blocking(&mut buf).unwrap();
+        out_data.extend_from_slice(&buf[..cnt]);
+    }
+    handle.join().unwrap();
+    assert_eq!(out_data, (0..100).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_threads_read_at_least() {
+    const SIZE: usize = 64;
+    const WINDOW: usize = 16;
+    let rb = SpscRb::new(SIZE);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    let handle = thread::spawn(move || {
+        for i in 0..(4 * WINDOW) {
+            producer.write_blocking(&[i]).unwrap();
+        }
+    });
+    let mut out_data = Vec::new();
+    let mut buf = [0; SIZE];
+    while out_data.len() < 4 * WINDOW {
+        let cnt = consumer.read_at_least(&mut buf, WINDOW).unwrap();
+        assert!(cnt >= WINDOW);
+        out_data.extend_from_slice(&buf[..cnt]);
+    }
+    handle.join().unwrap();
+    assert_eq!(out_data, (0..(4 * WINDOW)).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_threads_producer_dropped() {
+    const SIZE: usize = 4;
+    let (producer, consumer) = SpscRb::new(SIZE).into_split();
+    let handle = thread::spawn(move || {
+        let mut out_data = Vec::new();
+        let mut buf = [0; SIZE];
+        while let Some(cnt) = consumer.read_blocking(&mut buf) {
+            out_data.extend_from_slice(&buf[..cnt]);
+        }
+        out_data
+    });
+    for i in 0..10 {
+        producer.write_blocking(&[i]).unwrap();
+    }
+    drop(producer);
+    assert_eq!(handle.join().unwrap(), (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_threads_close() {
+    const SIZE: usize = 4;
+    let rb = SpscRb::new(SIZE);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    producer.write(&[0; SIZE]).unwrap();
+    // blocks since the buffer is full
+    let writer = thread::spawn(move || producer.write_blocking(&[1]));
+    thread::sleep(Duration::from_millis(10));
+    rb.close();
+    assert_eq!(writer.join().unwrap(), None);
+    // the pending values can still be read
+    let mut buf = [0; SIZE];
+    assert_eq!(consumer.read_blocking(&mut buf), Some(SIZE));
+    assert_eq!(consumer.read_blocking(&mut buf), None);
+}
+
+#[test]
+fn test_threads_wait_strategies() {
+    const SIZE: usize = 16;
+    for &wait_strategy in &[
+        WaitStrategy::Spin,
+        WaitStrategy::SpinYield { spins: 10 },
+        WaitStrategy::SpinBlock { spins: 10 },
+        WaitStrategy::Park,
+    ] {
+        let (producer, consumer) = SpscRb::new(SIZE)
+            .with_wait_strategy(wait_strategy)
+            .into_split();
+        let handle = thread::spawn(move || {
+            for i in 0..(8 * SIZE) {
+                producer.write_blocking(&[i]).unwrap();
+            }
+        });
+        let mut out_data = Vec::new();
+        let mut buf = [0; SIZE];
+        while let Some(cnt) = consumer.read_blocking(&mut buf) {
+            out_data.extend_from_slice(&buf[..cnt]);
+        }
+        handle.join().unwrap();
+        assert_eq!(out_data, (0..(8 * SIZE)).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn test_threads_park_timeout() {
+    let (producer, consumer) = SpscRb::<u8>::new(1)
+        .with_wait_strategy(WaitStrategy::Park)
+        .into_split();
+    match consumer.read_blocking_timeout(&mut [0], Duration::from_millis(10)) {
+        Err(RbError::TimedOut) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    producer.write(&[1]).unwrap();
+    match producer.write_blocking_timeout(&[2], Duration::from_millis(10)) {
+        Err(RbError::TimedOut) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    let handle = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(10));
+        drop(producer);
+    });
+    let mut buf = [0; 1];
+    assert_eq!(consumer.read_blocking(&mut buf), Some(1));
+    assert_eq!(consumer.read_blocking(&mut buf), None);
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_threads_park_more_threads_than_slots() {
+    const CONSUMERS: usize = 12;
+    let rb = MpmcRb::new(CONSUMERS).with_wait_strategy(WaitStrategy::Park);
+    let consumers = (0..CONSUMERS)
+        .map(|_| {
+            let consumer = rb.consumer();
+            thread::spawn(move || {
+                let mut buf = [0; 1];
+                consumer.read_blocking(&mut buf).map(|_| buf[0])
+            })
+        })
+        .collect::<Vec<_>>();
+    // The threads that found no free slot poll instead of being unparked.
+    thread::sleep(Duration::from_millis(10));
+    let producer = rb.producer();
+    producer.write(&(0..CONSUMERS).collect::<Vec<_>>()).unwrap();
+    let mut out = consumers
+        .into_iter()
+        .map(|consumer| consumer.join().unwrap().unwrap())
+        .collect::<Vec<_>>();
+    out.sort();
+    assert_eq!(out, (0..CONSUMERS).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_threads_stress_wakeups() {
+    const WORKERS: usize = 3;
+    const PER_PRODUCER: usize = 2_000;
+    for &wait_strategy in &[
+        WaitStrategy::Block,
+        WaitStrategy::SpinBlock { spins: 1 },
+        WaitStrategy::Park,
+    ] {
+        // A single slot makes the handles wake up far more often than they can make progress.
+        let rb = MpmcRb::new(1).with_wait_strategy(wait_strategy);
+        let producers = (0..WORKERS)
+            .map(|id| {
+                let producer = rb.producer();
+                thread::spawn(move || {
+                    for i in 0..PER_PRODUCER {
+                        assert_eq!(producer.write_blocking(&[id * PER_PRODUCER + i]), Some(1));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        let consumers = (0..WORKERS)
+            .map(|_| {
+                let consumer = rb.consumer();
+                thread::spawn(move || {
+                    let mut out = Vec::new();
+                    let mut buf = [0; 4];
+                    while let Some(cnt) = consumer.read_blocking(&mut buf) {
+                        assert!(cnt > 0, "woke up without pending values");
+                        out.extend_from_slice(&buf[..cnt]);
+                    }
+                    out
+                })
+            })
+            .collect::<Vec<_>>();
+        drop(rb);
+
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        let mut out_data = consumers
+            .into_iter()
+            .flat_map(|consumer| consumer.join().unwrap())
+            .collect::<Vec<_>>();
+        out_data.sort();
+        assert_eq!(out_data, (0..WORKERS * PER_PRODUCER).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn test_threads_shared_consumer_wakeups() {
+    let rb = SpscRb::new(8);
+    let (consumer, producer) = (Arc::new(rb.consumer()), rb.producer());
+    // Both threads block on the same consumer, but only one of them can proceed
+    // after the first write.
+    let at_least = {
+        let consumer = consumer.clone();
+        thread::spawn(move || consumer.read_at_least(&mut [0; 4], 4))
+    };
+    let single = {
+        let consumer = consumer.clone();
+        thread::spawn(move || consumer.read_blocking(&mut [0; 1]))
+    };
+    thread::sleep(Duration::from_millis(20));
+    producer.write(&[1]).unwrap();
+    assert_eq!(single.join().unwrap(), Some(1));
+    producer.write(&[2, 3, 4, 5]).unwrap();
+    assert_eq!(at_least.join().unwrap(), Some(4));
+}
+
+#[test]
+fn test_threads_wait_for_count() {
+    let (producer, consumer) = SpscRb::new(8).into_split();
+    match consumer.wait_for_count(1, Duration::from_millis(10)) {
+        Err(RbError::TimedOut) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    let handle = thread::spawn(move || {
+        for i in 0..4 {
+            producer.write_blocking(&[i]).unwrap();
+            thread::sleep(Duration::from_millis(1));
+        }
+    });
+    assert!(consumer.wait_for_count(4, Duration::MAX).unwrap() >= 4);
+    // nothing was read
+    assert_eq!(consumer.read_vec(8), vec![0, 1, 2, 3]);
+    handle.join().unwrap();
+    match consumer.wait_for_count(1, Duration::MAX) {
+        Err(RbError::Disconnected) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+
+    // `n` is limited to the capacity
+    let rb = SpscRb::new(2);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    producer.write(&[1, 2]).unwrap();
+    assert_eq!(consumer.wait_for_count(100, Duration::MAX).unwrap(), 2);
+}
+
+#[test]
+fn test_threads_wait_for_slots() {
+    let (producer, consumer) = SpscRb::new(8).into_split();
+    producer.write(&[0; 8]).unwrap();
+    match producer.wait_for_slots(1, Duration::from_millis(10)) {
+        Err(RbError::TimedOut) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    let handle = thread::spawn(move || {
+        for _ in 0..4 {
+            consumer.skip(1).unwrap();
+            thread::sleep(Duration::from_millis(1));
+        }
+    });
+    assert!(producer.wait_for_slots(4, Duration::MAX).unwrap() >= 4);
+    // the whole block fits
+    producer.write_exact(&[1; 4]).unwrap();
+    handle.join().unwrap();
+    match producer.wait_for_slots(100, Duration::MAX) {
+        Err(RbError::Disconnected) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+}
+
+#[test]
+fn test_threads_flush() {
+    let rb = SpscRb::new(8);
+    let (consumer, producer) = (rb.consumer(), rb.producer());
+    producer.write(&[0; 8]).unwrap();
+    match producer.flush_timeout(Duration::from_millis(10)) {
+        Err(RbError::TimedOut) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    let handle = thread::spawn(move || {
+        for _ in 0..4 {
+            thread::sleep(Duration::from_millis(1));
+            consumer.skip(2).unwrap();
+        }
+        consumer
+    });
+    producer.flush().unwrap();
+    assert!(rb.is_empty());
+
+    producer.write(&[0; 2]).unwrap();
+    drop((rb, handle.join().unwrap()));
+    match producer.flush() {
+        Err(RbError::Disconnected) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+}
+
+#[test]
+fn test_threads_select() {
+    let rbs = (0..3).map(|_| SpscRb::new(4)).collect::<Vec<_>>();
+    let consumers = rbs.iter().map(|rb| rb.consumer()).collect::<Vec<_>>();
+    let refs = consumers.iter().collect::<Vec<_>>();
+    match rb::select(&refs, Duration::from_millis(10)) {
+        Err(RbError::TimedOut) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    let producers = rbs.iter().map(|rb| rb.producer()).collect::<Vec<_>>();
+    // no more handles can be created, the consumers disconnect with the producers
+    drop(rbs);
+    let handle = thread::spawn(move || {
+        for i in 0..30 {
+            producers[i % 3].write_blocking(&[i]).unwrap();
+            thread::sleep(Duration::from_millis(1));
+        }
+    });
+    let mut data = Vec::new();
+    loop {
+        match rb::select(&refs, Duration::MAX) {
+            Ok(idx) => data.extend(consumers[idx].read_vec(4)),
+            Err(RbError::Disconnected) => break,
+            v => panic!("No error or incorrect error: {:?}", v),
+        }
+    }
+    handle.join().unwrap();
+    data.sort();
+    assert_eq!(data, (0..30).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_threads_channel() {
+    use std::sync::mpsc::{RecvTimeoutError, TryRecvError, TrySendError};
+
+    let (tx, rx) = rb::channel(2);
+    match rx.try_recv() {
+        Err(TryRecvError::Empty) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    tx.try_send(1).unwrap();
+    tx.try_send(2).unwrap();
+    match tx.try_send(3) {
+        Err(TrySendError::Full(3)) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+    assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec![1, 2]);
+    match rx.recv_timeout(Duration::from_millis(10)) {
+        Err(RecvTimeoutError::Timeout) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+
+    let handles = (0..4)
+        .map(|i| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for j in 0..100 {
+                    tx.send(i * 100 + j).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    drop(tx);
+    let mut received = rx.iter().collect::<Vec<_>>();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    received.sort();
+    assert_eq!(received, (0..400).collect::<Vec<_>>());
+    match rx.recv() {
+        Err(_) => {}
+        v => panic!("No error or incorrect error: {:?}", v),
+    }
+
+    let (tx, rx) = rb::channel(1);
+    drop(rx);
+    assert_eq!(tx.send(1).unwrap_err().0, 1);
+}
+
+#[test]
+fn test_threads_pump_from_receiver() {
+    use std::sync::mpsc;
+
+    // larger than the buffer, the bridge blocks while it is full
+    let (tx, rx) = mpsc::sync_channel(1000);
+    for i in 0..1000 {
+        tx.send(i).unwrap();
+    }
+    let rb = SpscRb::new(16);
+    let (producer, consumer) = (rb.producer(), rb.consumer());
+    let bridge = thread::spawn(move || {
+        let forwarded = rb::pump_from_receiver(&rx, &producer);
+        (forwarded, rx)
+    });
+    let mut data = Vec::new();
+    let mut buf = [0; 7];
+    while data.len() < 1000 {
+        let cnt = consumer.read_blocking(&mut buf).unwrap();
+        data.extend_from_slice(&buf[..cnt]);
+    }
+    assert_eq!(data, (0..1000).collect::<Vec<_>>());
+
+    // a disconnected buffer stops the bridge, the other values stay in the channel
+    tx.send(1000).unwrap();
+    drop(consumer);
+    tx.send(1001).unwrap();
+    drop(tx);
+    let (forwarded, rx) = bridge.join().unwrap();
+    assert!(forwarded >= 1000);
+    assert!(rx.iter().count() <= 1);
+}
+
+#[test]
+fn test_threads_resize() {
+    let rb = SpscRb::new(2);
+    let (producer, consumer) = (rb.producer(), rb.consumer());
+    producer.write(&[1, 2]).unwrap();
+    let writer = thread::spawn(move || producer.write_blocking(&[3, 4]));
+    thread::sleep(Duration::from_millis(10));
+    // growing the buffer wakes up the blocked producer
+    rb.resize(4).unwrap();
+    assert_eq!(writer.join().unwrap(), Some(2));
+    assert_eq!(consumer.read_vec(4), vec![1, 2, 3, 4]);
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "tracing")]
+
+extern crate rb;
+extern crate tracing;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rb::{RbProducer, SpscRb, RB};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// Records the names of the spans and the messages of the events.
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<String>>>);
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        self.0
+            .lock()
+            .unwrap()
+            .push(span.metadata().name().to_string());
+        Id::from_u64(1)
+    }
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+    fn event(&self, event: &Event<'_>) {
+        struct Message<'a>(&'a mut Vec<String>);
+        impl<'a> tracing::field::Visit for Message<'a> {
+            fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+                if field.name() == "message" {
+                    self.0.push(format!("{:?}", value));
+                }
+            }
+        }
+        event.record(&mut Message(&mut self.0.lock().unwrap()));
+    }
+    fn enter(&self, _: &Id) {}
+    fn exit(&self, _: &Id) {}
+}
+
+#[test]
+fn test_tracing() {
+    let recorder = Recorder::default();
+    tracing::subscriber::with_default(recorder.clone(), || {
+        let rb = SpscRb::new(2);
+        let (_consumer, producer) = (rb.consumer(), rb.producer());
+        assert_eq!(producer.write(&[1, 2, 3]).unwrap(), 2);
+        assert!(producer
+            .write_blocking_timeout(&[3], Duration::from_millis(1))
+            .is_err());
+        rb.clear();
+        rb.close();
+    });
+    let recorded = recorder.0.lock().unwrap();
+    assert_eq!(recorded[0], "ring buffer write truncated");
+    assert!(recorded.contains(&"ring buffer wait".to_string()));
+    assert_eq!(
+        recorded[recorded.len() - 2..],
+        ["ring buffer cleared", "ring buffer closed"]
+    );
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "std")]
+
+extern crate rb;
+
+use std::sync::mpsc;
+use std::time::Duration;
+
+use rb::{RbConsumer, RbProducer, SpscRb, XrunKind, RB};
+
+#[test]
+fn test_watchdog_poll() {
+    let rb = SpscRb::new(2);
+    let (producer, consumer) = (rb.producer(), rb.consumer());
+    // only the xruns after its creation are reported
+    assert!(consumer.read(&mut [0]).is_err());
+    let mut watchdog = rb.watchdog().with_history(2);
+    assert!(watchdog.poll().is_empty());
+
+    assert_eq!(producer.write(&[1, 2, 3, 4]).unwrap(), 2);
+    consumer.read_vec(2);
+    assert!(consumer.read(&mut [0]).is_err());
+    let xruns = watchdog.poll();
+    assert_eq!(
+        xruns.iter().map(|x| (x.kind, x.count)).collect::<Vec<_>>(),
+        [(XrunKind::Overrun, 2), (XrunKind::Underrun, 1)]
+    );
+    assert!(watchdog.poll().is_empty());
+
+    // a reset of the counters doesn't hide new xruns
+    rb.reset_xruns();
+    assert!(consumer.read(&mut [0]).is_err());
+    let xruns = watchdog.poll();
+    assert_eq!((xruns[0].kind, xruns[0].count), (XrunKind::Underrun, 1));
+    assert_eq!(watchdog.total(XrunKind::Overrun), 2);
+    assert_eq!(watchdog.total(XrunKind::Underrun), 2);
+    // the oldest xrun was dropped from the history
+    let history: Vec<_> = watchdog.history().map(|x| (x.kind, x.count)).collect();
+    assert_eq!(history, [(XrunKind::Underrun, 1), (XrunKind::Underrun, 1)]);
+}
+
+#[test]
+fn test_watchdog_spawn() {
+    let rb = SpscRb::new(2);
+    let producer = rb.producer();
+    let (tx, rx) = mpsc::channel();
+    let thread = rb.watchdog().spawn(Duration::from_millis(1), move |xrun| {
+        tx.send(*xrun).unwrap()
+    });
+    assert_eq!(producer.write(&[1, 2, 3]).unwrap(), 2);
+    let xrun = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!((xrun.kind, xrun.count), (XrunKind::Overrun, 1));
+    // the xruns right before the stop are still reported
+    assert!(producer.write(&[4]).is_err());
+    let watchdog = thread.stop();
+    assert_eq!(watchdog.total(XrunKind::Overrun), 2);
+    assert_eq!(rx.try_iter().map(|xrun| xrun.count).sum::<usize>(), 1);
+}
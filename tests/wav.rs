@@ -0,0 +1,62 @@
+#![cfg(feature = "hound")]
+
+extern crate hound;
+extern crate rb;
+
+use std::env;
+use std::fs;
+
+use rb::{RbProducer, SpscRb, WavDrain, RB};
+
+fn spec(bits_per_sample: u16, sample_format: hound::SampleFormat) -> hound::WavSpec {
+    hound::WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample,
+        sample_format,
+    }
+}
+
+#[test]
+fn test_drain_f32() {
+    const SIZE: usize = 256;
+    let path = env::temp_dir().join("rb_test_drain_f32.wav");
+    let rb = SpscRb::<f32>::new(SIZE);
+    let producer = rb.producer();
+    let drain =
+        WavDrain::create(&path, spec(32, hound::SampleFormat::Float), rb.consumer()).unwrap();
+    let in_data = (0..SIZE * 4).map(|i| i as f32 / 1024.0).collect::<Vec<_>>();
+    for chunk in in_data.chunks(SIZE / 4) {
+        producer.write_blocking(chunk).unwrap();
+    }
+    drain.flush().unwrap();
+    drain.stop().unwrap();
+
+    let out_data = hound::WavReader::open(&path)
+        .unwrap()
+        .samples::<f32>()
+        .map(|s| s.unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(in_data, out_data);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_drain_i16_pending_on_stop() {
+    const SIZE: usize = 128;
+    let path = env::temp_dir().join("rb_test_drain_i16.wav");
+    let rb = SpscRb::<i16>::new(SIZE);
+    let in_data = (0..SIZE as i16).collect::<Vec<_>>();
+    // Written before the drain starts, thus still pending when `stop` is called.
+    rb.producer().write(&in_data).unwrap();
+    let drain = WavDrain::create(&path, spec(16, hound::SampleFormat::Int), rb.consumer()).unwrap();
+    drain.stop().unwrap();
+
+    let out_data = hound::WavReader::open(&path)
+        .unwrap()
+        .samples::<i16>()
+        .map(|s| s.unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(in_data, out_data);
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,86 @@
+#![cfg(feature = "zeroize")]
+
+extern crate rb;
+extern crate zeroize;
+
+use std::cell::RefCell;
+
+use rb::{RbConsumer, RbInspector, RbProducer, SpscRb, RB};
+use zeroize::Zeroize;
+
+thread_local! {
+    static SCRUBBED: RefCell<Vec<u32>> = const { RefCell::new(Vec::new()) };
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Secret(u32);
+
+impl Zeroize for Secret {
+    fn zeroize(&mut self) {
+        SCRUBBED.with(|scrubbed| scrubbed.borrow_mut().push(self.0));
+        self.0 = 0;
+    }
+}
+
+fn scrubbed() -> Vec<u32> {
+    SCRUBBED.with(|scrubbed| scrubbed.borrow_mut().drain(..).collect())
+}
+
+#[test]
+fn test_zeroize_clear() {
+    let rb = SpscRb::new_zeroizing(4);
+    let (prod, cons) = (rb.producer(), rb.consumer());
+    prod.write(&[Secret(1), Secret(2), Secret(3)]).unwrap();
+    cons.read(&mut [Secret(0)]).unwrap();
+    rb.clear();
+    assert_eq!(scrubbed(), [1, 2, 3]);
+    assert!(cons.is_empty());
+    drop((prod, cons));
+    drop(rb);
+    assert!(scrubbed().is_empty());
+}
+
+#[test]
+fn test_zeroize_reset() {
+    let rb = SpscRb::new_zeroizing(4);
+    let (prod, cons) = (rb.producer(), rb.consumer());
+    prod.write(&[Secret(1), Secret(2), Secret(3)]).unwrap();
+    cons.read(&mut [Secret(0)]).unwrap();
+    rb.reset();
+    // only the pending values
+    assert_eq!(scrubbed(), [2, 3]);
+    assert!(cons.is_empty());
+}
+
+#[test]
+fn test_zeroize_rollback() {
+    let rb = SpscRb::new_zeroizing(4);
+    let prod = rb.producer();
+    prod.write(&[Secret(1), Secret(2), Secret(3)]).unwrap();
+    assert_eq!(prod.rollback(2), 2);
+    assert_eq!(scrubbed(), [2, 3]);
+    drop(prod);
+    drop(rb);
+    assert_eq!(scrubbed(), [1]);
+}
+
+#[test]
+fn test_zeroize_overwrite() {
+    let rb = SpscRb::new_zeroizing(4);
+    let (prod, cons) = (rb.producer(), rb.consumer());
+    prod.write(&[Secret(1), Secret(2), Secret(3), Secret(4)])
+        .unwrap();
+    assert!(scrubbed().is_empty());
+    assert_eq!(prod.write_overwriting(&[Secret(5), Secret(6)]).unwrap(), 2);
+    // both lost values, also the one whose slot is not reused yet
+    assert_eq!(scrubbed(), [1, 2]);
+    let mut data = vec![Secret(0); 4];
+    assert_eq!(cons.read(&mut data).unwrap(), 4);
+    assert_eq!(data, [Secret(3), Secret(4), Secret(5), Secret(6)]);
+    // the slots of read values are scrubbed before they are reused
+    prod.write(&[Secret(7), Secret(8)]).unwrap();
+    assert_eq!(scrubbed(), [0, 3]);
+    drop((prod, cons));
+    drop(rb);
+    assert_eq!(scrubbed(), [6, 7, 8, 4, 5]);
+}